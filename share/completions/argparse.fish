@@ -79,3 +79,8 @@ complete --command argparse --short-option U --long-option unknown-arguments --n
     --description 'Whether unknown options can have arguments'
 complete --command argparse --short-option s --long-option stop-nonopt \
     --description 'Exit on subcommand'
+complete --command argparse --long-option usage --require-parameter --no-files \
+    --description 'Print help and usage with this synopsis'
+complete --command argparse --long-option complete-for --require-parameter --no-files \
+    --arguments '(__fish_complete_command)' \
+    --description 'Register completions for a command instead'
@@ -168,7 +168,7 @@ complete -c aura -n $sync -s l -l list -xa "$listrepos" -d 'List all packages in
 complete -c aura -n $sync -s u -l sysupgrade -d 'Upgrade all packages that are out of date'
 complete -c aura -n $sync -s w -l downloadonly -d 'Only download the target packages'
 complete -c aura -n $sync -s y -l refresh -d 'Download fresh copy of the package list'
-complete -c aura -n "$sync; and $argument" -xa "$listall $listgroups"
+complete -c aura -n "$sync; and $argument" -xa "$listall $listgroups" --cache-ttl 250
 
 # Upgrade options
 complete -c aura -n "$upgrade; and $argument" -k -a '(__fish_complete_suffix pkg.tar.xz pkg.tar.gz pkg.tar.zst)' -d 'Package file'
@@ -16,10 +16,16 @@ complete -c complete -s e -l erase -d "Remove completion"
 complete -c complete -s h -l help -d "Display help and exit"
 complete -c complete -s C -l do-complete -d "Print completions for a commandline specified as a parameter"
 complete -c complete -l escape -d "Make -C escape special characters"
+complete -c complete -l json -d "Make -C print completions as JSON lines"
 complete -c complete -s n -l condition -d "Completion only used if command has zero exit status" -x
 complete -c complete -s w -l wraps -d "Inherit completions from specified command" -xa '(__fish_complete_command)'
 complete -c complete -s k -l keep-order -d "Keep order of arguments instead of sorting alphabetically"
 complete -c complete -l color -d "When to colorize output" -xa "always never auto"
+complete -c complete -l cache-ttl -d "Cache expanded arguments for this many seconds" -x
+complete -c complete -l cache-key -d "Share cached arguments under this key" -x
+complete -c complete -l clear-cache -d "Forget cached arguments"
+complete -c complete -l import-spec -d "Import completions from a JSON spec" -r
+complete -c complete -l subcommand -d "Only use completion after this subcommand" -x
 
 # Deprecated options
 
@@ -0,0 +1,6 @@
+complete -c coproc -s h -l help -d 'Display help and exit'
+complete -c coproc -n 'not __fish_seen_argument -s c -l close -l close-input' -s n -l name -x -d 'Name of the variable holding the file descriptors'
+complete -c coproc -s c -l close -d 'Close the pipes to a coprocess'
+complete -c coproc -l close-input -d 'Close the pipe to the input of a coprocess'
+complete -c coproc -n '__fish_seen_argument -s c -l close -l close-input' -x -a '(coproc | string replace -r "\t.*" "")'
+complete -c coproc -n 'not __fish_seen_argument -s c -l close -l close-input' -x -a '(__fish_complete_subcommand -- -n --name)'
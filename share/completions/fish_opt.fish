@@ -14,3 +14,4 @@ complete --command fish_opt --short-option r --long-option required-val -n $COND
 complete --command fish_opt --short-option m --long-option multiple-vals --description 'Store all values'
 complete --command fish_opt --short-option d --long-option delete --description 'Delete option from argv_opts'
 complete --command fish_opt --short-option v --long-option validate --require-parameter --description 'Fish script to validate option values'
+complete --command fish_opt --long-option type --no-files --require-parameter --arguments 'int float file dir' --description 'Type of option values'
@@ -63,7 +63,7 @@ complete -f -c flatpak -n "__fish_seen_subcommand_from info uninstall" -a "(__fi
 complete -f -c flatpak -n "__fish_seen_subcommand_from enter kill" -a "(__fish_flatpak ps --columns=instance,application)"
 complete -f -c flatpak -n "__fish_seen_subcommand_from remote-info remote-ls remote-modify remote-delete" -a "(__fish_flatpak remotes --columns=name,title)"
 
-complete -c flatpak -n '__fish_seen_subcommand_from install' -xa "(__fish_cached -t 250 -- 'flatpak remote-ls --columns=application,name' | string replace -rf '^([^A-Z].*?)(?: +|\t)(.*?)\s*\$' '\$1\t\$2')"
+complete -c flatpak -n '__fish_seen_subcommand_from install' -xa "(flatpak remote-ls --columns=application,name | string replace -rf '^([^A-Z].*?)(?: +|\t)(.*?)\s*\$' '\$1\t\$2')" --cache-ttl 250
 
 # Plenty of the other stuff is too free-form to complete (e.g. remote-add).
 complete -f -c flatpak -s h -l help
@@ -0,0 +1,22 @@
+set -l __fish_frecency_all_commands search add delete clear
+
+complete -c frecency -s h -l help -d "Display help and exit"
+
+# Note that these options are only valid with the "search" subcommand.
+complete -c frecency -n "__fish_seen_subcommand_from search; or not __fish_seen_subcommand_from $__fish_frecency_all_commands" \
+    -s n -l max -d "Limit output to the first 'n' matches" -x
+complete -c frecency -n "__fish_seen_subcommand_from search; or not __fish_seen_subcommand_from $__fish_frecency_all_commands" \
+    -s s -l show-score -d "Print the score of each directory"
+
+complete -f -c frecency -n "not __fish_seen_subcommand_from $__fish_frecency_all_commands" \
+    -a search -d "Print visited directories matching keywords"
+complete -f -c frecency -n "not __fish_seen_subcommand_from $__fish_frecency_all_commands" \
+    -a add -d "Record visits of directories"
+complete -f -c frecency -n "not __fish_seen_subcommand_from $__fish_frecency_all_commands" \
+    -a delete -d "Forget directories"
+complete -f -c frecency -n "not __fish_seen_subcommand_from $__fish_frecency_all_commands" \
+    -a clear -d "Forget all directories"
+
+complete -f -c frecency -n "__fish_seen_subcommand_from search clear"
+complete -f -c frecency -n "__fish_seen_subcommand_from add" -a "(__fish_complete_directories)"
+complete -f -c frecency -n "__fish_seen_subcommand_from delete" -a "(builtin frecency 2>/dev/null)"
@@ -0,0 +1,23 @@
+complete -c limit -n __fish_no_arguments -s h -l help -d 'Display help and exit'
+complete -c limit -n __fish_no_arguments -s b -l socket-buffers -x -d 'Maximum size of socket buffers'
+complete -c limit -n __fish_no_arguments -s c -l core-size -x -d 'Maximum size of core files created'
+complete -c limit -n __fish_no_arguments -s d -l data-size -x -d "Maximum size of a process's data segment"
+complete -c limit -n __fish_no_arguments -s e -l nice -x -d 'Control of maximum nice priority'
+complete -c limit -n __fish_no_arguments -s f -l file-size -x -d 'Maximum size of files created'
+complete -c limit -n __fish_no_arguments -s i -l pending-signals -x -d 'Maximum number of pending signals'
+complete -c limit -n __fish_no_arguments -s l -l lock-size -x -d 'Maximum size that may be locked into memory'
+complete -c limit -n __fish_no_arguments -s m -l resident-set-size -x -d 'Maximum resident set size'
+complete -c limit -n __fish_no_arguments -s n -l file-descriptor-count -x -d 'Maximum number of open file descriptors'
+complete -c limit -n __fish_no_arguments -s q -l queue-size -x -d 'Maximum bytes in POSIX message queues'
+complete -c limit -n __fish_no_arguments -s r -l realtime-priority -x -d 'Maximum realtime scheduling priority'
+complete -c limit -n __fish_no_arguments -s s -l stack-size -x -d 'Maximum stack size'
+complete -c limit -n __fish_no_arguments -s t -l cpu-time -x -d 'Maximum amount of cpu time in seconds'
+complete -c limit -n __fish_no_arguments -s u -l process-count -x -d 'Maximum number of processes available to a single user'
+complete -c limit -n __fish_no_arguments -s v -l virtual-memory-size -x -d 'Maximum amount of virtual memory available to each process'
+complete -c limit -n __fish_no_arguments -s w -l swap-size -x -d 'Maximum swap space'
+complete -c limit -n __fish_no_arguments -s y -l realtime-maxtime -x -d 'Maximum contiguous realtime CPU time'
+complete -c limit -n __fish_no_arguments -s K -l kernel-queues -x -d 'Maximum number of kqueues'
+complete -c limit -n __fish_no_arguments -s P -l ptys -x -d 'Maximum number of pseudo-terminals'
+complete -c limit -n __fish_no_arguments -s T -l threads -x -d 'Maximum number of simultaneous threads'
+complete -c limit -n __fish_no_arguments -l niceness -x -d 'Increment the niceness of the command'
+complete -c limit -x -a '(__fish_complete_subcommand -- -b -c -d -e -f -i -l -m -n -q -r -s -t -u -v -w -y -K -P -T --socket-buffers --core-size --data-size --nice --file-size --pending-signals --lock-size --resident-set-size --file-descriptor-count --queue-size --realtime-priority --stack-size --cpu-time --process-count --virtual-memory-size --swap-size --realtime-maxtime --kernel-queues --ptys --threads --niceness)'
@@ -133,7 +133,7 @@ complete -c $progname -n "$sync; and not __fish_contains_opt -s u sysupgrade" -s
 complete -c $progname -n "$sync; and __fish_contains_opt -s u sysupgrade" -s u -l sysupgrade -d 'Also downgrade packages'
 complete -c $progname -n $sync -s w -l downloadonly -d 'Only download the target packages'
 complete -c $progname -n $sync -s y -l refresh -d 'Download fresh copy of the package list'
-complete -c $progname -n "$sync" -xa "$listall $listgroups"
+complete -c $progname -n "$sync" -xa "$listall $listgroups" --cache-ttl 250
 
 # Database options
 set -l has_db_opt '__fish_contains_opt asdeps asexplicit'
@@ -149,7 +149,7 @@ complete -c $progname -n "$files; and not $has_file_opt" -xa -l -d 'List files o
 complete -c $progname -n "$files; and not $has_file_opt" -xa --search -d 'Search packages for matching files'
 complete -c $progname -n "$files; and not $has_file_opt" -xa -s -d 'Search packages for matching files'
 complete -c $progname -n "$files" -s y -l refresh -d 'Refresh the files database' -f
-complete -c $progname -n "$files" -s l -l list -d 'List files owned by given packages' -xa $listall
+complete -c $progname -n "$files" -s l -l list -d 'List files owned by given packages' -xa $listall --cache-ttl 250 --cache-key pacman-packages
 complete -c $progname -n "$files" -s s -l search -d 'Search packages for matching files'
 complete -c $progname -n "$files" -s o -l owns -d 'Search for packages that include the given files'
 complete -c $progname -n "$files" -s q -l quiet -d 'Show less information' -f
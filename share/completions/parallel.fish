@@ -0,0 +1,5 @@
+complete -c parallel -s h -l help -d 'Display help and exit'
+complete -c parallel -s j -l jobs -x -d 'Maximum number of items to run at the same time'
+complete -c parallel -s k -l keep-order -d 'Print output in the order of the items'
+complete -c parallel -l status-var -x -a '(set -n)' -d 'Variable to store the exit statuses in'
+complete -c parallel -x -a '(__fish_complete_subcommand -- -j --jobs --status-var)'
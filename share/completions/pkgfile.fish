@@ -7,6 +7,6 @@ complete -c pkgfile -s r -l regex -d 'allow the use of regex in searches'
 complete -c pkgfile -s R -l repo -d 'search only in the specified repository' -xa '(__fish_print_pacman_repos)'
 complete -c pkgfile -s v -l verbose -d 'enable verbose output'
 complete -c pkgfile -s i -l info -d 'provides information about the package owning a file' -r
-complete -c pkgfile -s l -l list -d 'list files of a given package; similar to "pacman -Ql"' -xa "(__fish_print_pacman_packages)"
+complete -c pkgfile -s l -l list -d 'list files of a given package; similar to "pacman -Ql"' -xa "(__fish_print_pacman_packages)" --cache-ttl 250 --cache-key pacman-packages
 complete -c pkgfile -s s -l search -d 'search which package owns a file' -r
 complete -c pkgfile -s u -l update -d 'update to the latest filelist. This requires write permission to /var/cache/pkgtools/lists'
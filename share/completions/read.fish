@@ -18,3 +18,5 @@ complete -c read -s z -l null -d "Use NUL character as line terminator"
 complete -c read -s L -l line -d "Read each line into its own variable"
 complete -c read -s d -l delimiter -d "Set string to use as delimiter" -x
 complete -c read -s t -l tokenize -d "Use shell tokenization rules when splitting"
+complete -c read -l timeout -d "Give up after the given number of seconds" -x
+complete -c read -l keep-partial -d "Keep input read before the timeout"
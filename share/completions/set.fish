@@ -61,6 +61,8 @@ function __fish_complete_special_vars
         FISH_DEBUG_OUTPUT "debug output path" \
         umask "current file creation mask" \
         fish_ambiguous_width "affects computed width of east asian chars" \
+        fish_async_completion "set to 1 to compute completions in the background" \
+        fish_async_prompt "set to 1 to compute prompts in the background" \
         fish_autosuggestion_enabled "set to 0 to turn autosuggestions off" \
         fish_cursor_end_mode "set to 'inclusive' to disallow moving the cursor beyond the command line end" \
         fish_cursor_selection_mode "set to 'inclusive' if selections should include the cursor" \
@@ -28,8 +28,10 @@ set -l __fish_status_all_commands \
     list-files \
     print-stack-trace \
     stack-trace \
+    strict \
     terminal \
     terminal-os \
+    terminal-report \
     test-feature \
     test-terminal-feature
 
@@ -70,6 +72,7 @@ complete -f -c status -n "not __fish_seen_subcommand_from $__fish_status_all_com
 complete -f -c status -n "not __fish_seen_subcommand_from $__fish_status_all_commands" -a fish-path -d "Print the path to the current instance of fish"
 complete -f -c status -n "not __fish_seen_subcommand_from $__fish_status_all_commands" -a terminal -d "Print name and version of the terminal fish is running in"
 complete -f -c status -n "not __fish_seen_subcommand_from $__fish_status_all_commands" -a terminal-os -d "Print the operating system the terminal is running on"
+complete -f -c status -n "not __fish_seen_subcommand_from $__fish_status_all_commands" -a terminal-report -d "Print all detected terminal capabilities"
 complete -f -c status -n "not __fish_seen_subcommand_from $__fish_status_all_commands" -a test-terminal-feature -d "Test if the terminal suports the given feature"
 complete -f -c status -n "__fish_seen_subcommand_from test-terminal-feature" -a 'scroll-content-up\t"Command for scrolling up terminal contents"'
 
@@ -79,6 +82,12 @@ complete -f -c status -n "__fish_seen_subcommand_from job-control" -a full -d "S
 complete -f -c status -n "__fish_seen_subcommand_from job-control" -a interactive -d "Set only interactive jobs under job control"
 complete -f -c status -n "__fish_seen_subcommand_from job-control" -a none -d "Set no jobs under job control"
 
+# The strict command changes how failing jobs are handled.
+complete -f -c status -n "not __fish_seen_subcommand_from $__fish_status_all_commands" -a strict -d "Abort on failing jobs in the current block"
+complete -f -c status -n "__fish_seen_subcommand_from strict" -a on -d "Abort when a job fails"
+complete -f -c status -n "__fish_seen_subcommand_from strict" -a pipefail -d "Abort when any process of a job fails"
+complete -f -c status -n "__fish_seen_subcommand_from strict" -a off -d "Ignore failing jobs"
+
 complete -f -c status -n "__fish_seen_subcommand_from get-file" -a '(status list-files 2>/dev/null)'
 complete -f -c status -n "__fish_seen_subcommand_from list-files" -a '(status list-files 2>/dev/null)'
 
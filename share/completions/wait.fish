@@ -1,3 +1,6 @@
 complete -c wait -xa '(__fish_complete_job_pids)'
 complete -c wait -s n -l any -d 'Return as soon as the first job completes'
+complete -c wait -s s -l status -d 'Return the exit status of the waited process'
+complete -c wait -s p -l print -d 'Print the pid and exit status of completed processes'
+complete -c wait -s t -l timeout -x -d 'Give up after this many seconds'
 complete -c wait -s h -l help -d 'Display help and exit'
@@ -1,5 +1,5 @@
 function __fish_winetricks__complete_verbs
-    __fish_cached -t 3600 -- 'winetricks list-all 2>/dev/null' |
+    winetricks list-all 2>/dev/null |
         string match --invert --regex '^==' |
         string match --invert --regex '^(apps|dlls|fonts|games|settings)$' |
         string replace --regex '(\S+)\s+(.+)' '$1\t$2'
@@ -70,6 +70,6 @@ for subcommand in benchmarks fonts games settings
         -n '__fish_seen_subcommand_from dlls'
 end
 
-complete -c $command -a '(__fish_winetricks__complete_verbs)' \
+complete -c $command -a '(__fish_winetricks__complete_verbs)' --cache-ttl 3600 \
     -d 'Specify the verb or path to it' \
     -n $root_condition
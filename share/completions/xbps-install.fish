@@ -6,7 +6,7 @@ set -l progname xbps-install
 set -l listall "(__fish_print_xbps_packages)"
 
 complete -c $progname -f
-complete -c $progname -a "$listall"
+complete -c $progname -a "$listall" --cache-ttl 250 --cache-key xbps-packages
 
 complete -c $progname -s A -d 'Install as a dependency'
 complete -c $progname -s C -d 'Use this XBPS configuration directory.' -xa "(__fish_complete_directories)"
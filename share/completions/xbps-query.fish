@@ -42,7 +42,7 @@ end
 
 complete -c $progname -f
 complete -c $progname -a "$listinstalled"
-complete -c $progname -n "__fish_contains_opt -s R" -a "$listall"
+complete -c $progname -n "__fish_contains_opt -s R" -a "$listall" --cache-ttl 250 --cache-key xbps-packages
 
 complete -c $progname -s C -d 'Use this XBPS configuration directory' -xa "(__fish_complete_directories)"
 complete -c $progname -s c -d 'Use this cache directory to store binary packages' -xa "(__fish_complete_directories)"
@@ -69,5 +69,5 @@ complete -c $progname -s s -d 'Search for packages by matching PATTERN on pkgver
 complete -c $progname -s f -d 'Show the package files for PKG' -x
 complete -c $progname -s x -d 'Show the required dependencies for PKG.  Only direct dependencies are shown' -x
 complete -c $progname -s X -d 'Show the reverse dependencies for PKG' -xa "$listinstalled"
-complete -c $progname -s X -d 'Show the reverse dependencies for PKG' -x -n "__fish_contains_opt -s R" -a "$listall"
+complete -c $progname -s X -d 'Show the reverse dependencies for PKG' -x -n "__fish_contains_opt -s R" -a "$listall" --cache-ttl 250 --cache-key xbps-packages
 complete -c $progname -l cat -d 'Prints the file FILE stored in binary package PKG to stdout' -F
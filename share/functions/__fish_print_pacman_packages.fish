@@ -1,6 +1,5 @@
 # localization: skip(private)
 function __fish_print_pacman_packages
-    # Caches for 5 minutes
     type -q -f pacman || return 1
 
     argparse i/installed r/repo=+ -- $argv
@@ -13,7 +12,7 @@ function __fish_print_pacman_packages
         pacman -Sl $_flag_repo | string replace --regex '^(\S+) (\S+) .*' '$1/$2\tPackage'
         return 0
     else
-        __fish_cached -t 250 -- "pacman -Ssq | sed -e 's/\$/\\tPackage/'"
+        pacman -Ssq | string replace -r '$' \tPackage
         return 0
     end
 end
@@ -2,9 +2,7 @@
 function __fish_print_port_packages
     type -q -f port || return 1
 
-    __fish_cached -t 250 -k port '
-        printf "all\ncurrent\nactive\ninactive\ninstalled\nuninstalled\noutdated\n"
-        port echo all | awk \'{$1=$1};1\'
-    '
+    printf '%s\n' all current active inactive installed uninstalled outdated
+    port echo all | awk '{$1=$1};1'
     return 0
 end
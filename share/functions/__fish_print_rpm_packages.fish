@@ -7,16 +7,15 @@ function __fish_print_rpm_packages
     or return 1
 
     if type -q -f /usr/share/yum-cli/completion-helper.py
-        # Remove package version information from output and pipe into cache file
-        __fish_cached -t 21600 -k yum-completion-helper -- '/usr/share/yum-cli/completion-helper.py list all -d 0 -C | sed "s/\\..*/\\tPackage/"'
+        # Remove package version information from output
+        /usr/share/yum-cli/completion-helper.py list all -d 0 -C | string replace -r '\..*' \tPackage
         return
     end
 
-    # Rpm is too slow for this job, so we set it up to do completions
-    # as a background job and cache the results.
+    # Rpm is slow, so completions using this should cache the results.
     if type -q -f rpm
-        # Remove package version information from output and pipe into cache file
-        __fish_cached -t 250 -- "rpm -qa | sed -e 's/-[^-]*-[^-]*\$/\\t'Package'/'"
+        # Remove package version information from output
+        rpm -qa | string replace -r -- '-[^-]*-[^-]*$' \tPackage
         return
     end
 end
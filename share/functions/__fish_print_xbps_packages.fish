@@ -1,6 +1,5 @@
 # localization: skip(private)
 function __fish_print_xbps_packages
-    # Caches for 5 minutes
     type -q -f xbps-query || return 1
 
     argparse i/installed -- $argv
@@ -8,7 +7,7 @@ function __fish_print_xbps_packages
 
     if not set -q _flag_installed
         # prints: <package name>	Package
-        __fish_cached -t 250 -- "xbps-query -Rs '' | sed 's/^... \\([^ ]*\\)-.* .*/\\1/; s/\$/\\t'Package'/'"
+        xbps-query -Rs '' | sed 's/^... \([^ ]*\)-.* .*/\1/' | string replace -r '$' \tPackage
         return 0
     else
         xbps-query -l | sed 's/^.. \([^ ]*\)-.* .*/\1/' # TODO: actually put package versions in tab for locally installed packages
@@ -11,6 +11,12 @@ function __fish_opt_validate_args --no-scope-shadowing
     else if set -q _flag_validate && not set -q _flag_multiple_vals && not set -q _flag_optional_val && not set -q _flag_required_val
         printf (_ "%s: The --validate flag requires the --required-val, --optional-value, or --multiple-vals flag\n") fish_opt >&2
         return 1
+    else if set -q _flag_placeholder && not set -q _flag_multiple_vals && not set -q _flag_optional_val && not set -q _flag_required_val
+        printf (_ "%s: The --placeholder flag requires the --required-val, --optional-value, or --multiple-vals flag\n") fish_opt >&2
+        return 1
+    else if set -q _flag_type && not set -q _flag_multiple_vals && not set -q _flag_optional_val && not set -q _flag_required_val
+        printf (_ "%s: The --type flag requires the --required-val, --optional-value, or --multiple-vals flag\n") fish_opt >&2
+        return 1
     else if set -q _flag_short && test 1 -ne (string length -- $_flag_short)
         printf (_ "%s: The --short flag must be a single character\n") fish_opt >&2
         return 1
@@ -28,8 +34,8 @@ end
 
 # The `fish_opt` command.
 function fish_opt -d 'Produce an option specification suitable for use with `argparse`.'
-    set -l options h/help 's/short=' 'l/long=' d/delete o/optional-val r/required-val m/multiple-vals long-only v/validate
-    argparse -n fish_opt --stop-nonopt --exclusive=r,o $options -- $argv
+    set -l options h/help 's/short=' 'l/long=' d/delete o/optional-val r/required-val m/multiple-vals long-only 'description=' 'placeholder=' 'type=' v/validate
+    argparse -n fish_opt --stop-nonopt --exclusive=r,o --exclusive=type,validate $options -- $argv
     or return
 
     if set -q _flag_help
@@ -60,13 +66,23 @@ function fish_opt -d 'Produce an option specification suitable for use with `arg
         and set opt_spec "$opt_spec=?"
     end
 
+    if set -q _flag_placeholder
+        set opt_spec "$opt_spec$_flag_placeholder"
+    end
+
     if set -q _flag_delete
         set opt_spec "$opt_spec&"
     end
 
-    if set -q _flag_validate
+    if set -q _flag_type
+        set opt_spec "$opt_spec:$_flag_type"
+    else if set -q _flag_validate
         set opt_spec "$opt_spec!$argv"
     end
 
+    if set -q _flag_description
+        set opt_spec "$opt_spec"\t"$_flag_description"
+    end
+
     echo $opt_spec
 end
@@ -2,13 +2,27 @@
 
 use super::prelude::*;
 
+use crate::common::escape;
+use crate::complete::{
+    CompleteFlags, CompleteOptionType, CompletionMode, complete_add, complete_remove,
+};
 use crate::env::{EnvMode, EnvSetMode, EnvStack};
 use crate::exec::exec_subshell;
+use crate::function;
 use crate::parser::ParserEnvSetMode;
-use crate::wutil::fish_iswalnum;
+use crate::re::to_boxed_chars;
+use crate::termsize::termsize_last;
+use crate::wutil::{fish_iswalnum, wstat};
+use fish_fallback::fish_wcswidth;
+use fish_wcstringutil::join_strings;
+use pcre2::utf32::{Regex, RegexBuilder};
 
 const VAR_NAME_PREFIX: &wstr = L!("_flag_");
 
+/// The status after printing the help for `--usage`. It is not 0, so that
+/// `argparse ... -- $argv; or return` stops the function.
+const STATUS_HELP_PRINTED: c_int = 3;
+
 localizable_consts!(
     BUILTIN_ERR_INVALID_OPT_SPEC
     "%s: Invalid option spec '%s' at char '%c'"
@@ -17,10 +31,195 @@
     "%s: Missing -- separator"
 );
 
+/// A check of the value of an option, given after a `:` in the option spec.
+#[derive(Default)]
+enum ValueCheck<'args> {
+    #[default]
+    None,
+    Int {
+        min: Option<i64>,
+        max: Option<i64>,
+    },
+    Float {
+        min: Option<f64>,
+        max: Option<f64>,
+    },
+    Choice(Vec<&'args wstr>),
+    File,
+    Dir,
+    Regex(&'args wstr, Regex),
+}
+
+impl<'args> ValueCheck<'args> {
+    /// Parse a value type like `int(1..10)`, `float`, `choice(a,b)`, `file`, `dir` or
+    /// `regex(PATTERN)`. Return None if it is invalid.
+    fn parse(spec: &'args wstr) -> Option<Self> {
+        let (name, args) = match spec.chars().position(|c| c == '(') {
+            Some(open) if spec.as_char_slice().last() == Some(&')') => {
+                (spec.slice_to(open), Some(&spec[open + 1..spec.len() - 1]))
+            }
+            Some(_) => return None,
+            None => (spec, None),
+        };
+        // Parse a range like `1..10`, where either bound may be omitted.
+        fn range<T>(
+            args: Option<&wstr>,
+            parse: impl Fn(&wstr) -> Option<T>,
+        ) -> Option<(Option<T>, Option<T>)> {
+            let Some(args) = args else {
+                return Some((None, None));
+            };
+            let sep = args
+                .as_char_slice()
+                .windows(2)
+                .position(|w| w == ['.', '.'])?;
+            let bound = |s: &wstr| {
+                if s.is_empty() {
+                    Some(None)
+                } else {
+                    parse(s).map(Some)
+                }
+            };
+            Some((bound(&args[..sep])?, bound(&args[sep + 2..])?))
+        }
+        let check = match name.to_string().as_str() {
+            "int" => {
+                let (min, max) = range(args, |s| fish_wcstol(s).ok())?;
+                ValueCheck::Int { min, max }
+            }
+            "float" => {
+                let (min, max) = range(args, parse_float)?;
+                ValueCheck::Float { min, max }
+            }
+            "choice" => {
+                let choices: Vec<_> = args?.split(',').collect();
+                if choices.iter().any(|choice| choice.is_empty()) {
+                    return None;
+                }
+                ValueCheck::Choice(choices)
+            }
+            "file" if args.is_none() => ValueCheck::File,
+            "dir" if args.is_none() => ValueCheck::Dir,
+            "regex" => {
+                let pattern = args?;
+                let mut builder = RegexBuilder::new();
+                builder.block_utf_pattern_directive(true);
+                let regex = builder.build(to_boxed_chars(pattern)).ok()?;
+                ValueCheck::Regex(pattern, regex)
+            }
+            _ => return None,
+        };
+        Some(check)
+    }
+
+    /// Check the value `val` of the flag `flag`, returning an error message if it is invalid.
+    fn check(&self, cmd: &wstr, flag: &wstr, val: &wstr) -> Option<WString> {
+        match self {
+            ValueCheck::None => None,
+            ValueCheck::Int { min, max } => {
+                let Ok(n) = fish_wcstol(val) else {
+                    return Some(wgettext_fmt!(
+                        "%s: Value '%s' for flag '%s' is not an integer",
+                        cmd,
+                        val,
+                        flag
+                    ));
+                };
+                check_range(cmd, flag, val, n, *min, *max, |n| n.to_wstring())
+            }
+            ValueCheck::Float { min, max } => {
+                let Some(n) = parse_float(val) else {
+                    return Some(wgettext_fmt!(
+                        "%s: Value '%s' for flag '%s' is not a number",
+                        cmd,
+                        val,
+                        flag
+                    ));
+                };
+                check_range(cmd, flag, val, n, *min, *max, |n| sprintf!("%g", n))
+            }
+            ValueCheck::Choice(choices) => (!choices.contains(&val)).then(|| {
+                wgettext_fmt!(
+                    "%s: Value '%s' for flag '%s' is not one of: %s",
+                    cmd,
+                    val,
+                    flag,
+                    join_strings(choices, ',')
+                )
+            }),
+            ValueCheck::File => (!wstat(val).is_ok_and(|md| !md.is_dir())).then(|| {
+                wgettext_fmt!(
+                    "%s: Value '%s' for flag '%s' is not an existing file",
+                    cmd,
+                    val,
+                    flag
+                )
+            }),
+            ValueCheck::Dir => (!wstat(val).is_ok_and(|md| md.is_dir())).then(|| {
+                wgettext_fmt!(
+                    "%s: Value '%s' for flag '%s' is not an existing directory",
+                    cmd,
+                    val,
+                    flag
+                )
+            }),
+            ValueCheck::Regex(pattern, regex) => {
+                let matched = regex
+                    .is_match(val.as_char_slice())
+                    .expect("regex match should not error");
+                (!matched).then(|| {
+                    wgettext_fmt!(
+                        "%s: Value '%s' for flag '%s' does not match '%s'",
+                        cmd,
+                        val,
+                        flag,
+                        pattern
+                    )
+                })
+            }
+        }
+    }
+}
+
+/// Check that `n`, parsed from the value `val` of the flag `flag`, is within the given bounds,
+/// returning an error message if not.
+fn check_range<T: PartialOrd>(
+    cmd: &wstr,
+    flag: &wstr,
+    val: &wstr,
+    n: T,
+    min: Option<T>,
+    max: Option<T>,
+    format: impl Fn(T) -> WString,
+) -> Option<WString> {
+    if let Some(min) = min.filter(|min| n < *min) {
+        return Some(wgettext_fmt!(
+            "%s: Value '%s' for flag '%s' less than min allowed of '%s'",
+            cmd,
+            val,
+            flag,
+            format(min)
+        ));
+    }
+    if let Some(max) = max.filter(|max| n > *max) {
+        return Some(wgettext_fmt!(
+            "%s: Value '%s' for flag '%s' greater than max allowed of '%s'",
+            cmd,
+            val,
+            flag,
+            format(max)
+        ));
+    }
+    None
+}
+
 #[derive(Default)]
 struct OptionSpec<'args> {
     short_flag: char,
     long_flag: &'args wstr,
+    description: &'args wstr,
+    placeholder: &'args wstr,
+    value_check: ValueCheck<'args>,
     validation_command: &'args wstr,
     vals: Vec<WString>,
     short_flag_valid: bool,
@@ -28,6 +227,8 @@ struct OptionSpec<'args> {
     arg_type: ArgType,
     accumulate_args: bool,
     num_seen: isize,
+    /// The position of the option among the option specs, used to list them in order.
+    order: usize,
 }
 
 impl OptionSpec<'_> {
@@ -61,6 +262,8 @@ struct ArgParseCmdOpts<'args> {
     max_args: usize,
     implicit_int_flag: char,
     name: WString,
+    complete_for: Option<&'args wstr>,
+    usage: Vec<&'args wstr>,
     raw_exclusive_flags: Vec<&'args wstr>,
     args: Vec<Cow<'args, wstr>>,
     args_opts: Vec<Cow<'args, wstr>>,
@@ -91,6 +294,8 @@ fn new() -> Self {
     wopt(L!("help"), ArgType::NoArgument, 'h'),
     wopt(L!("min-args"), ArgType::RequiredArgument, 'N'),
     wopt(L!("max-args"), ArgType::RequiredArgument, 'X'),
+    wopt(L!("complete-for"), ArgType::RequiredArgument, '\x01'),
+    wopt(L!("usage"), ArgType::RequiredArgument, '\x02'),
 ];
 
 // Check if any pair of mutually exclusive options was seen. Note that since every option must have
@@ -244,6 +449,19 @@ fn parse_flag_modifiers<'args>(
             }
             _ => (ArgType::RequiredArgument, false),
         };
+
+        // An optional name for the value in uppercase, shown in the help.
+        let placeholder_len = if s.char_at(0).is_ascii_uppercase() {
+            s.chars()
+                .take_while(|&c| {
+                    c == '-' || c == '_' || c.is_ascii_uppercase() || c.is_ascii_digit()
+                })
+                .count()
+        } else {
+            0
+        };
+        opt_spec.placeholder = s.slice_to(placeholder_len);
+        s = s.slice_from(placeholder_len);
     }
 
     if s.char_at(0) == '&' {
@@ -251,7 +469,29 @@ fn parse_flag_modifiers<'args>(
         s = s.slice_from(1);
     }
 
-    if s.char_at(0) == '!' {
+    if s.char_at(0) == ':' {
+        // A value type, which makes up the rest of the spec.
+        if opt_spec.arg_type == ArgType::NoArgument {
+            streams.err.appendln(&wgettext_fmt!(
+                BUILTIN_ERR_INVALID_OPT_SPEC,
+                opts.name,
+                option_spec,
+                s.char_at(0)
+            ));
+            return false;
+        }
+        let Some(check) = ValueCheck::parse(s.slice_from(1)) else {
+            streams.err.appendln(&wgettext_fmt!(
+                "%s: Invalid value type '%s' in option spec '%s'",
+                opts.name,
+                s.slice_from(1),
+                option_spec
+            ));
+            return false;
+        };
+        opt_spec.value_check = check;
+        s = s.slice_from(s.char_count());
+    } else if s.char_at(0) == '!' {
         if opt_spec.arg_type == ArgType::NoArgument {
             streams.err.appendln(&wgettext_fmt!(
                 BUILTIN_ERR_INVALID_OPT_SPEC,
@@ -409,6 +649,12 @@ fn parse_option_spec<'args>(
         return false;
     }
 
+    // Anything after a tab is the description, like in completions.
+    let (option_spec, description) = match option_spec.chars().position(|c| c == '\t') {
+        Some(pos) => (option_spec.slice_to(pos), option_spec.slice_from(pos + 1)),
+        None => (option_spec, L!("")),
+    };
+
     let mut s = option_spec;
     if !fish_iswalnum(s.char_at(0)) && s.char_at(0) != '#' && !(s.char_at(0) == '/' && s.len() > 1)
     {
@@ -421,6 +667,7 @@ fn parse_option_spec<'args>(
     }
 
     let mut opt_spec = OptionSpec::new(s.char_at(0));
+    opt_spec.description = description;
 
     // Try parsing stuff after the short flag.
     if s.char_count() > 1
@@ -463,6 +710,7 @@ fn parse_option_spec<'args>(
     }
 
     // Record our option under its short flag.
+    opt_spec.order = opts.options.len();
     opts.options.insert(opt_spec.short_flag, opt_spec);
 
     true
@@ -486,6 +734,10 @@ fn collect_option_specs<'args>(
 
     loop {
         if *optind == argc {
+            // When only registering completions, there are no arguments to parse.
+            if opts.complete_for.is_some() {
+                break;
+            }
             streams
                 .err
                 .appendln(&wgettext_fmt!(MISSING_DOUBLE_HYPHEN_SEPARATOR, cmd));
@@ -504,6 +756,22 @@ fn collect_option_specs<'args>(
         *optind += 1;
     }
 
+    // With a usage, argparse handles --help itself, so make sure there is such an option.
+    if !opts.usage.is_empty() && !opts.long_to_short_flag.contains_key(L!("help")) {
+        let mut opt_spec = OptionSpec::new('h');
+        if opts.options.contains_key(&'h') {
+            opt_spec.short_flag = char::from_u32(counter).unwrap();
+            opt_spec.short_flag_valid = false;
+            counter += 1;
+        }
+        opt_spec.long_flag = L!("help");
+        opt_spec.description = wgettext!("Show this help");
+        opt_spec.order = opts.options.len();
+        opts.long_to_short_flag
+            .insert(L!("help").to_owned(), opt_spec.short_flag);
+        opts.options.insert(opt_spec.short_flag, opt_spec);
+    }
+
     // Check for counter overreach once at the end because this is very unlikely to ever be reached.
     let counter_max = 0xF8FFu32;
 
@@ -577,6 +845,8 @@ fn parse_cmd_opts<'args>(
             // definitions we'll parse these strings into a more useful data structure.
             'x' => opts.raw_exclusive_flags.push(w.woptarg.unwrap()),
             'h' => opts.print_help = true,
+            '\x01' => opts.complete_for = Some(w.woptarg.unwrap()),
+            '\x02' => opts.usage.push(w.woptarg.unwrap()),
             'N' => {
                 opts.min_args = {
                     let x = fish_wcstol(w.woptarg.unwrap()).unwrap_or(-1);
@@ -702,6 +972,16 @@ fn validate_arg<'opts>(
     woptarg: &'opts wstr,
     streams: &mut IoStreams,
 ) -> BuiltinResult {
+    let flag_name = if is_long_flag {
+        opt_spec.long_flag.to_owned()
+    } else {
+        WString::from_chars(vec![opt_spec.short_flag])
+    };
+    if let Some(error) = opt_spec.value_check.check(opts_name, &flag_name, woptarg) {
+        streams.err.appendln(&error);
+        return Err(STATUS_CMD_ERROR);
+    }
+
     // Obviously if there is no arg validation command we assume the arg is okay.
     if opt_spec.validation_command.is_empty() {
         return Ok(SUCCESS);
@@ -1151,6 +1431,302 @@ fn check_min_max_args_constraints(
     Ok(SUCCESS)
 }
 
+/// Register completions for the command `cmd` derived from the option specs, replacing any previous
+/// completions for the same flags. Flags that are mutually exclusive with one already on the
+/// command line are not offered.
+fn add_completions(opts: &ArgParseCmdOpts, cmd: &wstr) {
+    // Sort the options so they are registered in a stable order.
+    let mut opt_specs: Vec<_> = opts.options.values().collect();
+    opt_specs.sort_by_key(|opt_spec| opt_spec.short_flag);
+
+    for opt_spec in opt_specs {
+        // The implicit int flag has no name to complete.
+        if opt_spec.short_flag == opts.implicit_int_flag {
+            continue;
+        }
+
+        let mut excluded = WString::new();
+        for xarg_set in &opts.exclusive_flag_sets {
+            if !xarg_set.contains(&opt_spec.short_flag) {
+                continue;
+            }
+            for xflag in xarg_set {
+                let Some(xopt_spec) = opts.options.get(xflag) else {
+                    continue;
+                };
+                if xopt_spec.short_flag == opt_spec.short_flag {
+                    continue;
+                }
+                if xopt_spec.short_flag_valid {
+                    excluded.push_str(" -s ");
+                    excluded.push(xopt_spec.short_flag);
+                }
+                if !xopt_spec.long_flag.is_empty() {
+                    excluded.push(' ');
+                    excluded.push_utfstr(&escape(xopt_spec.long_flag));
+                }
+            }
+        }
+        let conditions = if excluded.is_empty() {
+            vec![]
+        } else {
+            vec![L!("not __fish_contains_opt").to_owned() + &excluded[..]]
+        };
+
+        // Offer the values the option's value type allows.
+        let (comp, no_files) = match &opt_spec.value_check {
+            ValueCheck::None | ValueCheck::File | ValueCheck::Regex(..) => (WString::new(), false),
+            ValueCheck::Int { .. } | ValueCheck::Float { .. } => (WString::new(), true),
+            ValueCheck::Choice(choices) => {
+                let choices: Vec<_> = choices.iter().map(|choice| escape(choice)).collect();
+                (join_strings(&choices, ' '), true)
+            }
+            ValueCheck::Dir => (L!("(__fish_complete_directories)").to_owned(), true),
+        };
+        let result_mode = CompletionMode {
+            no_files,
+            force_files: false,
+            requires_param: opt_spec.arg_type == ArgType::RequiredArgument,
+        };
+
+        let mut flags = vec![];
+        if opt_spec.short_flag_valid {
+            flags.push((
+                WString::from_chars(vec![opt_spec.short_flag]),
+                CompleteOptionType::Short,
+            ));
+        }
+        if !opt_spec.long_flag.is_empty() {
+            flags.push((
+                opt_spec.long_flag.to_owned(),
+                CompleteOptionType::DoubleLong,
+            ));
+        }
+        for (flag, typ) in flags {
+            complete_remove(cmd.to_owned(), false, &flag, typ, &[]);
+            complete_add(
+                cmd.to_owned(),
+                false,
+                flag,
+                typ,
+                result_mode,
+                conditions.clone(),
+                vec![],
+                comp.clone(),
+                opt_spec.description.to_owned(),
+                CompleteFlags::empty(),
+                None,
+            );
+        }
+    }
+
+    // Don't offer files for commands that take no arguments.
+    if opts.max_args == 0 {
+        complete_remove(
+            cmd.to_owned(),
+            false,
+            L!(""),
+            CompleteOptionType::ArgsOnly,
+            &[],
+        );
+        complete_add(
+            cmd.to_owned(),
+            false,
+            WString::new(),
+            CompleteOptionType::ArgsOnly,
+            CompletionMode {
+                no_files: true,
+                force_files: false,
+                requires_param: false,
+            },
+            vec![],
+            vec![],
+            WString::new(),
+            WString::new(),
+            CompleteFlags::empty(),
+            None,
+        );
+    }
+}
+
+/// Return the name shown for the value of an option: its placeholder, or else its long flag in
+/// uppercase.
+fn value_name(opt_spec: &OptionSpec) -> WString {
+    if !opt_spec.placeholder.is_empty() {
+        opt_spec.placeholder.to_owned()
+    } else if let ValueCheck::Choice(choices) = &opt_spec.value_check {
+        join_strings(choices, '|')
+    } else if !opt_spec.long_flag.is_empty() {
+        opt_spec
+            .long_flag
+            .chars()
+            .map(|c| {
+                if c == '-' {
+                    '_'
+                } else {
+                    c.to_ascii_uppercase()
+                }
+            })
+            .collect()
+    } else {
+        L!("VALUE").to_owned()
+    }
+}
+
+/// Return how an option is shown in the help, like `-n, --name=NAME`.
+fn option_label(opts: &ArgParseCmdOpts, opt_spec: &OptionSpec) -> WString {
+    let value = value_name(opt_spec);
+    let mut label = WString::new();
+    if opt_spec.short_flag == opts.implicit_int_flag {
+        label.push('-');
+        label.push_utfstr(&value);
+    } else if opt_spec.short_flag_valid {
+        label.push('-');
+        label.push(opt_spec.short_flag);
+        if opt_spec.long_flag.is_empty() {
+            match opt_spec.arg_type {
+                ArgType::RequiredArgument => label.push_utfstr(&sprintf!(" %s", value)),
+                ArgType::OptionalArgument => label.push_utfstr(&sprintf!("[%s]", value)),
+                ArgType::NoArgument => {}
+            }
+        }
+    } else {
+        // Line up long-only options with the long flags of the others.
+        label.push_str("    ");
+    }
+    if !opt_spec.long_flag.is_empty() {
+        if !label.trim_matches(' ').is_empty() {
+            label.push_str(", ");
+        }
+        label.push_str("--");
+        label.push_utfstr(opt_spec.long_flag);
+        match opt_spec.arg_type {
+            ArgType::RequiredArgument => label.push_utfstr(&sprintf!("=%s", value)),
+            ArgType::OptionalArgument => label.push_utfstr(&sprintf!("[=%s]", value)),
+            ArgType::NoArgument => {}
+        }
+    }
+    label
+}
+
+/// Return the width of `text` on screen, ignoring nonprintable characters.
+fn display_width(text: &wstr) -> usize {
+    usize::try_from(fish_wcswidth(text)).unwrap_or(0)
+}
+
+/// Break `text` into lines no wider than `width`, at spaces.
+fn wrap_text(text: &wstr, width: usize) -> Vec<WString> {
+    let mut lines = vec![];
+    let mut line = WString::new();
+    for word in text.split(' ').filter(|word| !word.is_empty()) {
+        if !line.is_empty() && display_width(&line) + 1 + display_width(word) > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_utfstr(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Append the usage lines given with --usage to `out`.
+fn append_usage(opts: &ArgParseCmdOpts, out: &mut WString) {
+    let prefix = wgettext!("Usage:");
+    for (i, usage) in opts.usage.iter().enumerate() {
+        if i == 0 {
+            out.push_utfstr(prefix);
+        } else {
+            out.extend(std::iter::repeat_n(' ', display_width(prefix)));
+        }
+        out.push(' ');
+        out.push_utfstr(usage);
+        out.push('\n');
+    }
+}
+
+/// Print the help for the command using argparse: the usage, the description of the function
+/// and the options with their descriptions, wrapped to the width of the terminal.
+fn print_help(parser: &Parser, opts: &ArgParseCmdOpts, streams: &mut IoStreams) {
+    // Don't let the descriptions get too narrow on small terminals.
+    const MIN_DESCRIPTION_WIDTH: usize = 20;
+    // Options with longer labels get their description on the next line.
+    const MAX_LABEL_WIDTH: usize = 24;
+    let width = termsize_last().width();
+
+    let mut out = WString::new();
+    append_usage(opts, &mut out);
+
+    let description = parser
+        .get_function_name(1)
+        .and_then(|name| function::get_props(&name))
+        .map(|props| props.description.localize().to_owned())
+        .unwrap_or_default();
+    if !description.is_empty() {
+        out.push('\n');
+        for line in wrap_text(&description, width.max(MIN_DESCRIPTION_WIDTH)) {
+            out.push_utfstr(&line);
+            out.push('\n');
+        }
+    }
+
+    let mut opt_specs: Vec<_> = opts.options.values().collect();
+    opt_specs.sort_by_key(|opt_spec| opt_spec.order);
+    let labels: Vec<_> = opt_specs
+        .iter()
+        .map(|opt_spec| option_label(opts, opt_spec))
+        .collect();
+    let label_width = labels
+        .iter()
+        .map(|label| display_width(label))
+        .filter(|&w| w <= MAX_LABEL_WIDTH)
+        .max()
+        .unwrap_or(0);
+    // Two spaces of indentation, and two between the labels and the descriptions.
+    let column = label_width + 4;
+    let description_width = width.saturating_sub(column).max(MIN_DESCRIPTION_WIDTH);
+
+    out.push('\n');
+    out.push_utfstr(wgettext!("Options:"));
+    out.push('\n');
+    for (opt_spec, label) in opt_specs.iter().zip(labels) {
+        let mut lines = wrap_text(opt_spec.description, description_width).into_iter();
+        out.push_str("  ");
+        out.push_utfstr(&label);
+        let label_width = display_width(&label);
+        if label_width > MAX_LABEL_WIDTH {
+            out.push('\n');
+        } else if let Some(first) = lines.next() {
+            out.extend(std::iter::repeat_n(' ', column - 2 - label_width));
+            out.push_utfstr(&first);
+            out.push('\n');
+        } else {
+            out.push('\n');
+        }
+        for line in lines {
+            out.extend(std::iter::repeat_n(' ', column));
+            out.push_utfstr(&line);
+            out.push('\n');
+        }
+    }
+    streams.out.append(&out);
+}
+
+/// After an error, print the usage and how to get help.
+fn print_usage_summary(opts: &ArgParseCmdOpts, streams: &mut IoStreams) {
+    let mut out = WString::new();
+    append_usage(opts, &mut out);
+    out.push_utfstr(&wgettext_fmt!(
+        "Try '%s --help' for more information.",
+        opts.name
+    ));
+    out.push('\n');
+    streams.err.append(&out);
+}
+
 /// Put the result of parsing the supplied args into the caller environment as local vars.
 fn set_argparse_result_vars(vars: &EnvStack, local_mode: EnvSetMode, opts: ArgParseCmdOpts) {
     for opt_spec in opts.options.values() {
@@ -1214,18 +1790,44 @@ pub fn argparse(parser: &Parser, streams: &mut IoStreams, args: &mut [&wstr]) ->
 
     parse_exclusive_args(&mut opts, streams)?;
 
+    if let Some(complete_for) = opts.complete_for {
+        add_completions(&opts, complete_for);
+        return Ok(SUCCESS);
+    }
+
     // wgetopt expects the first argument to be the command, and skips it.
     // if optind was 0 we'd already have returned.
     assert!(optind > 0, "Optind is 0?");
-    argparse_parse_args(
+    let retval = argparse_parse_args(
         &mut opts,
         &mut args[optind - 1..],
         argc - optind + 1,
         parser,
         streams,
-    )?;
+    );
 
-    check_min_max_args_constraints(&opts, streams)?;
+    // With a usage, argparse prints the help itself, and skips checking the other arguments.
+    let help_requested = !opts.usage.is_empty()
+        && opts
+            .long_to_short_flag
+            .get(L!("help"))
+            .is_some_and(|short| {
+                opts.options
+                    .get(short)
+                    .is_some_and(|opt_spec| opt_spec.num_seen > 0)
+            });
+    let retval = retval.and_then(|_| {
+        if help_requested {
+            print_help(parser, &opts, streams);
+            Ok(SUCCESS)
+        } else {
+            check_min_max_args_constraints(&opts, streams)
+        }
+    });
+    if retval.is_err() && !opts.usage.is_empty() {
+        print_usage_summary(&opts, streams);
+    }
+    retval?;
 
     set_argparse_result_vars(
         parser.vars(),
@@ -1233,5 +1835,8 @@ pub fn argparse(parser: &Parser, streams: &mut IoStreams, args: &mut [&wstr]) ->
         opts,
     );
 
+    if help_requested {
+        return Err(STATUS_HELP_PRINTED);
+    }
     Ok(SUCCESS)
 }
@@ -4,15 +4,34 @@
 use crate::{
     env::{EnvMode, Environment},
     fds::{BEST_O_SEARCH, wopen_dir},
-    parser::ParserEnvSetMode,
+    flog::flog,
+    frecency::{frecency_add, frecency_search},
+    history::in_private_mode,
+    parser::{BlockData, BlockType, ParserEnvSetMode},
     path::path_apply_cdpath,
-    wutil::{normalize_path, wperror, wreadlink},
+    wutil::{lwstat, normalize_path, wperror, wreadlink},
 };
 use errno::Errno;
 use libc::{EACCES, ELOOP, ENOENT, ENOTDIR, EPERM};
 use nix::unistd::fchdir;
 use std::sync::Arc;
 
+/// Return whether `cd` was typed at the prompt, rather than run by a function, a sourced file, an
+/// event handler or a command substitution. The `cd` function, which wraps the builtin, does not
+/// count as a function here.
+fn is_typed_at_prompt(parser: &Parser) -> bool {
+    parser.is_interactive()
+        && parser.blocks_iter_rev().all(|b| match b.typ() {
+            BlockType::function_call { .. } => {
+                matches!(b.data(), Some(BlockData::Function { name, .. }) if name == "cd")
+            }
+            BlockType::source | BlockType::event | BlockType::subst | BlockType::breakpoint => {
+                false
+            }
+            _ => true,
+        })
+}
+
 // The cd builtin. Changes the current directory to the one specified or to $HOME if none is
 // specified. The directory can be relative to any directory in the CDPATH variable.
 pub fn cd(parser: &Parser, streams: &mut IoStreams, args: &mut [&wstr]) -> BuiltinResult {
@@ -67,7 +86,26 @@ pub fn cd(parser: &Parser, streams: &mut IoStreams, args: &mut [&wstr]) -> Built
 
     let pwd = vars.get_pwd_slash();
 
-    let dirs = path_apply_cdpath(dir_in, &pwd, vars);
+    let mut dirs = path_apply_cdpath(dir_in, &pwd, vars);
+    let typed_at_prompt = is_typed_at_prompt(parser);
+
+    // If there is no such directory, change to the best visited directory matching the name.
+    // This is only done for names typed at the prompt and not for explicit paths.
+    if typed_at_prompt
+        && !dir_in.starts_with('/')
+        && !dir_in.starts_with('.')
+        && dirs.iter().all(|dir| lwstat(dir).is_err())
+    {
+        let keywords: Vec<&wstr> = dir_in.split('/').filter(|s| !s.is_empty()).collect();
+        let current = vars.get_unless_empty(L!("PWD")).map(|v| v.as_string());
+        if let Some((entry, _)) = frecency_search(&keywords, current.as_deref())
+            .into_iter()
+            .next()
+        {
+            dirs = vec![entry.path];
+        }
+    }
+
     if dirs.is_empty() {
         streams
             .err
@@ -133,6 +171,13 @@ pub fn cd(parser: &Parser, streams: &mut IoStreams, args: &mut [&wstr]) -> Built
         // Stash the fd for the cwd in the parser.
         parser.libdata_mut().cwd_fd = Some(dir_fd);
 
+        // Remember the directories visited at the prompt, for ranking `cd` completions.
+        if typed_at_prompt && !in_private_mode(vars) {
+            if let Err(err) = frecency_add(std::slice::from_ref(&norm_dir)) {
+                flog!(frecency, "Failed to update the directory database:", err);
+            }
+        }
+
         parser.set_var_and_fire(
             L!("PWD"),
             ParserEnvSetMode::new(EnvMode::EXPORT | EnvMode::GLOBAL),
@@ -398,8 +398,10 @@ pub fn commandline(parser: &Parser, streams: &mut IoStreams, args: &mut [&wstr])
             };
             // Don't enqueue a repaint if we're currently in the middle of one,
             // because that's an infinite loop.
-            if matches!(cmd, RL::RepaintMode | RL::ForceRepaint | RL::Repaint)
-                && parser.is_repainting()
+            if matches!(
+                cmd,
+                RL::RepaintMode | RL::RepaintStatusLine | RL::ForceRepaint | RL::Repaint
+            ) && parser.is_repainting()
             {
                 continue;
             }
@@ -1,6 +1,9 @@
 use super::prelude::*;
-use crate::common::{ScopeGuard, UnescapeFlags, UnescapeStringStyle, unescape_string};
+use crate::common::{
+    ReadExt, ScopeGuard, UnescapeFlags, UnescapeStringStyle, unescape_string, wcs2bytes,
+};
 use crate::complete::{CompletionRequestOptions, complete_add_wrapper, complete_remove_wrapper};
+use crate::complete_spec::complete_import_spec;
 use crate::highlight::highlight_and_colorize;
 use crate::operation_context::OperationContext;
 use crate::parse_constants::ParseErrorList;
@@ -11,11 +14,17 @@
 use crate::{
     common::bytes2wcstring,
     complete::{
-        CompleteFlags, CompleteOptionType, CompletionMode, complete_add, complete_print,
-        complete_remove, complete_remove_all,
+        CompleteFlags, CompleteOptionType, CompletionCache, CompletionMode, complete_add,
+        complete_clear_cache, complete_print, complete_remove, complete_remove_all,
+        complete_remove_subcommand,
     },
 };
 use fish_wcstringutil::string_suffixes_string;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::Read;
+use std::os::unix::ffi::OsStrExt;
+use std::time::Duration;
 
 // builtin_complete_* are a set of rather silly looping functions that make sure that all the proper
 // combinations of complete_add or complete_remove get called. This is needed since complete allows
@@ -32,9 +41,11 @@ fn builtin_complete_add2(
     old_opts: &[&wstr],
     result_mode: CompletionMode,
     condition: &[WString],
+    subcommand: &[WString],
     comp: &wstr,
     desc: &wstr,
     flags: CompleteFlags,
+    cache: Option<&CompletionCache>,
 ) {
     for short_opt in short_opt.chars() {
         complete_add(
@@ -44,9 +55,11 @@ fn builtin_complete_add2(
             CompleteOptionType::Short,
             result_mode,
             condition.to_vec(),
+            subcommand.to_vec(),
             comp.to_owned(),
             desc.to_owned(),
             flags,
+            cache.cloned(),
         );
     }
 
@@ -58,9 +71,11 @@ fn builtin_complete_add2(
             CompleteOptionType::DoubleLong,
             result_mode,
             condition.to_vec(),
+            subcommand.to_vec(),
             comp.to_owned(),
             desc.to_owned(),
             flags,
+            cache.cloned(),
         );
     }
 
@@ -72,9 +87,11 @@ fn builtin_complete_add2(
             CompleteOptionType::SingleLong,
             result_mode,
             condition.to_vec(),
+            subcommand.to_vec(),
             comp.to_owned(),
             desc.to_owned(),
             flags,
+            cache.cloned(),
         );
     }
 
@@ -86,9 +103,11 @@ fn builtin_complete_add2(
             CompleteOptionType::ArgsOnly,
             result_mode,
             condition.to_vec(),
+            subcommand.to_vec(),
             comp.to_owned(),
             desc.to_owned(),
             flags,
+            cache.cloned(),
         );
     }
 }
@@ -103,9 +122,11 @@ fn builtin_complete_add(
     old_opt: &[&wstr],
     result_mode: CompletionMode,
     condition: &[WString],
+    subcommand: &[WString],
     comp: &wstr,
     desc: &wstr,
     flags: CompleteFlags,
+    cache: Option<&CompletionCache>,
 ) {
     for cmd in cmds {
         builtin_complete_add2(
@@ -116,9 +137,11 @@ fn builtin_complete_add(
             old_opt,
             result_mode,
             condition,
+            subcommand,
             comp,
             desc,
             flags,
+            cache,
         );
     }
     for path in paths {
@@ -130,9 +153,11 @@ fn builtin_complete_add(
             old_opt,
             result_mode,
             condition,
+            subcommand,
             comp,
             desc,
             flags,
+            cache,
         );
     }
 }
@@ -144,6 +169,7 @@ fn builtin_complete_remove_cmd(
     gnu_opt: &[&wstr],
     old_opt: &[&wstr],
     wrap_targets: &[WString],
+    subcommand: &[WString],
 ) {
     let mut removed = false;
     for s in short_opt.chars() {
@@ -152,6 +178,7 @@ fn builtin_complete_remove_cmd(
             cmd_is_path,
             wstr::from_char_slice(&[s]),
             CompleteOptionType::Short,
+            subcommand,
         );
         removed = true;
     }
@@ -162,6 +189,7 @@ fn builtin_complete_remove_cmd(
             cmd_is_path,
             opt,
             CompleteOptionType::SingleLong,
+            subcommand,
         );
         removed = true;
     }
@@ -172,6 +200,7 @@ fn builtin_complete_remove_cmd(
             cmd_is_path,
             opt,
             CompleteOptionType::DoubleLong,
+            subcommand,
         );
         removed = true;
     }
@@ -183,7 +212,11 @@ fn builtin_complete_remove_cmd(
 
     if !removed {
         // This means that all loops were empty.
-        complete_remove_all(cmd.to_owned(), cmd_is_path, /*explicit=*/ true);
+        if subcommand.is_empty() {
+            complete_remove_all(cmd.to_owned(), cmd_is_path, /*explicit=*/ true);
+        } else {
+            complete_remove_subcommand(cmd.to_owned(), cmd_is_path, subcommand);
+        }
     }
 }
 
@@ -194,6 +227,7 @@ fn builtin_complete_remove(
     gnu_opt: &[&wstr],
     old_opt: &[&wstr],
     wrap_targets: &[WString],
+    subcommand: &[WString],
 ) {
     for cmd in cmds {
         builtin_complete_remove_cmd(
@@ -203,6 +237,7 @@ fn builtin_complete_remove(
             gnu_opt,
             old_opt,
             wrap_targets,
+            subcommand,
         );
     }
 
@@ -214,6 +249,7 @@ fn builtin_complete_remove(
             gnu_opt,
             old_opt,
             wrap_targets,
+            subcommand,
         );
     }
 }
@@ -239,6 +275,43 @@ fn builtin_complete_print(
 
 /// Values used for long-only options.
 const OPT_ESCAPE: char = '\x01';
+const OPT_CACHE_TTL: char = '\x02';
+const OPT_CACHE_KEY: char = '\x03';
+const OPT_CLEAR_CACHE: char = '\x04';
+const OPT_JSON: char = '\x05';
+const OPT_IMPORT_SPEC: char = '\x06';
+const OPT_SUBCOMMAND: char = '\x07';
+
+/// Read a completion spec from the given file, or from stdin if it is "-".
+fn read_spec(cmd: &wstr, file: &wstr, streams: &mut IoStreams) -> Result<WString, ErrorCode> {
+    let mut buf = vec![];
+    if file == "-" {
+        let Some(stdin_file) = streams.stdin_file.as_mut() else {
+            streams
+                .err
+                .appendln(&wgettext_fmt!(BUILTIN_ERR_STDIN_CLOSED, cmd));
+            return Err(STATUS_CMD_ERROR);
+        };
+        if let Err(err) = stdin_file.read_to_end_interruptible(&mut buf) {
+            return Err(if err.kind() == std::io::ErrorKind::Interrupted {
+                128 + libc::SIGINT
+            } else {
+                STATUS_CMD_ERROR
+            });
+        }
+    } else if let Err(err) = fs::File::open(OsStr::from_bytes(&wcs2bytes(file)))
+        .and_then(|mut f| f.read_to_end(&mut buf))
+    {
+        streams.err.appendln(&wgettext_fmt!(
+            "%s: Reading '%s' failed: %s",
+            cmd,
+            file,
+            err.to_string()
+        ));
+        return Err(STATUS_CMD_ERROR);
+    }
+    Ok(bytes2wcstring(&buf))
+}
 
 /// The complete builtin. Used for specifying programmable tab-completions. Calls the functions in
 /// complete.rs for any heavy lifting.
@@ -265,7 +338,13 @@ pub fn complete(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) ->
     let mut wrap_targets = vec![];
     let mut preserve_order = false;
     let mut unescape_output = true;
+    let mut json = false;
+    let mut import_spec = None;
+    let mut subcommand = vec![];
     let mut color = ColorEnabled::default();
+    let mut cache_ttl = None;
+    let mut cache_key = None;
+    let mut clear_cache = false;
 
     let short_options: &wstr = L!("a:c:p:s:l:o:d:fFrxeuAn:C::w:hk");
     let long_options: &[WOption] = &[
@@ -289,6 +368,16 @@ pub fn complete(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) ->
         wopt(L!("help"), ArgType::NoArgument, 'h'),
         wopt(L!("keep-order"), ArgType::NoArgument, 'k'),
         wopt(L!("escape"), ArgType::NoArgument, OPT_ESCAPE),
+        wopt(L!("cache-ttl"), ArgType::RequiredArgument, OPT_CACHE_TTL),
+        wopt(L!("cache-key"), ArgType::RequiredArgument, OPT_CACHE_KEY),
+        wopt(L!("clear-cache"), ArgType::NoArgument, OPT_CLEAR_CACHE),
+        wopt(L!("json"), ArgType::NoArgument, OPT_JSON),
+        wopt(
+            L!("import-spec"),
+            ArgType::RequiredArgument,
+            OPT_IMPORT_SPEC,
+        ),
+        wopt(L!("subcommand"), ArgType::RequiredArgument, OPT_SUBCOMMAND),
         wopt(L!("color"), ArgType::RequiredArgument, COLOR_OPTION_CHAR),
     ];
 
@@ -399,6 +488,54 @@ pub fn complete(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) ->
             OPT_ESCAPE => {
                 unescape_output = false;
             }
+            OPT_JSON => {
+                json = true;
+            }
+            OPT_IMPORT_SPEC => {
+                import_spec = Some(w.woptarg.unwrap());
+            }
+            OPT_SUBCOMMAND => {
+                let arg = w.woptarg.unwrap();
+                subcommand = arg
+                    .split(' ')
+                    .filter(|name| !name.is_empty())
+                    .map(|name| name.to_owned())
+                    .collect();
+                if subcommand.is_empty() {
+                    streams.err.appendln(&wgettext_fmt!(
+                        OPTION_REQUIRES_NON_EMPTY_STRING,
+                        cmd,
+                        "--subcommand",
+                    ));
+                    return Err(STATUS_INVALID_ARGS);
+                }
+            }
+            OPT_CACHE_TTL => {
+                let arg = w.woptarg.unwrap();
+                let Ok(seconds) = fish_wcstoul(arg) else {
+                    streams
+                        .err
+                        .appendln(&wgettext_fmt!(BUILTIN_ERR_NOT_NUMBER, cmd, arg));
+                    builtin_print_error_trailer(parser, streams.err, cmd);
+                    return Err(STATUS_INVALID_ARGS);
+                };
+                cache_ttl = Some(Duration::from_secs(seconds));
+            }
+            OPT_CACHE_KEY => {
+                let arg = w.woptarg.unwrap();
+                if arg.is_empty() {
+                    streams.err.appendln(&wgettext_fmt!(
+                        OPTION_REQUIRES_NON_EMPTY_STRING,
+                        cmd,
+                        "--cache-key",
+                    ));
+                    return Err(STATUS_INVALID_ARGS);
+                }
+                cache_key = Some(arg.to_owned());
+            }
+            OPT_CLEAR_CACHE => {
+                clear_cache = true;
+            }
             'h' => {
                 builtin_print_help(parser, streams, cmd);
                 return Ok(SUCCESS);
@@ -441,6 +578,35 @@ pub fn complete(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) ->
         return Err(STATUS_INVALID_ARGS);
     }
 
+    if cache_key.is_some() && cache_ttl.is_none() && !clear_cache {
+        streams.err.appendln(&wgettext_fmt!(
+            BUILTIN_ERR_COMBO2,
+            cmd,
+            wgettext!("--cache-key requires --cache-ttl")
+        ));
+        return Err(STATUS_INVALID_ARGS);
+    }
+    if cache_ttl.is_some() && comp.is_empty() {
+        streams.err.appendln(&wgettext_fmt!(
+            BUILTIN_ERR_COMBO2,
+            cmd,
+            wgettext!("--cache-ttl requires --arguments")
+        ));
+        return Err(STATUS_INVALID_ARGS);
+    }
+    if json && !do_complete {
+        streams.err.appendln(&wgettext_fmt!(
+            BUILTIN_ERR_COMBO2,
+            cmd,
+            wgettext!("--json requires --do-complete")
+        ));
+        return Err(STATUS_INVALID_ARGS);
+    }
+    let cache = cache_ttl.map(|ttl| CompletionCache {
+        ttl,
+        key: cache_key.clone(),
+    });
+
     if w.wopt_index != argc {
         // Use one left-over arg as the do-complete argument
         // to enable `complete -C "git check"`.
@@ -488,7 +654,41 @@ pub fn complete(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) ->
         }
     }
 
-    if do_complete {
+    if let Some(file) = import_spec {
+        let spec = read_spec(cmd, file, streams)?;
+        if let Err(err) = complete_import_spec(&spec) {
+            let file = if file == "-" { L!("<stdin>") } else { file };
+            let message = match err.line {
+                Some(line) => wgettext_fmt!(
+                    "%s: %s (line %u): Invalid completion spec: %s",
+                    cmd,
+                    file,
+                    line,
+                    err.message
+                ),
+                None => wgettext_fmt!(
+                    "%s: %s: Invalid completion spec: %s",
+                    cmd,
+                    file,
+                    err.message
+                ),
+            };
+            streams.err.appendln(&message);
+            return Err(STATUS_CMD_ERROR);
+        }
+    } else if clear_cache {
+        // Forget the cached arguments of the given commands, or with the given key.
+        let cmds = cmd_to_complete.iter().map(|cmd| (cmd, false));
+        let paths = path.iter().map(|path| (path, true));
+        let mut cleared = false;
+        for (cmd, is_path) in cmds.chain(paths) {
+            complete_clear_cache(Some((cmd, is_path)), cache_key.as_deref());
+            cleared = true;
+        }
+        if !cleared {
+            complete_clear_cache(None, cache_key.as_deref());
+        }
+    } else if do_complete {
         let have_do_complete_param = do_complete_param.is_some();
         let do_complete_param = match do_complete_param {
             None => {
@@ -568,6 +768,13 @@ pub fn complete(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) ->
                     .expect("Unescaping commandline to complete failed");
                 }
 
+                if json {
+                    streams
+                        .out
+                        .appendln(&next.to_json(&faux_cmdline_with_completion));
+                    continue;
+                }
+
                 // Append any description.
                 if !next.description.is_empty() {
                     faux_cmdline_with_completion
@@ -589,6 +796,7 @@ pub fn complete(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) ->
         && comp.is_empty()
         && desc.is_empty()
         && condition.is_empty()
+        && subcommand.is_empty()
         && wrap_targets.is_empty()
         && !result_mode.no_files
         && !result_mode.force_files
@@ -620,6 +828,7 @@ pub fn complete(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) ->
                 &gnu_opt,
                 &old_opt,
                 &wrap_targets,
+                &subcommand,
             );
         } else {
             builtin_complete_add(
@@ -630,9 +839,11 @@ pub fn complete(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) ->
                 &old_opt,
                 result_mode,
                 &condition,
+                &subcommand,
                 &comp,
                 &desc,
                 flags,
+                cache.as_ref(),
             );
             // Handle wrap targets (probably empty). We only wrap commands, not paths.
             for wrap_target in wrap_targets {
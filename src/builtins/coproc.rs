@@ -0,0 +1,244 @@
+//! Implementation of the coproc builtin.
+
+use super::prelude::*;
+use crate::common::{escape, valid_var_name};
+use crate::env::{EnvMode, Environment};
+use crate::fds::make_autoclose_pipes;
+use crate::io::{IoChain, IoPipe};
+use crate::parse_execution::varname_error;
+use crate::parser::ParserEnvSetMode;
+use std::os::fd::{AsRawFd, OwnedFd, RawFd};
+use std::sync::{Arc, Mutex};
+
+/// A coprocess: a background job whose stdin and stdout are connected to pipes owned by fish.
+struct Coprocess {
+    /// The name of the variable holding the fds.
+    name: WString,
+    /// The pid of the last process in the job.
+    pid: WString,
+    /// The read end of the pipe connected to the job's stdout.
+    output: Option<OwnedFd>,
+    /// The write end of the pipe connected to the job's stdin.
+    input: Option<OwnedFd>,
+}
+
+impl Coprocess {
+    fn fds(&self) -> Vec<WString> {
+        [&self.output, &self.input]
+            .iter()
+            .map(|fd| fd.as_ref().map_or(-1, |fd| fd.as_raw_fd()).to_wstring())
+            .collect()
+    }
+}
+
+static COPROCESSES: Mutex<Vec<Coprocess>> = Mutex::new(Vec::new());
+
+/// Return whether the given fd belongs to a coprocess.
+/// Unlike fish's other internal fds, these may be used in redirections.
+pub fn is_coprocess_fd(fd: RawFd) -> bool {
+    COPROCESSES.lock().unwrap().iter().any(|coproc| {
+        [&coproc.output, &coproc.input]
+            .iter()
+            .any(|f| f.as_ref().is_some_and(|f| f.as_raw_fd() == fd))
+    })
+}
+
+/// Set the variables for a coprocess: `NAME` holds its fds, and `NAME_pid` its pid.
+fn set_coprocess_vars(parser: &Parser, name: &wstr, fds: Vec<WString>, pid: WString) {
+    let mode = ParserEnvSetMode::user(EnvMode::GLOBAL);
+    parser.set_var_and_fire(name, mode, fds);
+    parser.set_var_and_fire(&(name.to_owned() + L!("_pid")), mode, vec![pid]);
+}
+
+/// Close the fds of the coprocess with the given name, and remove its variables.
+/// If `input_only` is set, only the fd connected to the coprocess's stdin is closed, so it
+/// receives end-of-file. Return false if there is no such coprocess.
+fn close_coprocess(parser: &Parser, name: &wstr, input_only: bool) -> bool {
+    let mut coprocs = COPROCESSES.lock().unwrap();
+    let Some(idx) = coprocs.iter().position(|c| c.name == name) else {
+        return false;
+    };
+    if input_only {
+        let coproc = &mut coprocs[idx];
+        coproc.input = None;
+        let (fds, pid) = (coproc.fds(), coproc.pid.clone());
+        drop(coprocs);
+        set_coprocess_vars(parser, name, fds, pid);
+    } else {
+        coprocs.remove(idx);
+        drop(coprocs);
+        let mode = ParserEnvSetMode::user(EnvMode::GLOBAL);
+        parser.remove_var(name, mode);
+        parser.remove_var(&(name.to_owned() + L!("_pid")), mode);
+    }
+    true
+}
+
+/// Start `argv` as a background job with pipes connected to its stdin and stdout.
+fn start_coprocess(
+    parser: &Parser,
+    streams: &mut IoStreams,
+    cmd: &wstr,
+    name: &wstr,
+    argv: &[&wstr],
+) -> BuiltinResult {
+    let (to_coproc, from_coproc) = match (make_autoclose_pipes(), make_autoclose_pipes()) {
+        (Ok(to_coproc), Ok(from_coproc)) => (to_coproc, from_coproc),
+        (Err(err), _) | (_, Err(err)) => {
+            streams.err.appendln(&wgettext_fmt!(
+                "%s: Could not create pipes: %s",
+                cmd,
+                err.desc()
+            ));
+            return Err(STATUS_CMD_ERROR);
+        }
+    };
+
+    // Replace any previous coprocess of the same name.
+    close_coprocess(parser, name, false);
+
+    let mut source = WString::new();
+    for arg in argv {
+        source.push_utfstr(&escape(arg));
+        source.push(' ');
+    }
+    source.push('&');
+
+    let mut io = IoChain::new();
+    io.push(Arc::new(IoPipe::new(
+        libc::STDIN_FILENO,
+        true,
+        to_coproc.read,
+    )));
+    io.push(Arc::new(IoPipe::new(
+        libc::STDOUT_FILENO,
+        false,
+        from_coproc.write,
+    )));
+    let res = parser.eval(&source, &io);
+    // Close our copies of the job's ends of the pipes.
+    drop(io);
+
+    let pid = parser
+        .vars()
+        .get(L!("last_pid"))
+        .map(|var| var.as_string())
+        .unwrap_or_default();
+    if !res.status.is_success() {
+        return Err(res.status.status_value());
+    }
+    if pid.is_empty() {
+        return Err(STATUS_CMD_ERROR);
+    }
+
+    let coproc = Coprocess {
+        name: name.to_owned(),
+        pid,
+        output: Some(from_coproc.read),
+        input: Some(to_coproc.write),
+    };
+    let (fds, pid) = (coproc.fds(), coproc.pid.clone());
+    COPROCESSES.lock().unwrap().push(coproc);
+    set_coprocess_vars(parser, name, fds, pid);
+    Ok(SUCCESS)
+}
+
+pub fn coproc(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> BuiltinResult {
+    let cmd = argv[0];
+    let print_hints = false;
+    let mut print_help = false;
+    let mut name = L!("coproc");
+    let mut close = false;
+    let mut close_input = false;
+
+    const SHORT_OPTS: &wstr = L!("+n:ch");
+    const LONG_OPTS: &[WOption] = &[
+        wopt(L!("name"), RequiredArgument, 'n'),
+        wopt(L!("close"), NoArgument, 'c'),
+        wopt(L!("close-input"), NoArgument, '\x01'),
+        wopt(L!("help"), NoArgument, 'h'),
+    ];
+
+    let mut w = WGetopter::new(SHORT_OPTS, LONG_OPTS, argv);
+    while let Some(c) = w.next_opt() {
+        match c {
+            'n' => name = w.woptarg.unwrap(),
+            'c' => close = true,
+            '\x01' => close_input = true,
+            'h' => print_help = true,
+            ':' => {
+                builtin_missing_argument(parser, streams, cmd, argv[w.wopt_index - 1], print_hints);
+                return Err(STATUS_INVALID_ARGS);
+            }
+            ';' => {
+                builtin_unexpected_argument(
+                    parser,
+                    streams,
+                    cmd,
+                    argv[w.wopt_index - 1],
+                    print_hints,
+                );
+                return Err(STATUS_INVALID_ARGS);
+            }
+            '?' => {
+                builtin_unknown_option(parser, streams, cmd, argv[w.wopt_index - 1], print_hints);
+                return Err(STATUS_INVALID_ARGS);
+            }
+            _ => panic!("unexpected retval from WGetopter"),
+        }
+    }
+
+    if print_help {
+        builtin_print_help(parser, streams, cmd);
+        return Ok(SUCCESS);
+    }
+
+    let optind = w.wopt_index;
+    let args = &argv[optind..];
+
+    if close || close_input {
+        if close && close_input {
+            streams.err.appendln(&wgettext_fmt!(
+                BUILTIN_ERR_COMBO2,
+                cmd,
+                wgettext!("--close and --close-input are mutually exclusive")
+            ));
+            return Err(STATUS_INVALID_ARGS);
+        }
+        // Close the named coprocesses, or the one given with --name.
+        let names = if args.is_empty() { &[name][..] } else { args };
+        let mut retval = Ok(SUCCESS);
+        for &name in names {
+            if !close_coprocess(parser, name, close_input) {
+                streams
+                    .err
+                    .appendln(&wgettext_fmt!("%s: %s: no such coprocess", cmd, name));
+                retval = Err(STATUS_CMD_ERROR);
+            }
+        }
+        return retval;
+    }
+
+    if args.is_empty() {
+        // List the coprocesses.
+        for coproc in COPROCESSES.lock().unwrap().iter() {
+            let fds = coproc.fds();
+            streams.out.appendln(&sprintf!(
+                "%s\t%s\t%s\t%s",
+                coproc.name,
+                coproc.pid,
+                fds[0],
+                fds[1]
+            ));
+        }
+        return Ok(SUCCESS);
+    }
+
+    if !valid_var_name(name) {
+        streams.err.append(&varname_error(cmd, name));
+        builtin_print_error_trailer(parser, streams.err, cmd);
+        return Err(STATUS_INVALID_ARGS);
+    }
+
+    start_coprocess(parser, streams, cmd, name, args)
+}
@@ -2,7 +2,8 @@
 
 use crate::fds::make_fd_blocking;
 use crate::parser::ParserEnvSetMode;
-use crate::reader::{reader_save_screen_state, reader_write_title};
+use crate::proc::is_interactive_session;
+use crate::reader::{reader_job_title, reader_save_screen_state, reader_write_title};
 use crate::tokenizer::tok_command;
 use crate::wutil::perror;
 use crate::{env::EnvMode, tty_handoff::TtyHandoff};
@@ -131,6 +132,7 @@ pub fn fg(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Built
         parser.libdata_mut().status_vars.commandline = job.command().to_owned();
     }
     reader_write_title(job.command(), parser, true);
+    let job_title = is_interactive_session().then(|| reader_job_title(job.command(), parser));
 
     // Note if tty transfer fails, we still try running the job.
     parser.job_promote_at(job_pos);
@@ -157,6 +159,9 @@ pub fn fg(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Built
         }
     }
     handoff.to_job_group(job.group.as_ref().unwrap());
+    if let Some(job_title) = &job_title {
+        handoff.set_job_title(job_title);
+    }
     let resumed = job.resume();
     if resumed {
         job.continue_job(parser, /*block_io=*/ None);
@@ -0,0 +1,208 @@
+//! Implementation of the frecency builtin.
+
+use crate::env::Environment;
+use crate::frecency::{frecency_add, frecency_clear, frecency_remove, frecency_search};
+use crate::path::path_apply_working_directory;
+use crate::wutil::{normalize_path, wstat};
+
+use super::prelude::*;
+
+#[derive(Clone, Copy, Default, Eq, PartialEq)]
+enum FrecencyCmd {
+    #[default]
+    Search,
+    Add,
+    Delete,
+    Clear,
+}
+
+impl FrecencyCmd {
+    fn to_wstr(self) -> &'static wstr {
+        match self {
+            FrecencyCmd::Search => L!("search"),
+            FrecencyCmd::Add => L!("add"),
+            FrecencyCmd::Delete => L!("delete"),
+            FrecencyCmd::Clear => L!("clear"),
+        }
+    }
+}
+
+impl TryFrom<&wstr> for FrecencyCmd {
+    type Error = ();
+    fn try_from(val: &wstr) -> Result<Self, ()> {
+        match val {
+            _ if val == "search" => Ok(FrecencyCmd::Search),
+            _ if val == "add" => Ok(FrecencyCmd::Add),
+            _ if val == "delete" => Ok(FrecencyCmd::Delete),
+            _ if val == "clear" => Ok(FrecencyCmd::Clear),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Default)]
+struct FrecencyCmdOpts {
+    max_items: Option<usize>,
+    show_score: bool,
+    print_help: bool,
+}
+
+const SHORT_OPTIONS: &wstr = L!("hn:s");
+const LONG_OPTIONS: &[WOption] = &[
+    wopt(L!("help"), ArgType::NoArgument, 'h'),
+    wopt(L!("max"), ArgType::RequiredArgument, 'n'),
+    wopt(L!("show-score"), ArgType::NoArgument, 's'),
+];
+
+fn parse_cmd_opts(
+    opts: &mut FrecencyCmdOpts,
+    optind: &mut usize,
+    argv: &mut [&wstr],
+    parser: &Parser,
+    streams: &mut IoStreams,
+) -> BuiltinResult {
+    let Some(&cmd) = argv.first() else {
+        return Err(STATUS_INVALID_ARGS);
+    };
+
+    let mut w = WGetopter::new(SHORT_OPTIONS, LONG_OPTIONS, argv);
+    while let Some(opt) = w.next_opt() {
+        match opt {
+            'n' => match fish_wcstoi(w.woptarg.unwrap()) {
+                Ok(x) if x >= 0 => opts.max_items = Some(x as usize),
+                _ => {
+                    streams.err.appendln(&wgettext_fmt!(
+                        BUILTIN_ERR_NOT_NUMBER,
+                        cmd,
+                        w.woptarg.unwrap()
+                    ));
+                    return Err(STATUS_INVALID_ARGS);
+                }
+            },
+            's' => {
+                opts.show_score = true;
+            }
+            'h' => {
+                opts.print_help = true;
+            }
+            ':' => {
+                builtin_missing_argument(parser, streams, cmd, argv[w.wopt_index - 1], true);
+                return Err(STATUS_INVALID_ARGS);
+            }
+            ';' => {
+                builtin_unexpected_argument(parser, streams, cmd, argv[w.wopt_index - 1], true);
+                return Err(STATUS_INVALID_ARGS);
+            }
+            '?' => {
+                builtin_unknown_option(parser, streams, cmd, argv[w.wopt_index - 1], true);
+                return Err(STATUS_INVALID_ARGS);
+            }
+            _ => {
+                panic!("unexpected retval from WGetopter");
+            }
+        }
+    }
+
+    *optind = w.wopt_index;
+    Ok(SUCCESS)
+}
+
+/// Query and edit the database of visited directories.
+pub fn frecency(parser: &Parser, streams: &mut IoStreams, args: &mut [&wstr]) -> BuiltinResult {
+    let mut opts = FrecencyCmdOpts::default();
+    let mut optind = 0;
+
+    parse_cmd_opts(&mut opts, &mut optind, args, parser, streams)?;
+
+    let Some(&cmd) = args.first() else {
+        return Err(STATUS_INVALID_ARGS);
+    };
+
+    if opts.print_help {
+        builtin_print_help(parser, streams, cmd);
+        return Ok(SUCCESS);
+    }
+
+    // Without a subcommand, all arguments are keywords to search for.
+    let mut subcmd = FrecencyCmd::default();
+    if optind < args.len() {
+        if let Ok(c) = FrecencyCmd::try_from(args[optind]) {
+            subcmd = c;
+            optind += 1;
+        }
+    }
+    let args = &args[optind..];
+
+    if subcmd != FrecencyCmd::Search && (opts.max_items.is_some() || opts.show_score) {
+        streams.err.appendln(&wgettext_fmt!(
+            "%s: %s: subcommand takes no options",
+            cmd,
+            subcmd.to_wstr()
+        ));
+        return Err(STATUS_INVALID_ARGS);
+    }
+
+    // Directories are stored as absolute paths.
+    let pwd = parser.vars().get_pwd_slash();
+    let to_absolute = |path: &wstr| normalize_path(&path_apply_working_directory(path, &pwd), true);
+
+    let result = match subcmd {
+        FrecencyCmd::Search => {
+            let matches = frecency_search(args, None);
+            if matches.is_empty() {
+                return Err(STATUS_CMD_ERROR);
+            }
+            for (entry, score) in matches.iter().take(opts.max_items.unwrap_or(usize::MAX)) {
+                if opts.show_score {
+                    streams.out.append(&sprintf!("%.2f\t", score));
+                }
+                streams.out.appendln(&entry.path);
+            }
+            return Ok(SUCCESS);
+        }
+        FrecencyCmd::Add => {
+            let mut paths = vec![];
+            for &arg in args {
+                let path = to_absolute(arg);
+                if !wstat(&path).is_ok_and(|md| md.is_dir()) {
+                    streams
+                        .err
+                        .appendln(&wgettext_fmt!("%s: '%s' is not a directory", cmd, arg));
+                    return Err(STATUS_CMD_ERROR);
+                }
+                paths.push(path);
+            }
+            frecency_add(&paths)
+        }
+        FrecencyCmd::Delete => {
+            let paths: Vec<WString> = args.iter().map(|arg| to_absolute(arg)).collect();
+            match frecency_remove(&paths) {
+                Ok(0) if !paths.is_empty() => return Err(STATUS_CMD_ERROR),
+                result => result.map(|_| ()),
+            }
+        }
+        FrecencyCmd::Clear => {
+            if !args.is_empty() {
+                streams.err.appendln(&wgettext_fmt!(
+                    BUILTIN_ERR_ARG_COUNT2,
+                    cmd,
+                    subcmd.to_wstr(),
+                    0,
+                    args.len()
+                ));
+                return Err(STATUS_INVALID_ARGS);
+            }
+            frecency_clear()
+        }
+    };
+
+    if let Err(err) = result {
+        streams.err.appendln(&wgettext_fmt!(
+            "%s: Updating the directory database failed: %s",
+            cmd,
+            err.to_string()
+        ));
+        return Err(STATUS_CMD_ERROR);
+    }
+    Ok(SUCCESS)
+}
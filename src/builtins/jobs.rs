@@ -6,7 +6,7 @@
 use crate::job_group::{JobId, MaybeJobId};
 use crate::localization::{wgettext, wgettext_fmt};
 use crate::parser::Parser;
-use crate::proc::{HAVE_PROC_STAT, Job, clock_ticks_to_seconds, proc_get_jiffies};
+use crate::proc::{HAVE_PROC_STAT, Job, JobRef, clock_ticks_to_seconds, proc_get_jiffies};
 use crate::wutil::fish_wcstoi;
 use fish_wgetopt::{ArgType, WGetopter, WOption, wopt};
 use fish_widestring::{L, WExt, WString, wstr};
@@ -124,11 +124,75 @@ fn builtin_jobs_print(j: &Job, mode: JobsPrintMode, header: bool, streams: &mut
     }
 }
 
+/// Return the state of a job or process for JSON output.
+fn json_state(completed: bool, stopped: bool) -> &'static str {
+    if completed {
+        "completed"
+    } else if stopped {
+        "stopped"
+    } else {
+        "running"
+    }
+}
+
+/// Append a JSON object describing the specified job to `out`.
+fn append_job_json(out: &mut WString, j: &Job) {
+    sprintf!(=> out, "{\"id\": %d, \"internal_id\": %u, \"pgid\": ", j.job_id(), j.internal_job_id);
+    match j.get_pgid() {
+        Some(pgid) => sprintf!(=> out, "%d", pgid),
+        None => out.push_str("null"),
+    }
+    sprintf!(=> out,
+        ", \"state\": \"%s\", \"start_time\": %.0f, \"disowned\": %s, \"command\": ",
+        json_state(j.is_completed(), j.is_stopped()),
+        j.start_time,
+        if j.flags().disown_requested { "true" } else { "false" }
+    );
+    append_json_string(out, j.command());
+    out.push_str(", \"processes\": [");
+    for (i, p) in j.processes().iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        out.push_str("{\"pid\": ");
+        match p.pid() {
+            Some(pid) => sprintf!(=> out, "%d", pid),
+            None => out.push_str("null"),
+        }
+        out.push_str(", \"command\": ");
+        append_json_string(out, p.argv0().unwrap_or_default());
+        sprintf!(=> out,
+            ", \"state\": \"%s\", \"status\": ",
+            json_state(p.is_completed(), p.is_stopped())
+        );
+        let status = p.status();
+        if p.is_completed() && !status.is_empty() {
+            sprintf!(=> out, "%d", status.status_value());
+        } else {
+            out.push_str("null");
+        }
+        out.push('}');
+    }
+    out.push_str("]}");
+}
+
+/// Print the specified jobs as a JSON array, one job per line.
+fn builtin_jobs_print_json(jobs: &[JobRef], streams: &mut IoStreams) {
+    let mut out = WString::from("[");
+    for (i, j) in jobs.iter().enumerate() {
+        out.push_str(if i == 0 { "\n  " } else { ",\n  " });
+        append_job_json(&mut out, j);
+    }
+    out.push_str(if jobs.is_empty() { "]\n" } else { "\n]\n" });
+    streams.out.append(&out);
+}
+
 const SHORT_OPTIONS: &wstr = L!("cghlpq");
 const LONG_OPTIONS: &[WOption] = &[
     wopt(L!("command"), ArgType::NoArgument, 'c'),
     wopt(L!("group"), ArgType::NoArgument, 'g'),
     wopt(L!("help"), ArgType::NoArgument, 'h'),
+    wopt(L!("json"), ArgType::NoArgument, '\x01'),
     wopt(L!("last"), ArgType::NoArgument, 'l'),
     wopt(L!("pid"), ArgType::NoArgument, 'p'),
     wopt(L!("quiet"), ArgType::NoArgument, 'q'),
@@ -145,6 +209,8 @@ pub fn jobs(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
     let argc = argv.len();
     let mut found = false;
     let mut mode = JobsPrintMode::Default;
+    // Print all information as a JSON array. This overrides the mode, unless it is given later.
+    let mut json = false;
     let mut print_last = false;
 
     let mut w = WGetopter::new(SHORT_OPTIONS, LONG_OPTIONS, argv);
@@ -152,15 +218,22 @@ pub fn jobs(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
         match c {
             'p' => {
                 mode = JobsPrintMode::PrintPid;
+                json = false;
             }
             'q' => {
                 mode = JobsPrintMode::PrintNothing;
+                json = false;
             }
             'c' => {
                 mode = JobsPrintMode::PrintCommand;
+                json = false;
             }
             'g' => {
                 mode = JobsPrintMode::PrintGroup;
+                json = false;
+            }
+            '\x01' => {
+                json = true;
             }
             'l' => {
                 print_last = true;
@@ -185,15 +258,32 @@ pub fn jobs(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
         }
     }
 
+    // In JSON mode, the jobs are collected and printed at the end.
+    // This also lists jobs which have completed or were disowned but are not yet cleaned up.
+    let is_listed = |j: &Job| j.is_visible() || (json && j.is_constructed());
+    let mut json_jobs: Vec<JobRef> = vec![];
+    let mut print_job = |j: &JobRef, header: bool, streams: &mut IoStreams| {
+        if json {
+            json_jobs.push(j.clone());
+        } else {
+            builtin_jobs_print(j, mode, header, streams);
+        }
+    };
+
     if print_last {
         // Ignore unconstructed jobs, i.e. ourself.
-        for j in &parser.jobs()[..] {
-            if j.is_visible() {
-                builtin_jobs_print(j, mode, !streams.out_is_redirected, streams);
-                return Ok(SUCCESS);
-            }
+        let last_job = parser.jobs().iter().find(|j| is_listed(j)).cloned();
+        if let Some(j) = &last_job {
+            print_job(j, !streams.out_is_redirected, streams);
         }
-        return Err(STATUS_CMD_ERROR);
+        if json {
+            builtin_jobs_print_json(&json_jobs, streams);
+        }
+        return if last_job.is_some() {
+            Ok(SUCCESS)
+        } else {
+            Err(STATUS_CMD_ERROR)
+        };
     }
 
     if w.wopt_index < argc {
@@ -226,7 +316,7 @@ pub fn jobs(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
             }
 
             if let Some(j) = j.filter(|j| !j.is_completed() && j.is_constructed()) {
-                builtin_jobs_print(&j, mode, false, streams);
+                print_job(&j, false, streams);
                 found = true;
             } else {
                 if mode != JobsPrintMode::PrintNothing {
@@ -240,16 +330,20 @@ pub fn jobs(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
     } else {
         for j in &parser.jobs()[..] {
             // Ignore unconstructed jobs, i.e. ourself.
-            if j.is_visible() {
-                builtin_jobs_print(j, mode, !found && !streams.out_is_redirected, streams);
+            if is_listed(j) {
+                print_job(j, !found && !streams.out_is_redirected, streams);
                 found = true;
             }
         }
     }
 
+    if json {
+        builtin_jobs_print_json(&json_jobs, streams);
+    }
+
     if !found {
         // Do not babble if not interactive.
-        if !streams.out_is_redirected && mode != JobsPrintMode::PrintNothing {
+        if !streams.out_is_redirected && mode != JobsPrintMode::PrintNothing && !json {
             streams
                 .out
                 .appendln(&wgettext_fmt!("%s: There are no jobs", argv[0]));
@@ -0,0 +1,168 @@
+//! Implementation of the limit builtin.
+
+use std::cmp::Ordering;
+
+use fish_fallback::wcscasecmp;
+use libc::{RLIM_INFINITY, c_int, rlim_t};
+
+use super::prelude::*;
+use super::ulimit::{get_desc, getrlimit, resource_for_switch};
+use crate::common::{ScopeGuard, escape};
+
+/// The highest and lowest niceness.
+const MIN_NICENESS: c_int = -20;
+const MAX_NICENESS: c_int = 19;
+
+/// Parse a limit, which is "unlimited" or a number in the units used by ulimit. Limits on sizes,
+/// which ulimit counts in kilobytes, also accept a number of bytes with a K, M, G or T suffix.
+fn parse_limit(arg: &wstr, multiplier: rlim_t) -> Option<rlim_t> {
+    if wcscasecmp(arg, L!("unlimited")) == Ordering::Equal {
+        return Some(RLIM_INFINITY);
+    }
+    let base: rlim_t = 1024;
+    let (digits, multiplier) = match arg.as_char_slice().last().map(|c| c.to_ascii_uppercase()) {
+        Some(suffix @ ('K' | 'M' | 'G' | 'T')) if multiplier != 1 => {
+            let exp = "KMGT".find(suffix).unwrap() + 1;
+            (arg.slice_to(arg.len() - 1), base.pow(exp as u32))
+        }
+        _ => (arg, multiplier),
+    };
+    let value = rlim_t::try_from(fish_wcstoul(digits).ok()?).ok()?;
+    value.checked_mul(multiplier)
+}
+
+pub fn limit(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> BuiltinResult {
+    let cmd = argv[0];
+    let print_hints = false;
+    let mut print_help = false;
+    let mut limits = parser.libdata().process_limits;
+
+    const SHORT_OPTS: &wstr = L!("+b:c:d:e:f:i:l:m:n:q:r:s:t:u:v:w:y:K:P:T:h");
+    const LONG_OPTS: &[WOption] = &[
+        wopt(L!("socket-buffers"), RequiredArgument, 'b'),
+        wopt(L!("core-size"), RequiredArgument, 'c'),
+        wopt(L!("data-size"), RequiredArgument, 'd'),
+        wopt(L!("nice"), RequiredArgument, 'e'),
+        wopt(L!("file-size"), RequiredArgument, 'f'),
+        wopt(L!("pending-signals"), RequiredArgument, 'i'),
+        wopt(L!("lock-size"), RequiredArgument, 'l'),
+        wopt(L!("resident-set-size"), RequiredArgument, 'm'),
+        wopt(L!("file-descriptor-count"), RequiredArgument, 'n'),
+        wopt(L!("queue-size"), RequiredArgument, 'q'),
+        wopt(L!("realtime-priority"), RequiredArgument, 'r'),
+        wopt(L!("stack-size"), RequiredArgument, 's'),
+        wopt(L!("cpu-time"), RequiredArgument, 't'),
+        wopt(L!("process-count"), RequiredArgument, 'u'),
+        wopt(L!("virtual-memory-size"), RequiredArgument, 'v'),
+        wopt(L!("swap-size"), RequiredArgument, 'w'),
+        wopt(L!("realtime-maxtime"), RequiredArgument, 'y'),
+        wopt(L!("kernel-queues"), RequiredArgument, 'K'),
+        wopt(L!("ptys"), RequiredArgument, 'P'),
+        wopt(L!("threads"), RequiredArgument, 'T'),
+        wopt(L!("niceness"), RequiredArgument, '\x01'),
+        wopt(L!("help"), NoArgument, 'h'),
+    ];
+
+    let mut w = WGetopter::new(SHORT_OPTS, LONG_OPTS, argv);
+    while let Some(c) = w.next_opt() {
+        match c {
+            'h' => print_help = true,
+            '\x01' => {
+                let arg = w.woptarg.unwrap();
+                let Ok(increment) = fish_wcstoi(arg) else {
+                    streams
+                        .err
+                        .appendln(&wgettext_fmt!("%s: %s: invalid niceness", cmd, arg));
+                    builtin_print_error_trailer(parser, streams.err, cmd);
+                    return Err(STATUS_INVALID_ARGS);
+                };
+                // Like nice(1), the increment is relative to the niceness commands would
+                // otherwise get.
+                let niceness = limits
+                    .niceness
+                    .unwrap_or_else(|| unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) });
+                limits.niceness = Some(
+                    niceness
+                        .saturating_add(increment)
+                        .clamp(MIN_NICENESS, MAX_NICENESS),
+                );
+            }
+            ':' => {
+                builtin_missing_argument(parser, streams, cmd, argv[w.wopt_index - 1], print_hints);
+                return Err(STATUS_INVALID_ARGS);
+            }
+            ';' => {
+                builtin_unexpected_argument(
+                    parser,
+                    streams,
+                    cmd,
+                    argv[w.wopt_index - 1],
+                    print_hints,
+                );
+                return Err(STATUS_INVALID_ARGS);
+            }
+            '?' => {
+                builtin_unknown_option(parser, streams, cmd, argv[w.wopt_index - 1], print_hints);
+                return Err(STATUS_INVALID_ARGS);
+            }
+            c => {
+                let Some((resource, multiplier)) = resource_for_switch(c) else {
+                    streams.err.appendln(&wgettext_fmt!(
+                        "%s: Resource limit not available on this operating system",
+                        cmd
+                    ));
+                    builtin_print_error_trailer(parser, streams.err, cmd);
+                    return Err(STATUS_INVALID_ARGS);
+                };
+                let arg = w.woptarg.unwrap();
+                let Some(value) = parse_limit(arg, multiplier) else {
+                    streams
+                        .err
+                        .appendln(&wgettext_fmt!("%s: Invalid limit '%s'", cmd, arg));
+                    builtin_print_error_trailer(parser, streams.err, cmd);
+                    return Err(STATUS_INVALID_ARGS);
+                };
+                // Only root can raise a hard limit. Check here rather than failing in the child.
+                let Some((_, rlim_max)) = getrlimit(resource) else {
+                    return Err(STATUS_CMD_ERROR);
+                };
+                if value > rlim_max && !nix::unistd::geteuid().is_root() {
+                    streams.err.appendln(&wgettext_fmt!(
+                        "%s: Permission denied when changing resource of type '%s'",
+                        cmd,
+                        get_desc(resource)
+                    ));
+                    return Err(STATUS_CMD_ERROR);
+                }
+                limits.set_rlimit(resource as c_int, value);
+            }
+        }
+    }
+
+    if print_help {
+        builtin_print_help(parser, streams, cmd);
+        return Ok(SUCCESS);
+    }
+
+    let args = &w.argv[w.wopt_index..];
+    if args.is_empty() {
+        streams
+            .err
+            .appendln(&wgettext_fmt!("%s: missing command", cmd));
+        builtin_print_error_trailer(parser, streams.err, cmd);
+        return Err(STATUS_INVALID_ARGS);
+    }
+
+    let mut source = WString::new();
+    for arg in args {
+        source.push_utfstr(&escape(arg));
+        source.push(' ');
+    }
+
+    // External commands started while this is in scope get the limits after they are forked.
+    let saved_limits = std::mem::replace(&mut parser.libdata_mut().process_limits, limits);
+    let _restore_limits = ScopeGuard::new((), |()| {
+        parser.libdata_mut().process_limits = saved_limits;
+    });
+    super::eval::eval(parser, streams, &mut [cmd, &source])
+}
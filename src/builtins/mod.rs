@@ -14,6 +14,7 @@
 pub mod complete;
 pub mod contains;
 pub mod r#continue;
+pub mod coproc;
 pub mod count;
 pub mod disown;
 pub mod echo;
@@ -24,12 +25,15 @@
 pub mod fg;
 pub mod fish_indent;
 pub mod fish_key_reader;
+pub mod frecency;
 pub mod function;
 pub mod functions;
 pub mod r#gettext;
 pub mod history;
 pub mod jobs;
+pub mod limit;
 pub mod math;
+pub mod parallel;
 pub mod path;
 pub mod printf;
 pub mod pwd;
@@ -43,6 +47,7 @@
 pub mod status;
 pub mod string;
 pub mod test;
+pub mod trap;
 pub mod r#true;
 pub mod r#type;
 pub mod ulimit;
@@ -0,0 +1,385 @@
+//! Implementation of the parallel builtin.
+
+use super::prelude::*;
+use super::status::fish_path;
+use super::wait::{WaitHandleQuery, find_wait_handles};
+use crate::common::{escape, valid_var_name};
+use crate::env::{EnvMode, Environment};
+use crate::function;
+use crate::io::{IoBufferfill, IoChain, SeparatedBuffer};
+use crate::parse_execution::varname_error;
+use crate::parser::{Block, BlockType, ParserEnvSetMode};
+use crate::proc::{Pid, proc_wait_any};
+use crate::reader::fish_process::session_script;
+use crate::signal::SigChecker;
+use crate::wait_handle::WaitHandleRef;
+use libc::{STDERR_FILENO, STDOUT_FILENO};
+use std::sync::Arc;
+
+/// The separator between the command and the items.
+const ITEM_SEPARATOR: &wstr = L!(":::");
+
+/// The placeholder replaced by the item in the command's arguments.
+const ITEM_PLACEHOLDER: &wstr = L!("{}");
+
+/// The chunk size for reading items from stdin.
+const ITEM_CHUNK_SIZE: usize = 1024;
+
+#[derive(Default)]
+struct Options {
+    jobs: Option<usize>,
+    keep_order: bool,
+    status_var: Option<WString>,
+}
+
+/// An item which was started as a background job.
+struct Task {
+    /// The index of the item.
+    index: usize,
+    /// The job's last process, or None if the job did not run a process, like a builtin.
+    wait_handle: Option<WaitHandleRef>,
+    /// The exit status if the job did not run a process.
+    status: i32,
+    /// The buffers collecting the job's output.
+    stdout: Arc<IoBufferfill>,
+    stderr: Arc<IoBufferfill>,
+}
+
+impl Task {
+    fn is_completed(&self) -> bool {
+        self.wait_handle.as_ref().is_none_or(|wh| wh.is_completed())
+    }
+}
+
+/// A completed item, with its exit status and output.
+struct Done {
+    status: i32,
+    stdout: SeparatedBuffer,
+    stderr: SeparatedBuffer,
+}
+
+/// Return the job to run a function in a new fish process, with the arguments as $argv.
+/// Functions can't run in the background, so each item gets its own process. The process reads
+/// the session script, which sets up the variables and functions of this session, and the call of
+/// the function from its stdin, so their size is not limited like that of arguments.
+fn function_job(session: &wstr, name: &wstr, args: &[WString]) -> WString {
+    let mut script = session.to_owned();
+    script.push_utfstr(&escape(name));
+    for arg in args {
+        script.push(' ');
+        script.push_utfstr(&escape(arg));
+    }
+    script.push('\n');
+    let mut job = L!("builtin printf %s ").to_owned();
+    job.push_utfstr(&escape(&script));
+    job.push_str(" | command ");
+    job.push_utfstr(&escape(&fish_path()));
+    job.push_str(" --no-config");
+    job
+}
+
+/// Return the job to run a command with the arguments.
+fn command_job(args: &[WString]) -> WString {
+    let mut job = WString::new();
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 {
+            job.push(' ');
+        }
+        job.push_utfstr(&escape(arg));
+    }
+    job
+}
+
+/// Return the arguments to run for an item: the command's arguments with each `{}` replaced by
+/// the item, or with the item appended if there is no `{}`.
+fn item_arguments(cmd: &[&wstr], item: &wstr) -> Vec<WString> {
+    let has_placeholder = cmd.iter().any(|arg| arg.find(ITEM_PLACEHOLDER).is_some());
+    let mut args: Vec<WString> = cmd
+        .iter()
+        .map(|arg| {
+            if has_placeholder {
+                arg.replace(ITEM_PLACEHOLDER, item)
+            } else {
+                (*arg).to_owned()
+            }
+        })
+        .collect();
+    if !has_placeholder {
+        args.push(item.to_owned());
+    }
+    args
+}
+
+/// Start running an item's job in the background, with its output going to buffers.
+fn start_task(parser: &Parser, index: usize, job: &wstr) -> Result<Task, ErrorCode> {
+    let read_limit = parser.scope().read_limit;
+    let (Ok(stdout), Ok(stderr)) = (
+        IoBufferfill::create_opts(read_limit, STDOUT_FILENO),
+        IoBufferfill::create_opts(read_limit, STDERR_FILENO),
+    ) else {
+        // We were unable to create a pipe, probably fd exhaustion.
+        return Err(STATUS_CMD_ERROR);
+    };
+
+    let source = job.to_owned() + L!(" &");
+
+    let mut io = IoChain::new();
+    io.push(stdout.clone());
+    io.push(stderr.clone());
+
+    // $last_pid tells us which process was started, if any. It is restored afterwards, since the
+    // items are not started by the user.
+    let mode = ParserEnvSetMode::new(EnvMode::GLOBAL);
+    let saved_last_pid = parser.vars().getf(L!("last_pid"), EnvMode::GLOBAL);
+    parser.set_empty(L!("last_pid"), mode);
+    // Run the job inside a block, so fish doesn't announce when it ends like it does for
+    // interactive background jobs.
+    let block = parser.push_block(Block::scope_block(BlockType::begin));
+    let res = parser.eval(&source, &io);
+    parser.pop_block(block);
+
+    let pid = parser
+        .vars()
+        .getf(L!("last_pid"), EnvMode::GLOBAL)
+        .and_then(|var| fish_wcstoi(&var.as_string()).ok())
+        .filter(|&pid| pid > 0);
+    match saved_last_pid {
+        Some(var) => parser.set_var(L!("last_pid"), mode, var.as_list().to_vec()),
+        None => parser.remove_var(L!("last_pid"), mode),
+    };
+    let mut handles = Vec::new();
+    if let Some(pid) = pid {
+        find_wait_handles(WaitHandleQuery::Pid(Pid::new(pid)), parser, &mut handles);
+    }
+    Ok(Task {
+        index,
+        wait_handle: handles.pop(),
+        status: res.status.status_value(),
+        stdout,
+        stderr,
+    })
+}
+
+/// Collect the exit status and output of a completed task.
+fn finish_task(parser: &Parser, task: Task) -> (usize, Done) {
+    let status = match task.wait_handle {
+        Some(wh) => {
+            parser.mut_wait_handles().remove(&wh);
+            wh.status().unwrap_or(task.status)
+        }
+        None => task.status,
+    };
+    let done = Done {
+        status,
+        stdout: IoBufferfill::finish(task.stdout),
+        stderr: IoBufferfill::finish(task.stderr),
+    };
+    (task.index, done)
+}
+
+/// Wait until one of the running tasks has completed, and remove it from the list.
+/// Return None if we got interrupted.
+fn wait_for_any(parser: &Parser, running: &mut Vec<Task>, sigint: &mut SigChecker) -> Option<Task> {
+    loop {
+        if let Some(idx) = running.iter().position(Task::is_completed) {
+            return Some(running.remove(idx));
+        }
+        if sigint.check() {
+            return None;
+        }
+        proc_wait_any(parser);
+    }
+}
+
+fn print_output(streams: &mut IoStreams, done: &Done) {
+    streams.out.append_narrow_buffer(&done.stdout);
+    streams.err.append_narrow_buffer(&done.stderr);
+}
+
+/// Run the command once for each item, with at most `jobs` items running at a time.
+/// Return the exit statuses of all items, or None if interrupted.
+fn run_items(
+    parser: &Parser,
+    streams: &mut IoStreams,
+    opts: &Options,
+    cmd: &[&wstr],
+    items: &[WString],
+) -> Result<Option<Vec<i32>>, ErrorCode> {
+    // Functions run in a new fish process, since they can't run in the background.
+    let session = function::get_props_autoload(cmd[0], parser)
+        .is_some()
+        .then(|| session_script(parser.vars()));
+
+    let jobs = opts.jobs.unwrap_or_else(|| {
+        std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+    });
+    let mut results: Vec<Option<Done>> = items.iter().map(|_| None).collect();
+    let mut next_to_print = 0;
+    let mut running = Vec::new();
+    let mut sigint = SigChecker::new_sighupint();
+
+    let mut pending = items.iter().enumerate();
+    loop {
+        if running.len() < jobs {
+            if let Some((index, item)) = pending.next() {
+                let job = match &session {
+                    Some(session) => {
+                        function_job(session, cmd[0], &item_arguments(&cmd[1..], item))
+                    }
+                    None => command_job(&item_arguments(cmd, item)),
+                };
+                running.push(start_task(parser, index, &job)?);
+                continue;
+            }
+        }
+        if running.is_empty() {
+            break;
+        }
+        let Some(task) = wait_for_any(parser, &mut running, &mut sigint) else {
+            // Stop waiting. Our buffers are closed, so the jobs can't block on their output.
+            for task in running {
+                finish_task(parser, task);
+            }
+            return Ok(None);
+        };
+        let (index, done) = finish_task(parser, task);
+        if !opts.keep_order {
+            print_output(streams, &done);
+        }
+        results[index] = Some(done);
+        // With --keep-order, print the output of the items completed so far, in order.
+        while opts.keep_order && results.get(next_to_print).is_some_and(Option::is_some) {
+            print_output(streams, results[next_to_print].as_ref().unwrap());
+            next_to_print += 1;
+        }
+    }
+
+    Ok(Some(
+        results
+            .into_iter()
+            .map(|done| done.map_or(STATUS_CMD_ERROR, |done| done.status))
+            .collect(),
+    ))
+}
+
+pub fn parallel(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> BuiltinResult {
+    let cmd = argv[0];
+    let print_hints = false;
+    let mut print_help = false;
+    let mut opts = Options::default();
+
+    const SHORT_OPTS: &wstr = L!("+j:kh");
+    const LONG_OPTS: &[WOption] = &[
+        wopt(L!("jobs"), RequiredArgument, 'j'),
+        wopt(L!("keep-order"), NoArgument, 'k'),
+        wopt(L!("status-var"), RequiredArgument, '\x01'),
+        wopt(L!("help"), NoArgument, 'h'),
+    ];
+
+    let mut w = WGetopter::new(SHORT_OPTS, LONG_OPTS, argv);
+    while let Some(c) = w.next_opt() {
+        match c {
+            'j' => {
+                let arg = w.woptarg.unwrap();
+                match fish_wcstoi(arg) {
+                    Ok(n) if n > 0 => opts.jobs = Some(n as usize),
+                    _ => {
+                        streams.err.appendln(&wgettext_fmt!(
+                            "%s: %s: invalid number of jobs",
+                            cmd,
+                            arg
+                        ));
+                        builtin_print_error_trailer(parser, streams.err, cmd);
+                        return Err(STATUS_INVALID_ARGS);
+                    }
+                }
+            }
+            'k' => opts.keep_order = true,
+            '\x01' => {
+                let name = w.woptarg.unwrap();
+                if !valid_var_name(name) {
+                    streams.err.append(&varname_error(cmd, name));
+                    builtin_print_error_trailer(parser, streams.err, cmd);
+                    return Err(STATUS_INVALID_ARGS);
+                }
+                opts.status_var = Some(name.to_owned());
+            }
+            'h' => print_help = true,
+            ':' => {
+                builtin_missing_argument(parser, streams, cmd, argv[w.wopt_index - 1], print_hints);
+                return Err(STATUS_INVALID_ARGS);
+            }
+            ';' => {
+                builtin_unexpected_argument(
+                    parser,
+                    streams,
+                    cmd,
+                    argv[w.wopt_index - 1],
+                    print_hints,
+                );
+                return Err(STATUS_INVALID_ARGS);
+            }
+            '?' => {
+                builtin_unknown_option(parser, streams, cmd, argv[w.wopt_index - 1], print_hints);
+                return Err(STATUS_INVALID_ARGS);
+            }
+            _ => panic!("unexpected retval from WGetopter"),
+        }
+    }
+
+    if print_help {
+        builtin_print_help(parser, streams, cmd);
+        return Ok(SUCCESS);
+    }
+
+    let optind = w.wopt_index;
+    let mut args = &argv[optind..];
+    if args.first() == Some(&L!("--")) {
+        args = &args[1..];
+    }
+
+    // The items follow the separator, or are read from stdin, one per line.
+    let (command, items): (&[&wstr], Vec<WString>) =
+        match args.iter().position(|&arg| arg == ITEM_SEPARATOR) {
+            Some(sep) => (
+                &args[..sep],
+                args[sep + 1..]
+                    .iter()
+                    .map(|&item| item.to_owned())
+                    .collect(),
+            ),
+            None => {
+                let mut argidx = 0;
+                let items = Arguments::new(&[], &mut argidx, streams, ITEM_CHUNK_SIZE)
+                    .map(|item| item.arg.into_owned())
+                    .collect();
+                (args, items)
+            }
+        };
+
+    if command.is_empty() {
+        streams
+            .err
+            .appendln(&wgettext_fmt!("%s: missing command", cmd));
+        builtin_print_error_trailer(parser, streams.err, cmd);
+        return Err(STATUS_INVALID_ARGS);
+    }
+
+    let Some(statuses) = run_items(parser, streams, &opts, command, &items)? else {
+        return Err(128 + libc::SIGINT);
+    };
+
+    if let Some(name) = &opts.status_var {
+        parser.set_var_and_fire(
+            name,
+            ParserEnvSetMode::user(EnvMode::empty()),
+            statuses.iter().map(|status| status.to_wstring()).collect(),
+        );
+    }
+
+    // Return the status of the first item that failed.
+    match statuses.into_iter().find(|&status| status != 0) {
+        Some(status) => BuiltinResult::from_dynamic(status),
+        None => Ok(SUCCESS),
+    }
+}
@@ -11,6 +11,7 @@
 use crate::env::Environment;
 use crate::env::READ_BYTE_LIMIT;
 use crate::env::{EnvVar, EnvVarFlags};
+use crate::fd_readable_set::{Timeout, is_fd_readable};
 use crate::input_common::DecodeState;
 use crate::input_common::InvalidPolicy;
 use crate::input_common::decode_one_codepoint_utf8;
@@ -18,6 +19,7 @@
 use crate::parse_execution::varname_error;
 use crate::parser::ParserEnvSetMode;
 use crate::reader::ReaderConfig;
+use crate::reader::ReadlineResult;
 use crate::reader::commandline_set_buffer;
 use crate::reader::{reader_pop, reader_push, reader_readline, set_shell_modes_temporarily};
 use crate::tokenizer::TOK_ACCEPT_UNFINISHED;
@@ -31,6 +33,7 @@
 use std::num::NonZeroUsize;
 use std::os::fd::RawFd;
 use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
 
 #[derive(Clone, Copy, Eq, PartialEq)]
 pub(crate) enum TokenOutputMode {
@@ -58,6 +61,8 @@ struct Options {
     to_stdout: bool,
     nchars: Option<NonZeroUsize>,
     one_line: bool,
+    timeout: Option<Duration>,
+    keep_partial: bool,
 }
 
 impl Options {
@@ -90,6 +95,8 @@ fn new() -> Self {
     wopt(L!("silent"), ArgType::NoArgument, 's'),
     wopt(L!("tokenize"), ArgType::NoArgument, 't'),
     wopt(L!("tokenize-raw"), ArgType::NoArgument, '\x01'),
+    wopt(L!("timeout"), ArgType::RequiredArgument, '\x02'),
+    wopt(L!("keep-partial"), ArgType::NoArgument, '\x03'),
     wopt(L!("unexport"), ArgType::NoArgument, 'u'),
     wopt(L!("universal"), ArgType::NoArgument, 'U'),
 ];
@@ -204,6 +211,12 @@ fn parse_cmd_opts(
                 }
                 opts.token_mode = Some(new_mode);
             }
+            '\x02' => {
+                opts.timeout = Some(parse_seconds(parser, streams, cmd, w.woptarg.unwrap())?);
+            }
+            '\x03' => {
+                opts.keep_partial = true;
+            }
             'U' => {
                 opts.place.mode |= EnvMode::UNIVERSAL;
             }
@@ -251,6 +264,7 @@ fn read_interactive(
     right_prompt: &wstr,
     commandline: Option<&WString>,
     inputfd: RawFd,
+    deadline: Option<Instant>,
 ) -> BuiltinResult {
     let mut exit_res = Ok(SUCCESS);
 
@@ -272,6 +286,7 @@ fn read_interactive(
         right_prompt_cmd: right_prompt.to_owned(),
         event: L!("fish_read"),
 
+        timeout: deadline.map(|deadline| deadline.saturating_duration_since(Instant::now())),
         inputfd,
 
         ..Default::default()
@@ -294,24 +309,48 @@ fn read_interactive(
         let _interactive = parser.push_scope(|s| s.is_interactive = true);
         reader_readline(parser, old_modes, nchars)
     };
-    if let Some(line) = mline {
-        *buff = line;
-        if let Some(nchars) = nchars.map(usize::from) {
-            // Line may be longer than nchars if a keybinding used `commandline -i`
-            // note: we're deliberately throwing away the tail of the commandline.
-            // It shouldn't be unread because it was produced with `commandline -i`,
-            // not typed.
-            if nchars < buff.len() {
-                buff.truncate(nchars);
+    match mline {
+        ReadlineResult::Line(line) => {
+            *buff = line;
+            if let Some(nchars) = nchars.map(usize::from) {
+                // Line may be longer than nchars if a keybinding used `commandline -i`
+                // note: we're deliberately throwing away the tail of the commandline.
+                // It shouldn't be unread because it was produced with `commandline -i`,
+                // not typed.
+                if nchars < buff.len() {
+                    buff.truncate(nchars);
+                }
             }
         }
-    } else {
-        exit_res = Err(STATUS_CMD_ERROR);
+        ReadlineResult::TimedOut(partial) => {
+            *buff = partial;
+            exit_res = Err(STATUS_TIMEOUT);
+        }
+        ReadlineResult::Canceled => {
+            exit_res = Err(STATUS_CMD_ERROR);
+        }
     }
     reader_pop();
     exit_res
 }
 
+/// Wait until the fd becomes readable. Returns false if the deadline passed before that.
+fn wait_for_input(fd: RawFd, deadline: Option<Instant>) -> bool {
+    let Some(deadline) = deadline else {
+        return true;
+    };
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if is_fd_readable(fd, Timeout::Duration(remaining)) {
+            return true;
+        }
+        // We may also get here if a signal interrupted the wait.
+        if remaining.is_zero() || Instant::now() >= deadline {
+            return false;
+        }
+    }
+}
+
 /// Bash uses 128 bytes for its chunk size. Very informal testing I did suggested that a smaller
 /// chunk size performed better. However, we're going to use the bash value under the assumption
 /// they've done more extensive testing.
@@ -322,7 +361,13 @@ fn read_interactive(
 /// of chars.
 ///
 /// Returns an exit status.
-fn read_in_chunks(fd: RawFd, buff: &mut WString, split_null: bool, do_seek: bool) -> BuiltinResult {
+fn read_in_chunks(
+    fd: RawFd,
+    buff: &mut WString,
+    split_null: bool,
+    do_seek: bool,
+    deadline: Option<Instant>,
+) -> BuiltinResult {
     let mut exit_res = Ok(SUCCESS);
     let mut narrow_buff = vec![];
     let mut eof = false;
@@ -331,6 +376,10 @@ fn read_in_chunks(fd: RawFd, buff: &mut WString, split_null: bool, do_seek: bool
     while !finished {
         let mut inbuf = [0_u8; READ_CHUNK_SIZE];
 
+        if !wait_for_input(fd, deadline) {
+            exit_res = Err(STATUS_TIMEOUT);
+            break;
+        }
         let bytes_read = match read_blocked(fd, &mut inbuf) {
             Ok(0) | Err(_) => {
                 eof = true;
@@ -386,6 +435,7 @@ fn read_one_char_at_a_time(
     buff: &mut WString,
     nchars: Option<NonZeroUsize>,
     split_null: bool,
+    deadline: Option<Instant>,
 ) -> BuiltinResult {
     let mut exit_res = Ok(SUCCESS);
     let mut nbytes = 0;
@@ -395,6 +445,10 @@ fn read_one_char_at_a_time(
     loop {
         let chars_read = buff.len();
         let res = loop {
+            if !wait_for_input(fd, deadline) {
+                exit_res = Err(STATUS_TIMEOUT);
+                break None;
+            }
             let mut b = [0_u8; 1];
             match read_blocked(fd, &mut b) {
                 Ok(0) | Err(_) => {
@@ -421,8 +475,8 @@ fn read_one_char_at_a_time(
             break;
         }
         let Some(&res) = res else {
-            // EOF
-            if buff.is_empty() {
+            // EOF or timeout
+            if buff.is_empty() && exit_res.is_ok() {
                 exit_res = Err(STATUS_CMD_ERROR);
             }
             break;
@@ -633,6 +687,9 @@ pub fn read(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
 
     let stream_stdin_is_a_tty = streams.stdin_fd() >= 0 && isatty(streams.stdin_fd());
 
+    // With --line, the timeout applies to reading all lines.
+    let deadline = opts.timeout.map(|timeout| Instant::now() + timeout);
+
     // Normally, we either consume a line of input or all available input. But if we are reading a
     // line at a time, we need a middle ground where we only consume as many lines as we need to
     // fill the given vars.
@@ -652,6 +709,7 @@ pub fn read(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
                 &opts.right_prompt,
                 opts.commandline.as_ref(),
                 streams.stdin_fd(),
+                deadline,
             );
         } else if opts.nchars.is_none() && !stream_stdin_is_a_tty &&
                    // "one_line" is implemented as reading n-times to a new line,
@@ -674,6 +732,7 @@ pub fn read(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
                 &mut buff,
                 opts.split_null,
                 !streams.stdin_is_directly_redirected,
+                deadline,
             );
         } else {
             exit_res = read_one_char_at_a_time(
@@ -681,10 +740,13 @@ pub fn read(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
                 &mut buff,
                 opts.nchars,
                 opts.split_null,
+                deadline,
             );
         }
 
-        if exit_res.is_err() {
+        // On timeout, the input read so far is only used if requested.
+        let timed_out = exit_res == Err(STATUS_TIMEOUT);
+        if exit_res.is_err() && !(timed_out && opts.keep_partial) {
             clear_remaining_vars(&mut var_ptr);
             return exit_res;
         }
@@ -739,7 +801,7 @@ pub fn read(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
             }
             // The rest of the loop is other split-modes, we don't care about those.
             // Make sure to check the loop exit condition before continuing.
-            if !opts.one_line || vars_left(var_ptr) == 0 {
+            if timed_out || !opts.one_line || vars_left(var_ptr) == 0 {
                 break;
             }
             continue;
@@ -843,7 +905,7 @@ pub fn read(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
             }
         }
 
-        if !opts.one_line || vars_left(var_ptr) == 0 {
+        if timed_out || !opts.one_line || vars_left(var_ptr) == 0 {
             break;
         }
     }
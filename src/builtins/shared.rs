@@ -6,11 +6,13 @@
 use crate::parse_util::argument_is_help;
 use crate::parser::{BlockType, LoopStatus};
 use crate::proc::{Pid, ProcStatus, no_exec};
+use crate::wutil::wcstod::wcstod;
 use crate::{builtins::*, wutil};
 use errno::errno;
 use fish_common::assert_sorted_by_name;
 use fish_widestring::L;
 use std::io::{BufRead, BufReader, Read};
+use std::time::Duration;
 
 pub type BuiltinCmd = fn(&Parser, &mut IoStreams, &mut [&wstr]) -> BuiltinResult;
 
@@ -185,6 +187,8 @@ fn builtin_status_code(&self) -> c_int {
 pub const STATUS_READ_TOO_MUCH: c_int = 122;
 /// The status code when an expansion fails, for example, "$foo["
 pub const STATUS_EXPAND_ERROR: c_int = 121;
+/// The status code used when `read` or `wait` gives up because its timeout expired.
+pub const STATUS_TIMEOUT: c_int = 120;
 
 pub const STATUS_NO_VARIABLES_GIVEN: c_int = 255;
 
@@ -288,6 +292,10 @@ struct BuiltinData {
         name: L!("continue"),
         func: r#continue::r#continue,
     },
+    BuiltinData {
+        name: L!("coproc"),
+        func: coproc::coproc,
+    },
     BuiltinData {
         name: L!("count"),
         func: count::count,
@@ -344,6 +352,10 @@ struct BuiltinData {
         name: L!("for"),
         func: builtin_generic,
     },
+    BuiltinData {
+        name: L!("frecency"),
+        func: frecency::frecency,
+    },
     BuiltinData {
         name: L!("function"),
         func: builtin_generic,
@@ -364,6 +376,10 @@ struct BuiltinData {
         name: L!("jobs"),
         func: jobs::jobs,
     },
+    BuiltinData {
+        name: L!("limit"),
+        func: limit::limit,
+    },
     BuiltinData {
         name: L!("math"),
         func: math::math,
@@ -376,6 +392,10 @@ struct BuiltinData {
         name: L!("or"),
         func: builtin_generic,
     },
+    BuiltinData {
+        name: L!("parallel"),
+        func: parallel::parallel,
+    },
     BuiltinData {
         name: L!("path"),
         func: path::path,
@@ -436,6 +456,10 @@ struct BuiltinData {
         name: L!("time"),
         func: builtin_generic,
     },
+    BuiltinData {
+        name: L!("trap"),
+        func: trap::trap,
+    },
     BuiltinData {
         name: L!("true"),
         func: r#true::r#true,
@@ -590,6 +614,9 @@ pub fn builtin_get_desc(name: &wstr) -> Option<&'static wstr> {
         _ if name == "complete" => wgettext!("Edit command specific completions"),
         _ if name == "contains" => wgettext!("Search for a specified string in a list"),
         _ if name == "continue" => wgettext!("Skip over remaining innermost loop"),
+        _ if name == "coproc" => {
+            wgettext!("Start a background job with pipes to its input and output")
+        }
         _ if name == "count" => wgettext!("Count the number of arguments"),
         _ if name == "disown" => wgettext!("Remove job from job list"),
         _ if name == "echo" => wgettext!("Print arguments"),
@@ -603,14 +630,17 @@ pub fn builtin_get_desc(name: &wstr) -> Option<&'static wstr> {
         _ if name == "fg" => wgettext!("Send job to foreground"),
         _ if name == "fish_key_reader" => wgettext!("explore what characters keyboard keys send"),
         _ if name == "for" => wgettext!("Perform a set of commands multiple times"),
+        _ if name == "frecency" => wgettext!("Rank visited directories"),
         _ if name == "function" => wgettext!("Define a new function"),
         _ if name == "functions" => wgettext!("List or remove functions"),
         _ if name == "history" => wgettext!("History of commands executed by user"),
         _ if name == "if" => wgettext!("Evaluate block if condition is true"),
         _ if name == "jobs" => wgettext!("Print currently running jobs"),
+        _ if name == "limit" => wgettext!("Run a command with resource limits"),
         _ if name == "math" => wgettext!("Evaluate math expressions"),
         _ if name == "not" => wgettext!("Negate exit status of job"),
         _ if name == "or" => wgettext!("Execute command if previous command failed"),
+        _ if name == "parallel" => wgettext!("Run a command for each item in background jobs"),
         _ if name == "path" => wgettext!("Handle paths"),
         _ if name == "printf" => wgettext!("Prints formatted text"),
         _ if name == "pwd" => wgettext!("Print the working directory"),
@@ -626,6 +656,7 @@ pub fn builtin_get_desc(name: &wstr) -> Option<&'static wstr> {
         _ if name == "switch" => wgettext!("Conditionally run blocks of code"),
         _ if name == "test" => wgettext!("Test a condition"),
         _ if name == "time" => wgettext!("Measure how long a command or block takes"),
+        _ if name == "trap" => wgettext!("Perform an action when the shell receives a signal"),
         _ if name == "true" => wgettext!("Return a successful result"),
         _ if name == "type" => wgettext!("Check if a thing is a thing"),
         _ if name == "ulimit" => wgettext!("Get/set resource usage limits"),
@@ -1001,6 +1032,34 @@ fn parsed_pid(
     }
 }
 
+/// Parse a floating point number, which must make up all of `s`.
+pub fn parse_float(s: &wstr) -> Option<f64> {
+    let mut consumed = 0;
+    wcstod(s, '.', &mut consumed)
+        .ok()
+        .filter(|_| consumed == s.len())
+}
+
+/// Parse a number of seconds, which may be fractional, printing an error if it is not valid.
+pub fn parse_seconds(
+    parser: &Parser,
+    streams: &mut IoStreams,
+    cmd: &wstr,
+    arg: &wstr,
+) -> Result<Duration, ErrorCode> {
+    let seconds = parse_float(arg).and_then(|seconds| Duration::try_from_secs_f64(seconds).ok());
+    let Some(seconds) = seconds else {
+        streams.err.appendln(&wgettext_fmt!(
+            "%s: %s: invalid number of seconds",
+            cmd,
+            arg
+        ));
+        builtin_print_error_trailer(parser, streams.err, cmd);
+        return Err(STATUS_INVALID_ARGS);
+    };
+    Ok(seconds)
+}
+
 /// A generic builtin that only supports showing a help message. This is only a placeholder that
 /// prints the help message. Useful for commands that live in the parser.
 fn builtin_generic(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> BuiltinResult {
@@ -1121,3 +1180,35 @@ pub fn parse_from_opt(
         })
     }
 }
+
+/// Append `s` as a JSON string literal to `out`.
+pub fn append_json_string(out: &mut WString, s: &wstr) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if u32::from(c) < 0x20 || c == '\x7f' => {
+                sprintf!(=> out, "\\u%04x", u32::from(c));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::append_json_string;
+    use fish_widestring::{L, WString};
+
+    #[test]
+    fn test_json_string() {
+        let mut out = WString::new();
+        append_json_string(&mut out, L!("echo \"a\\b\"\t\n\x1b[0m ü"));
+        assert_eq!(out, L!("\"echo \\\"a\\\\b\\\"\\t\\n\\u001b[0m ü\""));
+    }
+}
@@ -2,11 +2,14 @@
 use crate::common::{bytes2wcstring, get_program_name, osstr2wcstring, str2wcstring};
 use crate::env::config_paths::get_fish_path;
 use crate::future_feature_flags::{self as features, feature_test};
+use crate::parser::StrictMode;
 use crate::proc::{
     JobControl, get_job_control_mode, get_login, is_interactive_session, set_job_control_mode,
 };
 use crate::reader::reader_in_interactive_read;
-use crate::tty_handoff::{TERMINAL_OS_NAME, get_scroll_content_up_capability, xtversion};
+use crate::tty_handoff::{
+    TERMINAL_OS_NAME, get_scroll_content_up_capability, terminal_report, xtversion,
+};
 use crate::wutil::{Error, waccess, wbasename, wdirname, wrealpath};
 use cfg_if::cfg_if;
 use nix::unistd::AccessFlags;
@@ -76,8 +79,10 @@ fn to_wstr(self) -> &'static wstr {
     (STATUS_LANGUAGE, "language"),
     (STATUS_SET_JOB_CONTROL, "job-control"),
     (STATUS_STACK_TRACE, "stack-trace", "print-stack-trace"),
+    (STATUS_STRICT, "strict"),
     (STATUS_TERMINAL, "terminal"),
     (STATUS_TERMINAL_OS, "terminal-os"),
+    (STATUS_TERMINAL_REPORT, "terminal-report"),
     (STATUS_TEST_FEATURE, "test-feature"),
     (STATUS_TEST_TERMINAL_FEATURE, "test-terminal-feature"),
 );
@@ -170,6 +175,7 @@ fn default() -> Self {
 
 localizable_consts! {
     BUILTIN_INVALID_JOB_CONTROL_MODE "%s: Invalid job control mode '%s'"
+    BUILTIN_INVALID_STRICT_MODE "%s: Invalid strict mode '%s'"
 }
 
 /// Print the features and their values.
@@ -337,6 +343,23 @@ fn iter() -> impl Iterator<Item = std::borrow::Cow<'static, str>> {
     }
 );
 
+/// Return the path to the running fish executable, as printed by `status fish-path`.
+pub fn fish_path() -> Cow<'static, wstr> {
+    use crate::env::config_paths::FishPath::*;
+    match get_fish_path() {
+        Absolute(path) => {
+            let path = osstr2wcstring(path);
+            Cow::Owned(match wrealpath(&path) {
+                Some(p) if waccess(&p, AccessFlags::F_OK).is_ok() => p,
+                // realpath did not work, just append the path
+                // - maybe this was obtained via $PATH?
+                _ => path,
+            })
+        }
+        LookUpInPath => Cow::Borrowed(get_program_name()),
+    }
+}
+
 pub fn status(parser: &Parser, streams: &mut IoStreams, args: &mut [&wstr]) -> BuiltinResult {
     let cmd = args[0];
     let argc = args.len();
@@ -433,6 +456,32 @@ pub fn status(parser: &Parser, streams: &mut IoStreams, args: &mut [&wstr]) -> B
             };
             set_job_control_mode(job_control_mode);
         }
+        c @ STATUS_STRICT => {
+            if args.len() > 1 {
+                streams.err.appendln(&wgettext_fmt!(
+                    BUILTIN_ERR_ARG_COUNT2,
+                    cmd,
+                    c.to_wstr(),
+                    1,
+                    args.len()
+                ));
+                return Err(STATUS_INVALID_ARGS);
+            }
+            let Some(&mode) = args.first() else {
+                streams.out.appendln(parser.strict_mode().to_wstr());
+                return Ok(SUCCESS);
+            };
+            let Ok(mode) = StrictMode::try_from(mode) else {
+                streams
+                    .err
+                    .appendln(&wgettext_fmt!(BUILTIN_INVALID_STRICT_MODE, cmd, mode));
+                return Err(STATUS_CMD_ERROR);
+            };
+            // The mode lasts until the end of the enclosing block.
+            if let Some(mut block) = parser.block_at_index_mut(0) {
+                block.strict_mode = Some(mode);
+            }
+        }
         STATUS_FEATURES => print_features(streams),
         c @ STATUS_TEST_FEATURE => {
             if args.len() != 1 {
@@ -735,20 +784,7 @@ pub fn status(parser: &Parser, streams: &mut IoStreams, args: &mut [&wstr]) -> B
                     streams.out.appendln(commandline);
                 }
                 STATUS_FISH_PATH => {
-                    use crate::env::config_paths::FishPath::*;
-                    let result = match get_fish_path() {
-                        Absolute(path) => {
-                            let path = osstr2wcstring(path);
-                            Cow::Owned(match wrealpath(&path) {
-                                Some(p) if waccess(&p, AccessFlags::F_OK).is_ok() => p,
-                                // realpath did not work, just append the path
-                                // - maybe this was obtained via $PATH?
-                                _ => path,
-                            })
-                        }
-                        LookUpInPath => Cow::Borrowed(get_program_name()),
-                    };
-                    streams.out.appendln(&result);
+                    streams.out.appendln(&fish_path());
                 }
                 STATUS_TERMINAL => {
                     let xtversion = xtversion().unwrap_or_default();
@@ -760,7 +796,19 @@ pub fn status(parser: &Parser, streams: &mut IoStreams, args: &mut [&wstr]) -> B
                     };
                     streams.out.appendln(first_line(terminal_os_name));
                 }
+                STATUS_TERMINAL_REPORT => {
+                    // One tab-separated line per capability: name, value, and the query.
+                    for entry in terminal_report(parser.vars()) {
+                        streams.out.append(&sprintf!(
+                            "%s\t%s\t%s\n",
+                            entry.name,
+                            first_line(&entry.value),
+                            entry.query
+                        ));
+                    }
+                }
                 STATUS_SET_JOB_CONTROL
+                | STATUS_STRICT
                 | STATUS_FEATURES
                 | STATUS_TEST_FEATURE
                 | STATUS_GET_FILE
@@ -0,0 +1,227 @@
+//! Implementation of the trap builtin.
+
+use super::prelude::*;
+use crate::common::escape;
+use crate::function;
+use crate::io::IoChain;
+use crate::signal::{Signal, signal_ignore, signal_restore_disposition, signal_save_disposition};
+use std::sync::Mutex;
+
+/// The conditions a trap can be set on, in the order they are printed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord)]
+enum TrapReason {
+    /// The shell exits.
+    Exit,
+    /// The shell receives a signal.
+    Signal(Signal),
+    /// A job run from the top level of a script or the command line fails.
+    Err,
+}
+
+impl TrapReason {
+    /// Parse a condition, which is EXIT, ERR or a signal name or number. Case is ignored, and
+    /// signal names may omit the SIG prefix. The number 0 means EXIT.
+    fn parse(s: &wstr) -> Option<Self> {
+        let is = |name: &str| {
+            s.len() == name.len()
+                && s.chars()
+                    .zip(name.chars())
+                    .all(|(a, b)| a.eq_ignore_ascii_case(&b))
+        };
+        if is("EXIT") || is("SIGEXIT") || s == "0" {
+            Some(TrapReason::Exit)
+        } else if is("ERR") {
+            Some(TrapReason::Err)
+        } else {
+            Signal::parse(s).map(TrapReason::Signal)
+        }
+    }
+
+    /// The name used when printing this trap.
+    fn name(self) -> &'static wstr {
+        match self {
+            TrapReason::Exit => L!("EXIT"),
+            TrapReason::Signal(sig) => sig.name(),
+            TrapReason::Err => L!("ERR"),
+        }
+    }
+
+    /// The name of the function that runs the trap's command.
+    fn handler_name(self) -> WString {
+        let name = self.name();
+        let name = name.strip_prefix(L!("SIG")).unwrap_or(name);
+        L!("__trap_handler_").to_owned() + name
+    }
+
+    /// The `function` option that makes the handler run when the condition occurs.
+    fn function_option(self) -> WString {
+        match self {
+            TrapReason::Exit => L!("--on-event fish_exit").to_owned(),
+            TrapReason::Signal(sig) => L!("--on-signal ").to_owned() + sig.name(),
+            TrapReason::Err => L!("--on-event fish_job_failed").to_owned(),
+        }
+    }
+}
+
+/// The commands of all installed traps. An empty command means the condition is ignored.
+static TRAPS: Mutex<Vec<(TrapReason, WString)>> = Mutex::new(Vec::new());
+
+/// Remove the trap for the given condition, restoring the default behavior.
+fn reset_trap(reason: TrapReason) {
+    TRAPS.lock().unwrap().retain(|(r, _)| *r != reason);
+    let handler = reason.handler_name();
+    if function::exists_no_autoload(&handler) {
+        function::remove(&handler);
+    }
+    if let TrapReason::Signal(sig) = reason {
+        signal_restore_disposition(sig);
+    }
+}
+
+/// Install a trap running `cmd` for the given condition, replacing any previous trap.
+fn set_trap(parser: &Parser, reason: TrapReason, cmd: &wstr) {
+    reset_trap(reason);
+    if cmd.is_empty() {
+        if let TrapReason::Signal(sig) = reason {
+            signal_ignore(sig);
+        }
+    } else {
+        if let TrapReason::Signal(sig) = reason {
+            signal_save_disposition(sig);
+        }
+        let source = sprintf!(
+            "function %s %s\n    eval %s\nend",
+            reason.handler_name(),
+            reason.function_option(),
+            escape(cmd)
+        );
+        parser.eval(&source, &IoChain::new());
+    }
+    TRAPS.lock().unwrap().push((reason, cmd.to_owned()));
+}
+
+/// Print the given traps, or all traps if `reasons` is None, in a form that can be used to
+/// reinstall them.
+fn print_traps(streams: &mut IoStreams, reasons: Option<&[TrapReason]>) {
+    let mut traps = TRAPS.lock().unwrap().clone();
+    traps.sort();
+    for (reason, cmd) in traps {
+        if reasons.is_some_and(|reasons| !reasons.contains(&reason)) {
+            continue;
+        }
+        // The handler function may have been erased behind our back.
+        if !cmd.is_empty() && !function::exists_no_autoload(&reason.handler_name()) {
+            continue;
+        }
+        streams
+            .out
+            .appendln(&sprintf!("trap -- %s %s", escape(&cmd), reason.name()));
+    }
+}
+
+/// Print the names of all signals, without the SIG prefix.
+fn list_signals(streams: &mut IoStreams) {
+    for sig in Signal::all() {
+        let name = sig.name();
+        streams
+            .out
+            .appendln(name.strip_prefix(L!("SIG")).unwrap_or(name));
+    }
+}
+
+pub fn trap(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> BuiltinResult {
+    let cmd = argv[0];
+    let print_hints = false;
+    let mut print_help = false;
+    let mut print = false;
+    let mut list = false;
+
+    const SHORT_OPTS: &wstr = L!("hlp");
+    const LONG_OPTS: &[WOption] = &[
+        wopt(L!("help"), NoArgument, 'h'),
+        wopt(L!("list-signals"), NoArgument, 'l'),
+        wopt(L!("print"), NoArgument, 'p'),
+    ];
+
+    let mut w = WGetopter::new(SHORT_OPTS, LONG_OPTS, argv);
+    while let Some(c) = w.next_opt() {
+        match c {
+            'h' => print_help = true,
+            'l' => list = true,
+            'p' => print = true,
+            ';' => {
+                builtin_unexpected_argument(
+                    parser,
+                    streams,
+                    cmd,
+                    argv[w.wopt_index - 1],
+                    print_hints,
+                );
+                return Err(STATUS_INVALID_ARGS);
+            }
+            '?' => {
+                builtin_unknown_option(parser, streams, cmd, argv[w.wopt_index - 1], print_hints);
+                return Err(STATUS_INVALID_ARGS);
+            }
+            _ => panic!("unexpected retval from WGetopter"),
+        }
+    }
+
+    if print_help {
+        builtin_print_help(parser, streams, cmd);
+        return Ok(SUCCESS);
+    }
+
+    if list {
+        list_signals(streams);
+        return Ok(SUCCESS);
+    }
+
+    let optind = w.wopt_index;
+    let args = &argv[optind..];
+
+    // Figure out the command, if any, and which arguments are conditions.
+    // With a single argument, or a first argument of "-" or a number, the traps are reset.
+    let (action, conditions) = if print || args.is_empty() {
+        (None, args)
+    } else if args.len() == 1 || args[0] == "-" {
+        (Some(None), if args[0] == "-" { &args[1..] } else { args })
+    } else if fish_wcstoul(args[0]).is_ok() {
+        (Some(None), args)
+    } else {
+        (Some(Some(args[0])), &args[1..])
+    };
+
+    let mut retval = Ok(SUCCESS);
+    let mut reasons = Vec::new();
+    for &arg in conditions {
+        match TrapReason::parse(arg) {
+            Some(reason) => reasons.push(reason),
+            None => {
+                streams.err.appendln(&wgettext_fmt!(
+                    "%s: %s: invalid signal specification",
+                    cmd,
+                    arg
+                ));
+                retval = Err(STATUS_CMD_ERROR);
+            }
+        }
+    }
+
+    match action {
+        None if conditions.is_empty() => print_traps(streams, None),
+        None => print_traps(streams, Some(&reasons)),
+        Some(None) => {
+            for reason in reasons {
+                reset_trap(reason);
+            }
+        }
+        Some(Some(command)) => {
+            for reason in reasons {
+                set_trap(parser, reason, command);
+            }
+        }
+    }
+
+    retval
+}
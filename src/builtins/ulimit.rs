@@ -97,7 +97,7 @@ fn convert_resource(resource: c_uint) -> ResourceEnum {
 }
 
 /// Calls getrlimit.
-fn getrlimit(resource: c_uint) -> Option<(rlim_t, rlim_t)> {
+pub(super) fn getrlimit(resource: c_uint) -> Option<(rlim_t, rlim_t)> {
     nix::sys::resource::getrlimit(convert_resource(resource))
         .map_err(|_| perror("getrlimit"))
         .ok()
@@ -162,7 +162,7 @@ fn print_all(hard: bool, streams: &mut IoStreams) {
 }
 
 /// Returns the description for the specified resource limit.
-fn get_desc(what: c_uint) -> &'static wstr {
+pub(super) fn get_desc(what: c_uint) -> &'static wstr {
     for resource in RESOURCE_ARR.iter() {
         if resource.resource == what {
             return resource.desc;
@@ -221,6 +221,15 @@ fn get_multiplier(what: c_uint) -> rlim_t {
     unreachable!()
 }
 
+/// Get the resource and its implicit multiplication factor for the given command line switch, if
+/// that resource is available on this operating system.
+pub(super) fn resource_for_switch(switch_char: char) -> Option<(c_uint, rlim_t)> {
+    RESOURCE_ARR
+        .iter()
+        .find(|resource| resource.switch_char == switch_char)
+        .map(|resource| (resource.resource, resource.multiplier as rlim_t))
+}
+
 fn get(resource: c_uint, hard: bool) -> Option<rlim_t> {
     let (rlim_cur, rlim_max) = getrlimit(resource)?;
 
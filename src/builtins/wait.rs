@@ -1,7 +1,16 @@
 use super::prelude::*;
-use crate::proc::{Job, Pid, proc_wait_any};
-use crate::signal::SigChecker;
-use crate::wait_handle::{WaitHandleRef, WaitHandleStore};
+use crate::proc::{Job, Pid, proc_wait_any, proc_wait_any_until};
+use crate::signal::{SigChecker, Signal};
+use crate::wait_handle::{InternalJobId, WaitHandleRef, WaitHandleStore};
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
+struct Options {
+    any_flag: bool,
+    status_flag: bool,
+    print_flag: bool,
+    timeout: Option<Duration>,
+}
 
 /// Return true if we can wait on a job.
 fn can_wait_on_job(j: &Job) -> bool {
@@ -22,7 +31,7 @@ fn iswnumeric(s: &wstr) -> bool {
 }
 
 #[derive(Copy, Clone)]
-enum WaitHandleQuery<'a> {
+pub enum WaitHandleQuery<'a> {
     Pid(Pid),
     ProcName(&'a wstr),
 }
@@ -30,7 +39,7 @@ enum WaitHandleQuery<'a> {
 /// Walk the list of jobs, looking for a process with the given pid or proc name.
 /// Append all matching wait handles to `handles`.
 /// Return true if we found a matching job (even if not waitable), false if not.
-fn find_wait_handles(
+pub fn find_wait_handles(
     query: WaitHandleQuery<'_>,
     parser: &Parser,
     handles: &mut Vec<WaitHandleRef>,
@@ -98,51 +107,105 @@ fn is_completed(wh: &WaitHandleRef) -> bool {
     wh.is_completed()
 }
 
+/// Print the pid and status of a completed wait handle, like "1234\t143\tSIGTERM".
+fn print_wait_handle(streams: &mut IoStreams, wh: &WaitHandleRef) {
+    let Some(status) = wh.proc_status() else {
+        return;
+    };
+    let mut line = sprintf!("%d\t%d", wh.pid, status.status_value());
+    if status.signal_exited() {
+        line.push('\t');
+        line.push_utfstr(Signal::new(status.signal_code()).name());
+    }
+    streams.out.appendln(&line);
+}
+
+/// Return the internal job ID of the first job whose given wait handles are all completed.
+fn first_completed_job(whs: &[WaitHandleRef]) -> Option<InternalJobId> {
+    whs.iter().map(|wh| wh.internal_job_id).find(|&jid| {
+        whs.iter()
+            .filter(|wh| wh.internal_job_id == jid)
+            .all(is_completed)
+    })
+}
+
 /// Wait for the given wait handles to be marked as completed.
-/// If `any_flag` is set, wait for the first one; otherwise wait for all.
+/// If `any_flag` is set, wait for the first job whose handles are all completed; otherwise wait
+/// for all.
 /// Return a status code.
-fn wait_for_completion(parser: &Parser, whs: &[WaitHandleRef], any_flag: bool) -> BuiltinResult {
+fn wait_for_completion(
+    parser: &Parser,
+    streams: &mut IoStreams,
+    whs: &[WaitHandleRef],
+    opts: &Options,
+) -> BuiltinResult {
     if whs.is_empty() {
         return Ok(SUCCESS);
     }
 
+    let deadline = opts.timeout.map(|timeout| Instant::now() + timeout);
     let mut sigint = SigChecker::new_sighupint();
     loop {
-        let finished = if any_flag {
-            whs.iter().any(is_completed)
+        let completed_job = if opts.any_flag {
+            first_completed_job(whs)
+        } else {
+            None
+        };
+        let finished = if opts.any_flag {
+            completed_job.is_some()
         } else {
             whs.iter().all(is_completed)
         };
+        let timed_out = !finished && deadline.is_some_and(|deadline| Instant::now() >= deadline);
+        if timed_out && opts.any_flag {
+            // No job has completed, so leave the wait handles of its finished processes alone.
+            return Err(STATUS_TIMEOUT);
+        }
 
-        if finished {
-            // Remove completed wait handles (at most 1 if any_flag is set).
+        if finished || timed_out {
+            // Remove completed wait handles (only those of the completed job if any_flag is set).
+            let mut last_status = None;
             for wh in whs {
-                if is_completed(wh) {
-                    parser.mut_wait_handles().remove(wh);
-                    if any_flag {
-                        break;
-                    }
+                if !is_completed(wh) || completed_job.is_some_and(|jid| jid != wh.internal_job_id) {
+                    continue;
+                }
+                parser.mut_wait_handles().remove(wh);
+                last_status = wh.status();
+                if opts.print_flag {
+                    print_wait_handle(streams, wh);
                 }
             }
-            return Ok(SUCCESS);
+            if timed_out {
+                return Err(STATUS_TIMEOUT);
+            }
+            return match last_status {
+                Some(status) if opts.status_flag => BuiltinResult::from_dynamic(status),
+                _ => Ok(SUCCESS),
+            };
         }
         if sigint.check() {
             return Err(128 + libc::SIGINT);
         }
-        proc_wait_any(parser);
+        match deadline {
+            Some(deadline) => proc_wait_any_until(parser, deadline),
+            None => proc_wait_any(parser),
+        }
     }
 }
 
 pub fn wait(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> BuiltinResult {
     let cmd = argv[0];
     let argc = argv.len();
-    let mut any_flag = false; // flag for -n option
+    let mut opts = Options::default();
     let mut print_help = false;
     let print_hints = false;
 
-    let shortopts: &wstr = L!("nh");
+    let shortopts: &wstr = L!("nspt:h");
     let longopts: &[WOption] = &[
         wopt(L!("any"), ArgType::NoArgument, 'n'),
+        wopt(L!("status"), ArgType::NoArgument, 's'),
+        wopt(L!("print"), ArgType::NoArgument, 'p'),
+        wopt(L!("timeout"), ArgType::RequiredArgument, 't'),
         wopt(L!("help"), ArgType::NoArgument, 'h'),
     ];
 
@@ -150,7 +213,16 @@ pub fn wait(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
     while let Some(c) = w.next_opt() {
         match c {
             'n' => {
-                any_flag = true;
+                opts.any_flag = true;
+            }
+            's' => {
+                opts.status_flag = true;
+            }
+            'p' => {
+                opts.print_flag = true;
+            }
+            't' => {
+                opts.timeout = Some(parse_seconds(parser, streams, cmd, w.woptarg.unwrap())?);
             }
             'h' => {
                 print_help = true;
@@ -187,7 +259,7 @@ pub fn wait(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
     if w.wopt_index == argc {
         // No jobs specified.
         // Note this may succeed with an empty wait list.
-        return wait_for_completion(parser, &get_all_wait_handles(parser), any_flag);
+        return wait_for_completion(parser, streams, &get_all_wait_handles(parser), &opts);
     }
 
     // Get the list of wait handles for our waiting.
@@ -219,5 +291,5 @@ pub fn wait(parser: &Parser, streams: &mut IoStreams, argv: &mut [&wstr]) -> Bui
     if wait_handles.is_empty() {
         return Err(STATUS_INVALID_ARGS);
     }
-    wait_for_completion(parser, &wait_handles, any_flag)
+    wait_for_completion(parser, streams, &wait_handles, &opts)
 }
@@ -13,7 +13,7 @@
 use crate::{
     abbrs::with_abbrs,
     autoload::Autoload,
-    builtins::shared::{builtin_exists, builtin_get_desc, builtin_get_names},
+    builtins::shared::{append_json_string, builtin_exists, builtin_get_desc, builtin_get_names},
     common::{
         ScopeGuard, UnescapeFlags, UnescapeStringStyle, escape, unescape_string,
         valid_var_name_char,
@@ -22,9 +22,10 @@
     exec::exec_subshell,
     expand::{
         ExpandFlags, ExpandResultCode, expand_escape_string, expand_escape_variable, expand_one,
-        expand_string, expand_to_receiver,
+        expand_string, expand_tilde, expand_to_receiver,
     },
     flog::{flog, flogf},
+    frecency::{frecency_load, frecency_matches, frecency_now},
     function,
     history::{History, history_session_id},
     operation_context::OperationContext,
@@ -32,16 +33,18 @@
     parse_util::{get_cmdsubst_extent, get_process_extent, unescape_wildcards},
     parser::{Block, Parser, ParserEnvSetMode},
     parser_keywords::parser_keywords_is_subcommand,
-    path::{path_get_path, path_try_get_path},
+    path::{path_apply_cdpath, path_get_path, path_try_get_path},
     prelude::*,
     tokenizer::{Tok, TokFlags, TokenType, Tokenizer, variable_assignment_equals_pos},
     wildcard::{wildcard_complete, wildcard_has, wildcard_match},
-    wutil::wrealpath,
+    wutil::{normalize_path, wrealpath, wstat},
 };
 use crate::{
     ast::unescape_keyword,
     autoload::AutoloadResult,
     common::charptr2wcstring,
+    complete_cache::{ArgsCacheKey, args_cache_clear, args_cache_get, args_cache_put},
+    complete_spec::{Json, parse_json},
     localization::{LocalizableString, localizable_string},
     reader::{get_quote, is_backslashed},
 };
@@ -49,9 +52,9 @@
 use bitflags::bitflags;
 use fish_util::wcsfilecmp;
 use fish_wcstringutil::{
-    StringFuzzyMatch, string_fuzzy_match_string, string_prefixes_string,
-    string_prefixes_string_case_insensitive, string_suffixes_string_case_insensitive,
-    strip_executable_suffix,
+    CaseSensitivity, ContainType, StringFuzzyMatch, join_strings, string_fuzzy_match_string,
+    string_prefixes_string, string_prefixes_string_case_insensitive,
+    string_suffixes_string_case_insensitive, strip_executable_suffix,
 };
 use fish_widestring::WExt;
 
@@ -209,6 +212,110 @@ pub fn prepend_token_prefix(&mut self, prefix: &wstr) {
             self.completion.insert_utfstr(0, prefix);
         }
     }
+
+    /// Return a JSON object describing this completion, as printed by `complete --json`. `token`
+    /// is the token with the completion applied, like it is printed without `--json`.
+    pub fn to_json(&self, token: &wstr) -> WString {
+        let mut out = WString::new();
+        out.push_str("{\"completion\": ");
+        append_json_string(&mut out, &self.completion);
+        out.push_str(", \"description\": ");
+        append_json_string(&mut out, &self.description);
+        out.push_str(", \"token\": ");
+        append_json_string(&mut out, token);
+        out.push_str(", \"flags\": [");
+        for (i, (name, _)) in self.flags.iter_names().enumerate() {
+            if i > 0 {
+                out.push_str(", ");
+            }
+            sprintf!(=> &mut out, "\"%s\"", name.to_ascii_lowercase());
+        }
+        let typ = match self.r#match.typ {
+            ContainType::Exact => "exact",
+            ContainType::Prefix => "prefix",
+            ContainType::Substr => "substring",
+            ContainType::Subseq => "subsequence",
+        };
+        let case_fold = match self.r#match.case_fold {
+            CaseSensitivity::Sensitive => "sensitive",
+            CaseSensitivity::Smart => "smart",
+            CaseSensitivity::Insensitive => "insensitive",
+        };
+        sprintf!(=> &mut out,
+            "], \"rank\": %u, \"match\": {\"type\": \"%s\", \"case\": \"%s\", \"from_separator\": %s}}",
+            self.rank(),
+            typ,
+            case_fold,
+            if self.r#match.from_separator { "true" } else { "false" }
+        );
+        out
+    }
+
+    /// Parse a JSON object produced by [`Completion::to_json`]. This is used to read completions
+    /// from another fish process.
+    pub fn from_json(line: &wstr) -> Option<Self> {
+        let Ok(Json::Object(fields)) = parse_json(line) else {
+            return None;
+        };
+        let mut completion = None;
+        let mut description = None;
+        let mut flags = CompleteFlags::empty();
+        let mut r#match = None;
+        for (key, value) in fields {
+            match (key.to_string().as_str(), value) {
+                ("completion", Json::String(value)) => completion = Some(value),
+                ("description", Json::String(value)) => description = Some(value),
+                ("flags", Json::Array(names)) => {
+                    for name in names {
+                        let Json::String(name) = name else {
+                            return None;
+                        };
+                        let flag =
+                            CompleteFlags::from_name(&name.to_string().to_ascii_uppercase())?;
+                        flags |= flag;
+                    }
+                }
+                ("match", Json::Object(fields)) => {
+                    let mut typ = None;
+                    let mut case_fold = None;
+                    let mut from_separator = None;
+                    for (key, value) in fields {
+                        match (key.to_string().as_str(), value) {
+                            ("type", Json::String(value)) => {
+                                typ = Some(match value.to_string().as_str() {
+                                    "exact" => ContainType::Exact,
+                                    "prefix" => ContainType::Prefix,
+                                    "substring" => ContainType::Substr,
+                                    "subsequence" => ContainType::Subseq,
+                                    _ => return None,
+                                });
+                            }
+                            ("case", Json::String(value)) => {
+                                case_fold = Some(match value.to_string().as_str() {
+                                    "sensitive" => CaseSensitivity::Sensitive,
+                                    "smart" => CaseSensitivity::Smart,
+                                    "insensitive" => CaseSensitivity::Insensitive,
+                                    _ => return None,
+                                });
+                            }
+                            ("from_separator", Json::Bool(value)) => from_separator = Some(value),
+                            _ => (),
+                        }
+                    }
+                    let mut m = StringFuzzyMatch::new(typ?, case_fold?);
+                    m.from_separator = from_separator?;
+                    r#match = Some(m);
+                }
+                _ => (),
+            }
+        }
+        Some(Self {
+            completion: completion?,
+            description: description?,
+            r#match: r#match?,
+            flags,
+        })
+    }
 }
 
 impl CompletionRequestOptions {
@@ -346,7 +453,7 @@ pub fn subreceiver(&self) -> Self {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum CompleteOptionType {
     /// no option
     ArgsOnly,
@@ -380,12 +487,17 @@ struct CompleteEntryOpt {
     desc: LocalizableString,
     /// Conditions under which to use the option, expanded and evaluated at completion time.
     conditions: Vec<WString>,
+    /// The subcommand under which to use the option, like `["remote", "add"]`, or empty if it is
+    /// used regardless of subcommands. See `complete --subcommand`.
+    subcommand: Vec<WString>,
     /// Type of the option: `ArgsOnly`, `Short`, `SingleLong`, or `DoubleLong`.
     typ: CompleteOptionType,
     /// Determines how completions should be performed on the argument after the switch.
     result_mode: CompletionMode,
     /// Completion flags.
     flags: CompleteFlags,
+    /// How long the expanded arguments are cached, if at all.
+    cache: Option<CompletionCache>,
 }
 
 impl CompleteEntryOpt {
@@ -396,6 +508,43 @@ pub fn expected_dash_count(&self) -> usize {
             CompleteOptionType::DoubleLong => 2,
         }
     }
+
+    /// Whether to use this option for the given subcommand of the command line. The options of a
+    /// subcommand are also used for its subcommands, but its arguments are not.
+    fn applies_to_subcommand(&self, subcommand: &[WString]) -> bool {
+        if self.typ == CompleteOptionType::ArgsOnly {
+            self.subcommand.is_empty() || self.subcommand == subcommand
+        } else {
+            subcommand.starts_with(&self.subcommand)
+        }
+    }
+
+    /// Return the key under which the expanded arguments of this option of the command `idx` are
+    /// cached, and for how long, or None if they are not cached.
+    fn cache_key(&self, idx: &CompletionEntryIndex) -> Option<(ArgsCacheKey, Duration)> {
+        let cache = self.cache.as_ref()?;
+        let key = match &cache.key {
+            Some(key) => ArgsCacheKey::Named(key.clone()),
+            None => ArgsCacheKey::Option {
+                command: idx.name.clone(),
+                is_path: idx.is_path,
+                option: self.option.clone(),
+                typ: self.typ,
+                args: self.comp.clone(),
+            },
+        };
+        Some((key, cache.ttl))
+    }
+}
+
+/// How the expanded arguments of a completion are cached, see `complete --cache-ttl`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompletionCache {
+    /// How long the arguments are reused before they are expanded again.
+    pub ttl: Duration,
+    /// A key shared by all completions that use the same arguments. If None, the arguments are
+    /// cached for the command and option only.
+    pub key: Option<WString>,
 }
 
 /// Last value used in the order field of [`CompletionEntry`].
@@ -428,11 +577,27 @@ pub fn add_option(&mut self, opt: CompleteEntryOpt) {
     }
 
     /// Remove all completion options in the specified entry that match the specified short / long
-    /// option strings. Returns true if it is now empty and should be deleted, false if it's not
-    /// empty.
-    pub fn remove_option(&mut self, option: &wstr, typ: CompleteOptionType) -> bool {
+    /// option strings, and the subcommand unless it is empty. Returns true if it is now empty and
+    /// should be deleted, false if it's not empty.
+    pub fn remove_option(
+        &mut self,
+        option: &wstr,
+        typ: CompleteOptionType,
+        subcommand: &[WString],
+    ) -> bool {
+        self.options.retain(|opt| {
+            opt.option != option
+                || opt.typ != typ
+                || (!subcommand.is_empty() && opt.subcommand != subcommand)
+        });
+        self.options.is_empty()
+    }
+
+    /// Remove all completion options of the given subcommand and its subcommands. Returns true if
+    /// it is now empty and should be deleted, false if it's not empty.
+    pub fn remove_subcommand(&mut self, subcommand: &[WString]) -> bool {
         self.options
-            .retain(|opt| opt.option != option || opt.typ != typ);
+            .retain(|opt| !opt.subcommand.starts_with(subcommand));
         self.options.is_empty()
     }
 }
@@ -1183,7 +1348,43 @@ fn complete_abbr(&mut self, cmd: WString) {
     /// - `args`: The list of option arguments to be evaluated.
     /// - `desc`: Description of the completion
     /// - `flags`: The flags
-    fn complete_from_args(&mut self, s: &wstr, args: &wstr, desc: &wstr, flags: CompleteFlags) {
+    /// - `cache`: The key under which the expanded arguments are cached, and for how long.
+    fn complete_from_args(
+        &mut self,
+        s: &wstr,
+        args: &wstr,
+        desc: &wstr,
+        flags: CompleteFlags,
+        cache: Option<(ArgsCacheKey, Duration)>,
+    ) {
+        let cached = cache.as_ref().and_then(|(key, _)| args_cache_get(key));
+        let possible_comp = match cached {
+            Some(args) => args.into_iter().map(Completion::from).collect(),
+            None => {
+                let possible_comp = self.expand_args(args);
+                // Autosuggestions don't run command substitutions, so their results are incomplete.
+                if let Some((key, ttl)) = cache {
+                    if !self.flags.autosuggestion && !self.ctx.check_cancel() {
+                        let args = possible_comp.iter().map(|c| c.completion.clone());
+                        args_cache_put(key, ttl, args.collect());
+                    }
+                }
+                possible_comp
+            }
+        };
+
+        // Allow leading dots - see #3707.
+        self.complete_strings(
+            &escape(s),
+            &const_desc(desc),
+            &possible_comp,
+            flags,
+            ExpandFlags::ALLOW_NONLITERAL_LEADING_DOT,
+        );
+    }
+
+    /// Expand the arguments of a completion, like `-a '(command)'`.
+    fn expand_args(&mut self, args: &wstr) -> Vec<Completion> {
         let is_autosuggest = self.flags.autosuggestion;
 
         let mut saved_statuses = None;
@@ -1206,14 +1407,7 @@ fn complete_from_args(&mut self, s: &wstr, args: &wstr, desc: &wstr, flags: Comp
         }
         std::mem::drop(scope);
 
-        // Allow leading dots - see #3707.
-        self.complete_strings(
-            &escape(s),
-            &const_desc(desc),
-            &possible_comp,
-            flags,
-            ExpandFlags::ALLOW_NONLITERAL_LEADING_DOT,
-        );
+        possible_comp
     }
 
     /// complete_param: Given a command, find completions for the argument `s` of command `cmd_orig`
@@ -1228,11 +1422,14 @@ fn complete_from_args(&mut self, s: &wstr, args: &wstr, desc: &wstr, flags: Comp
     /// echo hello world <tab> -> ("echo", "world", "")
     /// echo hello world<tab> -> ("echo", "hello", "world")
     /// ```
+    ///
+    /// `arguments` are the unescaped arguments before `s`, which select the subcommand.
     fn complete_param_for_command(
         &mut self,
         cmd_orig: &wstr,
         popt: &wstr,
         s: &wstr,
+        arguments: &[WString],
         use_switches: bool,
         out_do_file: &mut bool,
     ) -> bool {
@@ -1264,7 +1461,7 @@ fn complete_param_for_command(
         }
 
         // Make a list of lists of all options that we care about.
-        let all_options: Vec<Vec<CompleteEntryOpt>> = COMPLETION_MAP
+        let all_options: Vec<(CompletionEntryIndex, Vec<CompleteEntryOpt>)> = COMPLETION_MAP
             .lock()
             .unwrap()
             .iter()
@@ -1283,7 +1480,7 @@ fn complete_param_for_command(
                     let mut options = completion.get_options().to_vec();
                     // We have to copy them in reverse order to preserve legacy behavior (#9221).
                     options.reverse();
-                    Some(options)
+                    Some((idx.clone(), options))
                 } else {
                     None
                 }
@@ -1292,7 +1489,22 @@ fn complete_param_for_command(
 
         // Now release the lock and test each option that we captured above. We have to do this outside
         // the lock because callouts (like the condition) may add or remove completions. See issue #2.
-        for options in all_options {
+        for (idx, options) in all_options {
+            // Only use the options of the subcommand on the command line, if the command has any.
+            let mut subcommand_names = vec![];
+            let options = if options.iter().any(|o| !o.subcommand.is_empty()) {
+                let (subcommand, open) = find_subcommand(&options, arguments);
+                if open {
+                    subcommand_names = subcommand_completions(&options, &subcommand);
+                }
+                options
+                    .into_iter()
+                    .filter(|o| o.applies_to_subcommand(&subcommand))
+                    .collect()
+            } else {
+                options
+            };
+
             let short_opt_pos = short_option_pos(s, &options);
             // We want last_option_requires_param to default to false but distinguish between when
             // a previous completion has set it to false and when it has its default value.
@@ -1335,7 +1547,13 @@ fn complete_param_for_command(
                                 }
                                 let (arg_prefix, arg) = s.split_once(arg_offset);
                                 let first_new = self.completions.completions.len();
-                                self.complete_from_args(arg, &o.comp, o.desc.localize(), o.flags);
+                                self.complete_from_args(
+                                    arg,
+                                    &o.comp,
+                                    o.desc.localize(),
+                                    o.flags,
+                                    o.cache_key(&idx),
+                                );
                                 for compl in &mut self.completions.completions[first_new..] {
                                     if compl.replaces_token() {
                                         compl.completion.insert_utfstr(0, arg_prefix);
@@ -1366,7 +1584,13 @@ fn complete_param_for_command(
                             if o.result_mode.force_files {
                                 has_force = true;
                             }
-                            self.complete_from_args(s, &o.comp, o.desc.localize(), o.flags);
+                            self.complete_from_args(
+                                s,
+                                &o.comp,
+                                o.desc.localize(),
+                                o.flags,
+                                o.cache_key(&idx),
+                            );
                         }
                     }
 
@@ -1402,7 +1626,13 @@ fn complete_param_for_command(
                                 if o.result_mode.force_files {
                                     has_force = true;
                                 }
-                                self.complete_from_args(s, &o.comp, o.desc.localize(), o.flags);
+                                self.complete_from_args(
+                                    s,
+                                    &o.comp,
+                                    o.desc.localize(),
+                                    o.flags,
+                                    o.cache_key(&idx),
+                                );
                             }
                         }
                     }
@@ -1413,6 +1643,18 @@ fn complete_param_for_command(
                 continue;
             }
 
+            // Offer the subcommands which may follow instead of files.
+            if !subcommand_names.is_empty() {
+                use_files = false;
+                self.complete_strings(
+                    &escape(s),
+                    &const_desc(L!("")),
+                    &subcommand_names,
+                    CompleteFlags::AUTO_SPACE,
+                    ExpandFlags::empty(),
+                );
+            }
+
             // Set a default value for last_option_requires_param only if one hasn't been set
             let last_option_requires_param = last_option_requires_param.unwrap_or(false);
 
@@ -1425,7 +1667,13 @@ fn complete_param_for_command(
                 if o.option.is_empty() {
                     use_files &= !o.result_mode.no_files;
                     has_force |= o.result_mode.force_files;
-                    self.complete_from_args(s, &o.comp, o.desc.localize(), o.flags);
+                    self.complete_from_args(
+                        s,
+                        &o.comp,
+                        o.desc.localize(),
+                        o.flags,
+                        o.cache_key(&idx),
+                    );
                 }
 
                 if !use_switches || s.is_empty() {
@@ -1618,6 +1866,9 @@ fn complete_param_expand(
                 flogf!(complete, "Error while expanding string '%s'", s);
             }
             Self::escape_opening_brackets(&mut self.completions[first_from_start..], s);
+            if handle_as_special_cd && do_file && !self.flags.autosuggestion {
+                self.rank_cd_completions(s, first_from_start);
+            }
         }
 
         let Some(sep_index) = sep_index else {
@@ -1651,6 +1902,98 @@ fn complete_param_expand(
         let _ = self.completions.extend(local_completions);
     }
 
+    /// Put the completions of `cd` starting at index `first` in the order of how frecently the
+    /// directories were visited, followed by the unvisited ones. If the token is a plain name,
+    /// also offer the visited directories which match it, wherever they are.
+    fn rank_cd_completions(&mut self, s: &wstr, first: usize) {
+        let visited = frecency_load();
+        if visited.is_empty() {
+            return;
+        }
+        let Some(token) =
+            unescape_string(s, UnescapeStringStyle::Script(UnescapeFlags::INCOMPLETE))
+        else {
+            return;
+        };
+        let vars = self.ctx.vars();
+        let pwd = vars.get_pwd_slash();
+        let now = frecency_now();
+        let scores: HashMap<&wstr, f64> = visited
+            .iter()
+            .map(|entry| (&entry.path[..], entry.score(now)))
+            .collect();
+
+        // Find the directory that `cd` would change to for the given completion.
+        let resolve = |comp: &Completion| {
+            let mut path = if comp.replaces_token() {
+                comp.completion.clone()
+            } else {
+                token.clone() + comp.completion.as_utfstr()
+            };
+            expand_tilde(&mut path, vars);
+            path_apply_cdpath(&path, &pwd, vars)
+                .into_iter()
+                .find_map(|dir| {
+                    let mut dir = normalize_path(&dir, true);
+                    if dir.len() > 1 && dir.ends_with('/') {
+                        dir.pop();
+                    }
+                    scores.get_key_value(&dir[..])
+                })
+                .map(|(&path, &score)| (path, score))
+        };
+
+        let mut completions = self.completions.take();
+        let mut seen = HashSet::new();
+        let mut ranked: Vec<(Completion, Option<f64>)> = completions
+            .split_off(first)
+            .into_iter()
+            .map(|mut comp| {
+                let found = resolve(&comp);
+                if let Some((path, _)) = found {
+                    comp.flags |= CompleteFlags::DONT_SORT;
+                    seen.insert(path);
+                }
+                (comp, found.map(|(_, score)| score))
+            })
+            .collect();
+        ranked.sort_by(|(_, a), (_, b)| match (a, b) {
+            (Some(a), Some(b)) => b.total_cmp(a),
+            _ => b.is_some().cmp(&a.is_some()),
+        });
+        completions.extend(ranked.into_iter().map(|(comp, _)| comp));
+
+        if !token.is_empty()
+            && !token.starts_with('.')
+            && !token.starts_with('~')
+            && !token.contains('/')
+        {
+            let current = &pwd[..pwd.len() - 1];
+            let mut jumps: Vec<_> = visited
+                .iter()
+                .filter(|entry| entry.path != current && !seen.contains(&entry.path[..]))
+                .filter(|entry| frecency_matches(&entry.path, &[&token]))
+                .filter(|entry| wstat(&entry.path).is_ok_and(|md| md.is_dir()))
+                .filter_map(|entry| {
+                    let r#match = string_fuzzy_match_string(&token, &entry.path, false)?;
+                    Some((entry, r#match))
+                })
+                .collect();
+            jumps.sort_by(|(a, _), (b, _)| b.score(now).total_cmp(&a.score(now)));
+            for (entry, r#match) in jumps {
+                completions.push(Completion::new(
+                    entry.path.clone() + L!("/"),
+                    WString::new(),
+                    r#match,
+                    CompleteFlags::REPLACES_TOKEN
+                        | CompleteFlags::NO_SPACE
+                        | CompleteFlags::DONT_SORT,
+                ));
+            }
+        }
+        let _ = self.completions.extend(completions);
+    }
+
     /// Complete the specified string as an environment variable.
     /// Returns `true` if this was a variable, so we should stop completion.
     fn complete_variable(&mut self, s: &wstr, start_offset: usize) -> bool {
@@ -1926,8 +2269,15 @@ fn apply_var_assignments<T: AsRef<wstr>>(
         Some(ScopeGuard::new((), move |_| parser.pop_block(block)))
     }
 
-    /// Complete a command by invoking user-specified completions.
-    fn complete_custom(&mut self, cmd: &wstr, cmdline: &wstr, ad: &mut CustomArgData) {
+    /// Complete a command by invoking user-specified completions. `arguments` are the arguments of
+    /// the command before the one which is being completed.
+    fn complete_custom(
+        &mut self,
+        cmd: &wstr,
+        cmdline: &wstr,
+        arguments: &[WString],
+        ad: &mut CustomArgData,
+    ) {
         if self.ctx.check_cancel() {
             return;
         }
@@ -1958,6 +2308,7 @@ fn complete_custom(&mut self, cmd: &wstr, cmdline: &wstr, ad: &mut CustomArgData
             cmd,
             &ad.previous_argument,
             &ad.current_argument,
+            arguments,
             !ad.had_ddash,
             &mut ad.do_file,
         );
@@ -1986,7 +2337,8 @@ fn walk_wrap_chain(
         }
 
         // Extract command from the command line and invoke the receiver with it.
-        self.complete_custom(cmd, cmdline, ad);
+        let arguments = command_arguments(cmdline, cmdrange);
+        self.complete_custom(cmd, cmdline, &arguments, ad);
 
         let targets = complete_get_wrap_targets(cmd);
         let wrap_depth = ad.wrap_depth;
@@ -2216,6 +2568,135 @@ fn param_match2(e: &CompleteEntryOpt, optstr: &wstr) -> Option<usize> {
     Some(cursor)
 }
 
+/// Return the unescaped arguments of the command at `cmdrange` in `cmdline`, without the argument
+/// which is being completed at the end of `cmdline`.
+fn command_arguments(cmdline: &wstr, cmdrange: SourceRange) -> Vec<WString> {
+    let args = &cmdline[cmdrange.end()..];
+    let mut result = vec![];
+    let mut after_redirection = false;
+    for tok in Tokenizer::new(args, TokFlags(0)) {
+        match tok.type_ {
+            TokenType::String if tok.end() < args.len() => {
+                if !mem::take(&mut after_redirection) {
+                    if let Some(arg) =
+                        unescape_string(tok.get_source(args), UnescapeStringStyle::default())
+                    {
+                        result.push(arg);
+                    }
+                }
+            }
+            TokenType::Redirect => after_redirection = true,
+            TokenType::Comment => (),
+            _ => break,
+        }
+    }
+    result
+}
+
+/// Return the subcommand given by `arguments` among the subcommands of the `options` of a command,
+/// and whether another subcommand may follow, which is the case unless a non-option argument that
+/// is no subcommand follows it.
+fn find_subcommand(options: &[CompleteEntryOpt], arguments: &[WString]) -> (Vec<WString>, bool) {
+    let mut subcommand: Vec<WString> = vec![];
+    let mut open = true;
+    let mut had_ddash = false;
+    let mut arguments = arguments.iter();
+    while let Some(arg) = arguments.next() {
+        if !had_ddash && arg == "--" {
+            had_ddash = true;
+            continue;
+        }
+        if !had_ddash && arg.len() > 1 && arg.starts_with("-") {
+            // Skip the argument of an option, like in "git -C dir status".
+            let scoped = options
+                .iter()
+                .filter(|o| o.typ != CompleteOptionType::ArgsOnly)
+                .filter(|o| subcommand.starts_with(&o.subcommand));
+            if option_takes_next_argument(scoped, arg) {
+                arguments.next();
+            }
+            continue;
+        }
+        let is_subcommand = open
+            && options.iter().any(|o| {
+                o.subcommand.len() > subcommand.len()
+                    && o.subcommand.starts_with(&subcommand)
+                    && o.subcommand[subcommand.len()] == *arg
+            });
+        if is_subcommand {
+            subcommand.push(arg.clone());
+        } else {
+            open = false;
+        }
+    }
+    (subcommand, open)
+}
+
+/// Test if the option `arg` given on the command line is followed by its argument in the next
+/// token, like "-o file" or "--output file".
+fn option_takes_next_argument<'a>(
+    options: impl Iterator<Item = &'a CompleteEntryOpt> + Clone,
+    arg: &wstr,
+) -> bool {
+    if leading_dash_count(arg) == 1 {
+        // This may be a group of short options like "-xvf", of which only the last one can take
+        // its argument from the next token.
+        let chars = arg.as_char_slice();
+        for (pos, &c) in chars.iter().enumerate().skip(1) {
+            let short = options
+                .clone()
+                .find(|o| o.typ == CompleteOptionType::Short && o.option.char_at(0) == c);
+            match short {
+                Some(o) if o.result_mode.requires_param => return pos + 1 == chars.len(),
+                Some(_) => (),
+                None => break,
+            }
+        }
+    }
+    options
+        .into_iter()
+        .any(|o| o.result_mode.requires_param && param_match(o, arg))
+}
+
+/// Return the names of the subcommands which may follow `subcommand`, as completions with their
+/// descriptions. A subcommand is described by a completion for it without an option or arguments.
+fn subcommand_completions(options: &[CompleteEntryOpt], subcommand: &[WString]) -> Vec<Completion> {
+    let mut names: Vec<(&wstr, &wstr)> = vec![];
+    for o in options {
+        if o.subcommand.len() <= subcommand.len() || !o.subcommand.starts_with(subcommand) {
+            continue;
+        }
+        let name = &o.subcommand[subcommand.len()];
+        let desc = if o.subcommand.len() == subcommand.len() + 1
+            && o.typ == CompleteOptionType::ArgsOnly
+            && o.comp.is_empty()
+        {
+            o.desc.localize()
+        } else {
+            L!("")
+        };
+        match names.iter_mut().find(|(n, _)| n == name) {
+            Some((_, d)) => {
+                if d.is_empty() {
+                    *d = desc;
+                }
+            }
+            None => names.push((name, desc)),
+        }
+    }
+    names
+        .into_iter()
+        .map(|(name, desc)| {
+            let mut completion = escape(name);
+            if !desc.is_empty() {
+                completion.push(PROG_COMPLETE_SEP);
+                completion.push_utfstr(desc);
+            }
+            Completion::from_completion(completion)
+        })
+        .collect()
+}
+
 /// Parses a token of short options plus one optional parameter like
 /// '-xzPARAM', where x and z are short options.
 ///
@@ -2287,6 +2768,7 @@ fn expand_command_token(ctx: &OperationContext<'_>, cmd_tok: &mut WString) -> bo
 /// - `condition`: a command to be run to check it this completion should be used. If `condition`
 ///   is empty, the completion is always used.
 /// - `flags`: A set of completion flags
+/// - `cache`: How long the expanded `comp` is reused, if at all.
 #[allow(clippy::too_many_arguments)]
 pub fn complete_add(
     cmd: WString,
@@ -2295,9 +2777,11 @@ pub fn complete_add(
     option_type: CompleteOptionType,
     result_mode: CompletionMode,
     condition: Vec<WString>,
+    subcommand: Vec<WString>,
     comp: WString,
     desc: WString,
     flags: CompleteFlags,
+    cache: Option<CompletionCache>,
 ) {
     // option should be empty iff the option type is arguments only.
     assert_eq!(
@@ -2324,20 +2808,58 @@ pub fn complete_add(
         // from which `build_tools/fish_xgettext.fish` extracts descriptions.
         desc: LocalizableString::from_external_source(desc),
         conditions: condition,
+        subcommand,
         flags,
+        cache,
     };
     c.add_option(opt);
 }
 
-/// Remove a previously defined completion.
-pub fn complete_remove(cmd: WString, cmd_is_path: bool, option: &wstr, typ: CompleteOptionType) {
+/// Forget cached completion arguments: those with the given key and those of the given command
+/// (and whether it is a path), or all of them if neither is given.
+pub fn complete_clear_cache(cmd: Option<(&wstr, bool)>, key: Option<&wstr>) {
+    args_cache_clear(|cache_key| match cache_key {
+        ArgsCacheKey::Named(name) => key.map_or(cmd.is_none(), |key| key == name),
+        ArgsCacheKey::Option {
+            command, is_path, ..
+        } => cmd.map_or(key.is_none(), |(cmd, cmd_is_path)| {
+            cmd == command && cmd_is_path == *is_path
+        }),
+    });
+}
+
+/// Remove a previously defined completion. If `subcommand` is not empty, only the completion for
+/// that subcommand is removed.
+pub fn complete_remove(
+    cmd: WString,
+    cmd_is_path: bool,
+    option: &wstr,
+    typ: CompleteOptionType,
+    subcommand: &[WString],
+) {
+    let mut completion_map = COMPLETION_MAP.lock().expect("mutex poisoned");
+    let idx = CompletionEntryIndex {
+        name: cmd,
+        is_path: cmd_is_path,
+    };
+    if let Some(c) = completion_map.get_mut(&idx) {
+        let delete_it = c.remove_option(option, typ, subcommand);
+        if delete_it {
+            completion_map.remove(&idx);
+        }
+    }
+}
+
+/// Removes all completions for a subcommand of a given command, including those of its
+/// subcommands.
+pub fn complete_remove_subcommand(cmd: WString, cmd_is_path: bool, subcommand: &[WString]) {
     let mut completion_map = COMPLETION_MAP.lock().expect("mutex poisoned");
     let idx = CompletionEntryIndex {
         name: cmd,
         is_path: cmd_is_path,
     };
     if let Some(c) = completion_map.get_mut(&idx) {
-        let delete_it = c.remove_option(option, typ);
+        let delete_it = c.remove_subcommand(subcommand);
         if delete_it {
             completion_map.remove(&idx);
         }
@@ -2425,6 +2947,14 @@ fn completion2string(index: &CompletionEntryIndex, o: &CompleteEntryOpt) -> WStr
         out.push_utfstr(&escape(&index.name));
     }
 
+    if !o.subcommand.is_empty() {
+        append_switch_long_arg(
+            &mut out,
+            L!("subcommand"),
+            &join_strings(&o.subcommand, ' '),
+        );
+    }
+
     match o.typ {
         CompleteOptionType::ArgsOnly => {}
         CompleteOptionType::Short => append_switch_short_arg(&mut out, 's', &o.option[..1]),
@@ -2437,6 +2967,12 @@ fn completion2string(index: &CompletionEntryIndex, o: &CompleteEntryOpt) -> WStr
     for c in &o.conditions {
         append_switch_short_arg(&mut out, 'n', c);
     }
+    if let Some(cache) = &o.cache {
+        append_switch_long_arg(&mut out, L!("cache-ttl"), &cache.ttl.as_secs().to_wstring());
+        if let Some(key) = &cache.key {
+            append_switch_long_arg(&mut out, L!("cache-key"), key);
+        }
+    }
     out.push('\n');
 
     out
@@ -2509,6 +3045,20 @@ pub fn complete_load(cmd: &wstr, parser: &Parser) -> bool {
 /// Return a list of all current completions.
 /// Used by the bare `complete`, loaded completions are printed out as commands
 pub fn complete_print(cmd: &wstr) -> WString {
+    complete_print_filtered(|name, _is_path| cmd.is_empty() || name == cmd)
+}
+
+/// Returns a script which defines the completions that were not loaded from completion files,
+/// like those from config.fish or the command line.
+pub fn complete_print_unautoloaded() -> WString {
+    let autoloaded = COMPLETION_AUTOLOADER
+        .lock()
+        .expect("mutex poisoned")
+        .get_autoloaded_commands();
+    complete_print_filtered(|name, is_path| is_path || !autoloaded.iter().any(|c| c == name))
+}
+
+fn complete_print_filtered(include: impl Fn(&wstr, bool) -> bool) -> WString {
     let mut out = WString::new();
 
     // Get references to our completions and sort them by order.
@@ -2517,7 +3067,7 @@ pub fn complete_print(cmd: &wstr) -> WString {
     completion_refs.sort_by_key(|(_, c)| c.order);
 
     for (key, entry) in completion_refs {
-        if !cmd.is_empty() && key.name != cmd {
+        if !include(&key.name, key.is_path) {
             continue;
         }
 
@@ -2530,7 +3080,7 @@ pub fn complete_print(cmd: &wstr) -> WString {
     // Append wraps.
     let wrappers = WRAPPER_MAP.lock().expect("poisoned mutex");
     for (src, targets) in wrappers.iter() {
-        if !cmd.is_empty() && src != cmd {
+        if !include(src, false) {
             continue;
         }
         for target in targets {
@@ -2628,9 +3178,10 @@ pub struct CompletionRequestOptions {
 #[cfg(test)]
 mod tests {
     use super::{
-        CompleteFlags, CompleteOptionType, CompletionMode, CompletionRequestOptions, complete,
-        complete_add, complete_add_wrapper, complete_get_wrap_targets, complete_remove_wrapper,
-        sort_and_prioritize,
+        CompleteEntryOpt, CompleteFlags, CompleteOptionType, Completion, CompletionMode,
+        CompletionRequestOptions, complete, complete_add, complete_add_wrapper,
+        complete_get_wrap_targets, complete_remove_wrapper, find_subcommand, sort_and_prioritize,
+        subcommand_completions,
     };
     use crate::abbrs::{self, Abbreviation, with_abbrs_mut};
     use crate::common::str2wcstring;
@@ -2643,7 +3194,7 @@ mod tests {
     use crate::prelude::*;
     use crate::reader::completion_apply_to_command_line;
     use crate::tests::prelude::*;
-    use fish_wcstringutil::join_strings;
+    use fish_wcstringutil::{CaseSensitivity, ContainType, StringFuzzyMatch, join_strings};
     use std::collections::HashMap;
     use std::ffi::CString;
 
@@ -2970,9 +3521,11 @@ macro_rules! unique_completion_applies_as {
             CompleteOptionType::ArgsOnly,
             no_files,
             vec![],
+            vec![],
             L!("qux").into(),
             WString::new(),
             CompleteFlags::AUTO_SPACE,
+            None,
         );
         let completions = do_complete(L!("foobarbaz "), CompletionRequestOptions::default());
         assert_eq!(completions.len(), 1);
@@ -3373,4 +3926,78 @@ macro_rules! perform_one_autosuggestion_should_ignore_test {
         perform_one_autosuggestion_should_ignore_test!("echo PIPE_TEST#comment");
         perform_one_autosuggestion_should_ignore_test!("echo PIPE_TEST;");
     }
+
+    #[test]
+    fn test_completion_json() {
+        let mut r#match = StringFuzzyMatch::new(ContainType::Substr, CaseSensitivity::Smart);
+        r#match.from_separator = true;
+        let comps = [
+            Completion::from_completion(L!("foo").to_owned()),
+            Completion::new(
+                L!("a\tb c%\n").to_owned(),
+                L!("desc\twith tab").to_owned(),
+                r#match,
+                CompleteFlags::REPLACES_TOKEN | CompleteFlags::NO_SPACE,
+            ),
+            Completion::with_desc(L!("").to_owned(), L!("").to_owned()),
+        ];
+        for comp in comps {
+            let line = comp.to_json(L!("token"));
+            assert!(!line.contains('\n'));
+            assert_eq!(Completion::from_json(&line), Some(comp));
+        }
+        assert_eq!(Completion::from_json(L!("foo")), None);
+        assert_eq!(
+            Completion::from_json(L!(
+                r#"{"completion": "a", "description": "", "flags": ["bogus"], "match": {"type": "exact", "case": "sensitive", "from_separator": false}}"#
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn test_find_subcommand() {
+        let opt = |subcommand: &str, typ, option: &str, requires_param| CompleteEntryOpt {
+            option: option.into(),
+            comp: WString::new(),
+            desc: LocalizableString::from_external_source(WString::new()),
+            conditions: vec![],
+            subcommand: subcommand.split_whitespace().map(WString::from).collect(),
+            typ,
+            result_mode: CompletionMode {
+                requires_param,
+                ..Default::default()
+            },
+            flags: CompleteFlags::empty(),
+            cache: None,
+        };
+        let options = [
+            opt("", CompleteOptionType::Short, "C", true),
+            opt("", CompleteOptionType::Short, "v", false),
+            opt("add", CompleteOptionType::ArgsOnly, "", false),
+            opt("remote add", CompleteOptionType::DoubleLong, "name", true),
+            opt("remote remove", CompleteOptionType::ArgsOnly, "", false),
+        ];
+        let find = |args: &str| {
+            let args: Vec<WString> = args.split_whitespace().map(WString::from).collect();
+            let (subcommand, open) = find_subcommand(&options, &args);
+            (comma_join(subcommand), open)
+        };
+        assert_eq!(find(""), (L!("").to_owned(), true));
+        assert_eq!(find("remote"), (L!("remote").to_owned(), true));
+        assert_eq!(find("-v remote add"), (L!("remote,add").to_owned(), true));
+        assert_eq!(find("-vC add remote"), (L!("remote").to_owned(), true));
+        assert_eq!(find("-C dir add"), (L!("add").to_owned(), true));
+        assert_eq!(find("add remote"), (L!("add").to_owned(), false));
+        assert_eq!(find("other remote"), (L!("").to_owned(), false));
+        assert_eq!(
+            find("remote add --name add x"),
+            (L!("remote,add").to_owned(), false)
+        );
+        assert_eq!(find("-- -v remote"), (L!("").to_owned(), false));
+
+        let names = subcommand_completions(&options, &[L!("remote").to_owned()]);
+        let names: Vec<_> = names.into_iter().map(|c| c.completion).collect();
+        assert_eq!(names, [L!("add"), L!("remove")]);
+    }
 }
@@ -0,0 +1,272 @@
+//! The cache of expanded completion arguments, see `complete --cache-ttl`.
+//!
+//! The cache is kept in a file in the cache directory, so it is shared by all fish processes,
+//! including those which compute completions in the background. Each line holds the time until
+//! which the arguments are valid, the key and the expanded arguments, all URL-escaped and
+//! separated by tabs.
+
+use crate::common::{
+    EscapeStringStyle, UnescapeStringStyle, bytes2wcstring, escape_string, unescape_string,
+    wcs2bytes,
+};
+use crate::complete::CompleteOptionType;
+use crate::flog::flog;
+use crate::fs::{PotentialUpdate, lock_and_load, rewrite_via_temporary_file};
+use crate::path::path_get_cache;
+use crate::prelude::*;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The name of the cache file in the cache directory.
+const CACHE_FILE_NAME: &str = "completion_args";
+
+/// The key under which expanded completion arguments are cached.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArgsCacheKey {
+    /// A key given with `complete --cache-key`.
+    Named(WString),
+    /// The arguments of an option of a command.
+    Option {
+        command: WString,
+        is_path: bool,
+        option: WString,
+        typ: CompleteOptionType,
+        args: WString,
+    },
+}
+
+/// Expanded completion arguments, and until when they are used.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct CachedArgs {
+    key: ArgsCacheKey,
+    /// In milliseconds since the epoch.
+    expires: u64,
+    args: Vec<WString>,
+}
+
+/// Return the current time in milliseconds since the epoch.
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis().try_into().unwrap_or(u64::MAX))
+}
+
+/// Return the path of the cache file, or None if there is no cache directory.
+fn cache_path() -> Option<WString> {
+    let mut path = path_get_cache()?;
+    path.push('/');
+    path.push_str(CACHE_FILE_NAME);
+    Some(path)
+}
+
+/// Parse the contents of the cache file, skipping invalid lines.
+fn parse(contents: &[u8]) -> Vec<CachedArgs> {
+    let mut entries = vec![];
+    for line in contents.split(|&c| c == b'\n') {
+        let line = bytes2wcstring(line);
+        let Some(fields) = line
+            .split('\t')
+            .map(|field| unescape_string(field, UnescapeStringStyle::Url))
+            .collect::<Option<Vec<WString>>>()
+        else {
+            continue;
+        };
+        let Some(entry) = parse_entry(&fields) else {
+            continue;
+        };
+        entries.push(entry);
+    }
+    entries
+}
+
+/// Parse the unescaped fields of a line of the cache file.
+fn parse_entry(fields: &[WString]) -> Option<CachedArgs> {
+    let (expires, fields) = fields.split_first()?;
+    let expires = expires.to_string().parse::<u64>().ok()?;
+    let (kind, fields) = fields.split_first()?;
+    let (key, args) = match kind.to_string().as_str() {
+        "key" => {
+            let (name, args) = fields.split_first()?;
+            (ArgsCacheKey::Named(name.clone()), args)
+        }
+        "option" => {
+            let [command, is_path, typ, option, args, rest @ ..] = fields else {
+                return None;
+            };
+            let is_path = match is_path.to_string().as_str() {
+                "0" => false,
+                "1" => true,
+                _ => return None,
+            };
+            let typ = match typ.to_string().as_str() {
+                "args" => CompleteOptionType::ArgsOnly,
+                "short" => CompleteOptionType::Short,
+                "old" => CompleteOptionType::SingleLong,
+                "long" => CompleteOptionType::DoubleLong,
+                _ => return None,
+            };
+            let key = ArgsCacheKey::Option {
+                command: command.clone(),
+                is_path,
+                option: option.clone(),
+                typ,
+                args: args.clone(),
+            };
+            (key, rest)
+        }
+        _ => return None,
+    };
+    Some(CachedArgs {
+        key,
+        expires,
+        args: args.to_vec(),
+    })
+}
+
+/// Return the contents of the cache file for the given entries.
+fn serialize(entries: &[CachedArgs]) -> Vec<u8> {
+    let mut contents = WString::new();
+    for entry in entries {
+        let mut fields = vec![entry.expires.to_string().into()];
+        match &entry.key {
+            ArgsCacheKey::Named(name) => {
+                fields.push(L!("key").to_owned());
+                fields.push(name.clone());
+            }
+            ArgsCacheKey::Option {
+                command,
+                is_path,
+                option,
+                typ,
+                args,
+            } => {
+                let typ = match typ {
+                    CompleteOptionType::ArgsOnly => "args",
+                    CompleteOptionType::Short => "short",
+                    CompleteOptionType::SingleLong => "old",
+                    CompleteOptionType::DoubleLong => "long",
+                };
+                fields.push(L!("option").to_owned());
+                fields.push(command.clone());
+                fields.push(if *is_path { "1" } else { "0" }.into());
+                fields.push(typ.into());
+                fields.push(option.clone());
+                fields.push(args.clone());
+            }
+        }
+        fields.extend(entry.args.iter().cloned());
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                contents.push('\t');
+            }
+            contents.push_utfstr(&escape_string(field, EscapeStringStyle::Url));
+        }
+        contents.push('\n');
+    }
+    wcs2bytes(&contents)
+}
+
+/// Read and parse the given cache file.
+fn read_entries(file: &File) -> std::io::Result<Vec<CachedArgs>> {
+    let mut contents = vec![];
+    (&*file).read_to_end(&mut contents)?;
+    Ok(parse(&contents))
+}
+
+/// Modify the entries in the cache file with `update`. Expired entries are dropped.
+fn update_cache(update: impl Fn(&mut Vec<CachedArgs>)) {
+    let Some(path) = cache_path() else {
+        return;
+    };
+    let rewrite = |old_file: &File, tmp_file: &mut File| {
+        let mut entries = read_entries(old_file)?;
+        let now = now();
+        entries.retain(|entry| entry.expires > now);
+        update(&mut entries);
+        tmp_file.write_all(&serialize(&entries))?;
+        Ok(PotentialUpdate {
+            do_save: true,
+            data: (),
+        })
+    };
+    if let Err(err) = rewrite_via_temporary_file(&path, rewrite) {
+        flog!(complete, "Failed to write the completion cache:", err);
+    }
+}
+
+/// Return the cached arguments with the given key, unless they have expired.
+pub fn args_cache_get(key: &ArgsCacheKey) -> Option<Vec<WString>> {
+    let path = cache_path()?;
+    let entries = match lock_and_load(&path, |file, _| read_entries(file)) {
+        Ok((_, entries)) => entries,
+        Err(err) => {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                flog!(complete, "Failed to read the completion cache:", err);
+            }
+            return None;
+        }
+    };
+    let now = now();
+    entries
+        .into_iter()
+        .find(|entry| entry.key == *key && entry.expires > now)
+        .map(|entry| entry.args)
+}
+
+/// Cache the expanded arguments under the given key for the duration `ttl`.
+pub fn args_cache_put(key: ArgsCacheKey, ttl: Duration, args: Vec<WString>) {
+    let ttl: u64 = ttl.as_millis().try_into().unwrap_or(u64::MAX);
+    let entry = CachedArgs {
+        key,
+        expires: now().saturating_add(ttl),
+        args,
+    };
+    update_cache(|entries| {
+        entries.retain(|old| old.key != entry.key);
+        entries.push(entry.clone());
+    });
+}
+
+/// Forget the cached arguments whose key matches `clear`.
+pub fn args_cache_clear(clear: impl Fn(&ArgsCacheKey) -> bool) {
+    update_cache(|entries| entries.retain(|entry| !clear(&entry.key)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArgsCacheKey, CachedArgs, parse, serialize};
+    use crate::complete::CompleteOptionType;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_args_cache_file() {
+        let entries = vec![
+            CachedArgs {
+                key: ArgsCacheKey::Named(L!("pkg\tavailable").to_owned()),
+                expires: 100,
+                args: vec![L!("a b").to_owned(), L!("c\ndesc").to_owned()],
+            },
+            CachedArgs {
+                key: ArgsCacheKey::Option {
+                    command: L!("/bin/cmd").to_owned(),
+                    is_path: true,
+                    option: L!("p").to_owned(),
+                    typ: CompleteOptionType::Short,
+                    args: L!("(cmd list)").to_owned(),
+                },
+                expires: 200,
+                args: vec![],
+            },
+        ];
+        assert_eq!(parse(&serialize(&entries)), entries);
+        assert_eq!(
+            parse(b"x\tkey\ta\n1\tfoo\ta\n\n1\toption\tcmd\t2\targs\t\t\n2\tkey\tk\ta\n"),
+            [CachedArgs {
+                key: ArgsCacheKey::Named(L!("k").to_owned()),
+                expires: 2,
+                args: vec![L!("a").to_owned()],
+            }]
+        );
+    }
+}
@@ -0,0 +1,645 @@
+//! Importing completions from declarative specs.
+//!
+//! A spec is a JSON document which describes a command with its options, arguments and
+//! subcommands. It is turned into completions which are registered with [`complete_add`], like
+//! those given to the `complete` builtin. See the documentation of `complete --import-spec` for
+//! the format.
+
+use crate::common::escape;
+use crate::complete::{
+    CompleteFlags, CompleteOptionType, CompletionMode, complete_add, complete_remove_all,
+};
+use crate::parse_util::detect_errors_in_argument_list;
+use crate::prelude::*;
+
+/// A JSON value.
+#[derive(Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number,
+    String(WString),
+    Array(Vec<Json>),
+    Object(Vec<(WString, Json)>),
+}
+
+impl Json {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Json::Null => "null",
+            Json::Bool(_) => "boolean",
+            Json::Number => "number",
+            Json::String(_) => "string",
+            Json::Array(_) => "array",
+            Json::Object(_) => "object",
+        }
+    }
+}
+
+/// An error in a spec.
+#[derive(Debug, PartialEq)]
+pub struct SpecError {
+    /// The line of a syntax error, or None if the error is in the structure of the spec.
+    pub line: Option<usize>,
+    pub message: WString,
+}
+
+impl SpecError {
+    fn new(message: WString) -> Self {
+        Self {
+            line: None,
+            message,
+        }
+    }
+}
+
+/// A parser for the subset of JSON that specs need. Numbers are accepted, but their value is
+/// not used.
+struct JsonParser<'a> {
+    chars: &'a [char],
+    pos: usize,
+    /// How many objects and arrays enclose the current position.
+    depth: usize,
+}
+
+/// The maximum nesting of objects and arrays, so that deeply nested input can't overflow the stack.
+const MAX_JSON_DEPTH: usize = 64;
+
+impl<'a> JsonParser<'a> {
+    fn error(&self, message: WString) -> SpecError {
+        let line = 1 + self.chars[..self.pos]
+            .iter()
+            .filter(|&&c| c == '\n')
+            .count();
+        SpecError {
+            line: Some(line),
+            message,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), SpecError> {
+        self.skip_whitespace();
+        if self.peek() != Some(c) {
+            return Err(self.error(wgettext_fmt!("expected '%s'", c.to_string())));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn parse_document(&mut self) -> Result<Json, SpecError> {
+        let value = self.parse_value()?;
+        self.skip_whitespace();
+        if self.peek().is_some() {
+            return Err(self.error(wgettext!("unexpected text after the spec").to_owned()));
+        }
+        Ok(value)
+    }
+
+    fn parse_value(&mut self) -> Result<Json, SpecError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(c @ ('{' | '[')) => {
+                if self.depth == MAX_JSON_DEPTH {
+                    return Err(self.error(wgettext_fmt!(
+                        "objects and arrays are nested more than %d levels deep",
+                        MAX_JSON_DEPTH
+                    )));
+                }
+                self.depth += 1;
+                let value = if c == '{' {
+                    self.parse_object()
+                } else {
+                    self.parse_array()
+                };
+                self.depth -= 1;
+                value
+            }
+            Some('"') => Ok(Json::String(self.parse_string()?)),
+            Some('-' | '0'..='9') => {
+                while matches!(self.peek(), Some('-' | '+' | '.' | 'e' | 'E' | '0'..='9')) {
+                    self.pos += 1;
+                }
+                Ok(Json::Number)
+            }
+            Some('t' | 'f' | 'n') => {
+                for (word, value) in [
+                    ("true", Json::Bool(true)),
+                    ("false", Json::Bool(false)),
+                    ("null", Json::Null),
+                ] {
+                    let end = self.pos + word.len();
+                    if end <= self.chars.len()
+                        && self.chars[self.pos..end].iter().copied().eq(word.chars())
+                    {
+                        self.pos = end;
+                        return Ok(value);
+                    }
+                }
+                Err(self.error(wgettext!("expected a value").to_owned()))
+            }
+            _ => Err(self.error(wgettext!("expected a value").to_owned())),
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Json, SpecError> {
+        self.expect('{')?;
+        let mut members = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Json::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.error(wgettext!("expected a key").to_owned()));
+            }
+            let key = self.parse_string()?;
+            self.expect(':')?;
+            members.push((key, self.parse_value()?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(Json::Object(members));
+                }
+                _ => return Err(self.error(wgettext!("expected ',' or '}'").to_owned())),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Json, SpecError> {
+        self.expect('[')?;
+        let mut elements = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(Json::Array(elements));
+        }
+        loop {
+            elements.push(self.parse_value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {
+                    self.pos += 1;
+                    return Ok(Json::Array(elements));
+                }
+                _ => return Err(self.error(wgettext!("expected ',' or ']'").to_owned())),
+            }
+        }
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, SpecError> {
+        let mut result = 0;
+        for _ in 0..4 {
+            let Some(digit) = self.peek().and_then(|c| c.to_digit(16)) else {
+                return Err(self.error(wgettext!("invalid unicode escape").to_owned()));
+            };
+            result = result * 16 + digit;
+            self.pos += 1;
+        }
+        Ok(result)
+    }
+
+    fn parse_string(&mut self) -> Result<WString, SpecError> {
+        // Skip the opening quote.
+        self.pos += 1;
+        let mut result = WString::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(self.error(wgettext!("unterminated string").to_owned()));
+            };
+            self.pos += 1;
+            match c {
+                '"' => return Ok(result),
+                '\\' => {
+                    let Some(esc) = self.peek() else {
+                        return Err(self.error(wgettext!("unterminated string").to_owned()));
+                    };
+                    self.pos += 1;
+                    let c = match esc {
+                        '"' | '\\' | '/' => esc,
+                        'b' => '\x08',
+                        'f' => '\x0c',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'u' => {
+                            let mut code = self.parse_hex4()?;
+                            // Combine surrogate pairs.
+                            if (0xD800..0xDC00).contains(&code)
+                                && self.chars[self.pos..].starts_with(&['\\', 'u'])
+                            {
+                                self.pos += 2;
+                                let low = self.parse_hex4()?;
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return Err(
+                                        self.error(wgettext!("invalid unicode escape").to_owned())
+                                    );
+                                }
+                                code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                            }
+                            let Some(c) = char::from_u32(code) else {
+                                return Err(
+                                    self.error(wgettext!("invalid unicode escape").to_owned())
+                                );
+                            };
+                            c
+                        }
+                        _ => return Err(self.error(wgettext!("invalid escape").to_owned())),
+                    };
+                    result.push(c);
+                }
+                c => result.push(c),
+            }
+        }
+    }
+}
+
+/// The values of an argument, to an option or a (sub)command.
+#[derive(Debug, Default, PartialEq)]
+struct SpecArguments {
+    /// Fixed values, with their descriptions.
+    values: Vec<(WString, WString)>,
+    /// A command whose output lists further values, like a command substitution in
+    /// `complete --arguments`.
+    command: Option<WString>,
+    /// Whether files are completed as well.
+    files: bool,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct SpecOption {
+    short: Vec<WString>,
+    long: Vec<WString>,
+    old: Vec<WString>,
+    description: WString,
+    argument: Option<SpecArguments>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct SpecCommand {
+    name: WString,
+    description: WString,
+    options: Vec<SpecOption>,
+    arguments: Option<SpecArguments>,
+    subcommands: Vec<SpecCommand>,
+}
+
+/// Return an error for a member with an unexpected type.
+fn type_error(key: &wstr, expected: &str, value: &Json) -> SpecError {
+    SpecError::new(wgettext_fmt!(
+        "'%s' must be %s, not %s",
+        key,
+        expected,
+        value.type_name()
+    ))
+}
+
+fn expect_object(key: &wstr, value: Json) -> Result<Vec<(WString, Json)>, SpecError> {
+    match value {
+        Json::Object(members) => Ok(members),
+        value => Err(type_error(key, "an object", &value)),
+    }
+}
+
+fn expect_string(key: &wstr, value: Json) -> Result<WString, SpecError> {
+    match value {
+        Json::String(s) => Ok(s),
+        value => Err(type_error(key, "a string", &value)),
+    }
+}
+
+fn expect_array(key: &wstr, value: Json) -> Result<Vec<Json>, SpecError> {
+    match value {
+        Json::Array(elements) => Ok(elements),
+        value => Err(type_error(key, "an array", &value)),
+    }
+}
+
+/// Return a string or an array of strings as a list.
+fn expect_names(key: &wstr, value: Json) -> Result<Vec<WString>, SpecError> {
+    match value {
+        Json::String(s) => Ok(vec![s]),
+        Json::Array(elements) => elements
+            .into_iter()
+            .map(|element| expect_string(key, element))
+            .collect(),
+        value => Err(type_error(key, "a string or an array of strings", &value)),
+    }
+}
+
+fn parse_arguments(key: &wstr, value: Json) -> Result<SpecArguments, SpecError> {
+    let mut result = SpecArguments::default();
+    for (key, value) in expect_object(key, value)? {
+        match key.to_string().as_str() {
+            "values" => {
+                for element in expect_array(&key, value)? {
+                    result.values.push(match element {
+                        Json::String(s) => (s, WString::new()),
+                        Json::Object(members) => {
+                            let mut value = None;
+                            let mut description = WString::new();
+                            for (key, member) in members {
+                                match key.to_string().as_str() {
+                                    "value" => value = Some(expect_string(&key, member)?),
+                                    "description" => description = expect_string(&key, member)?,
+                                    _ => (),
+                                }
+                            }
+                            let Some(value) = value else {
+                                return Err(SpecError::new(
+                                    wgettext!("a value is missing its 'value'").to_owned(),
+                                ));
+                            };
+                            (value, description)
+                        }
+                        element => {
+                            return Err(type_error(
+                                &key,
+                                "an array of strings or objects",
+                                &element,
+                            ));
+                        }
+                    });
+                }
+            }
+            "command" => {
+                let command = expect_string(&key, value)?;
+                let subst = L!("(").to_owned() + &command[..] + L!(")");
+                if detect_errors_in_argument_list(&subst, L!("")).is_err() {
+                    return Err(SpecError::new(wgettext_fmt!(
+                        "command '%s' contains a syntax error",
+                        command
+                    )));
+                }
+                result.command = Some(command);
+            }
+            "files" => match value {
+                Json::Bool(files) => result.files = files,
+                value => return Err(type_error(&key, "a boolean", &value)),
+            },
+            _ => (),
+        }
+    }
+    Ok(result)
+}
+
+fn parse_option(value: Json) -> Result<SpecOption, SpecError> {
+    let mut result = SpecOption::default();
+    for (key, value) in expect_object(L!("options"), value)? {
+        match key.to_string().as_str() {
+            "short" => {
+                result.short = expect_names(&key, value)?;
+                if let Some(short) = result.short.iter().find(|s| s.char_count() != 1) {
+                    return Err(SpecError::new(wgettext_fmt!(
+                        "short option '%s' must be a single character",
+                        short
+                    )));
+                }
+            }
+            "long" => result.long = expect_names(&key, value)?,
+            "old" => result.old = expect_names(&key, value)?,
+            "description" => result.description = expect_string(&key, value)?,
+            "argument" => result.argument = Some(parse_arguments(&key, value)?),
+            _ => (),
+        }
+    }
+    if result.short.is_empty() && result.long.is_empty() && result.old.is_empty() {
+        return Err(SpecError::new(
+            wgettext!("an option needs 'short', 'long' or 'old'").to_owned(),
+        ));
+    }
+    Ok(result)
+}
+
+fn parse_command(key: &wstr, value: Json) -> Result<SpecCommand, SpecError> {
+    let mut result = SpecCommand::default();
+    for (key, value) in expect_object(key, value)? {
+        match key.to_string().as_str() {
+            "name" => result.name = expect_string(&key, value)?,
+            "description" => result.description = expect_string(&key, value)?,
+            "options" => {
+                for option in expect_array(&key, value)? {
+                    result.options.push(parse_option(option)?);
+                }
+            }
+            "arguments" => result.arguments = Some(parse_arguments(&key, value)?),
+            "subcommands" => {
+                for subcommand in expect_array(&key, value)? {
+                    result.subcommands.push(parse_command(&key, subcommand)?);
+                }
+            }
+            _ => (),
+        }
+    }
+    if result.name.is_empty() {
+        return Err(SpecError::new(
+            wgettext!("a command or subcommand needs a 'name'").to_owned(),
+        ));
+    }
+    Ok(result)
+}
+
+/// Return the `--arguments` string which offers the given arguments.
+fn arguments_string(args: &SpecArguments) -> WString {
+    let mut result = WString::new();
+    for (value, description) in &args.values {
+        if !result.is_empty() {
+            result.push(' ');
+        }
+        let mut value = value.clone();
+        if !description.is_empty() {
+            value.push('\t');
+            value.push_utfstr(description);
+        }
+        result.push_utfstr(&escape(&value));
+    }
+    if let Some(command) = &args.command {
+        if !result.is_empty() {
+            result.push(' ');
+        }
+        result.push('(');
+        result.push_utfstr(command);
+        result.push(')');
+    }
+    result
+}
+
+/// Register the completions of a command or one of its subcommands, given by `subcommand`.
+fn add_command(cmd: &wstr, command: &SpecCommand, subcommand: &[WString]) {
+    for option in &command.options {
+        let result_mode = CompletionMode {
+            no_files: option.argument.as_ref().is_some_and(|arg| !arg.files),
+            force_files: false,
+            requires_param: option.argument.is_some(),
+        };
+        let comp = option
+            .argument
+            .as_ref()
+            .map(arguments_string)
+            .unwrap_or_default();
+        let names = option
+            .short
+            .iter()
+            .map(|name| (name, CompleteOptionType::Short))
+            .chain((option.long.iter()).map(|name| (name, CompleteOptionType::DoubleLong)))
+            .chain((option.old.iter()).map(|name| (name, CompleteOptionType::SingleLong)));
+        for (name, option_type) in names {
+            complete_add(
+                cmd.to_owned(),
+                false,
+                name.clone(),
+                option_type,
+                result_mode,
+                vec![],
+                subcommand.to_vec(),
+                comp.clone(),
+                option.description.clone(),
+                CompleteFlags::AUTO_SPACE,
+                None,
+            );
+        }
+    }
+
+    if let Some(args) = &command.arguments {
+        // Files are not offered along with subcommands unless forced.
+        complete_add(
+            cmd.to_owned(),
+            false,
+            WString::new(),
+            CompleteOptionType::ArgsOnly,
+            CompletionMode {
+                no_files: !args.files,
+                force_files: args.files && !command.subcommands.is_empty(),
+                requires_param: false,
+            },
+            vec![],
+            subcommand.to_vec(),
+            arguments_string(args),
+            WString::new(),
+            CompleteFlags::AUTO_SPACE,
+            None,
+        );
+    }
+
+    for child in &command.subcommands {
+        let mut child_subcommand = subcommand.to_vec();
+        child_subcommand.push(child.name.clone());
+        // Declare the subcommand, so it is offered even if it has no completions of its own.
+        complete_add(
+            cmd.to_owned(),
+            false,
+            WString::new(),
+            CompleteOptionType::ArgsOnly,
+            CompletionMode::default(),
+            vec![],
+            child_subcommand.clone(),
+            WString::new(),
+            child.description.clone(),
+            CompleteFlags::AUTO_SPACE,
+            None,
+        );
+        add_command(cmd, child, &child_subcommand);
+    }
+}
+
+/// Parse a JSON document.
+pub fn parse_json(s: &wstr) -> Result<Json, SpecError> {
+    JsonParser {
+        chars: s.as_char_slice(),
+        pos: 0,
+        depth: 0,
+    }
+    .parse_document()
+}
+
+/// Parse a spec and register its completions, replacing any existing completions for the
+/// command. Returns the name of the command.
+pub fn complete_import_spec(spec: &wstr) -> Result<WString, SpecError> {
+    let command = parse_command(L!("spec"), parse_json(spec)?)?;
+    complete_remove_all(command.name.clone(), false, /*explicit=*/ false);
+    add_command(&command.name, &command, &[]);
+    Ok(command.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Json, complete_import_spec, parse_json as parse};
+    use crate::complete::complete_print;
+    use crate::prelude::*;
+    use crate::tests::prelude::*;
+
+    #[test]
+    fn test_json_parser() {
+        assert_eq!(
+            parse(L!(
+                r#" {"a": [1, -2.5e3, true, null, "x\tyü😀"], "b": {}} "#
+            )),
+            Ok(Json::Object(vec![
+                (
+                    L!("a").to_owned(),
+                    Json::Array(vec![
+                        Json::Number,
+                        Json::Number,
+                        Json::Bool(true),
+                        Json::Null,
+                        Json::String(L!("x\ty\u{fc}\u{1f600}").to_owned()),
+                    ])
+                ),
+                (L!("b").to_owned(), Json::Object(vec![])),
+            ]))
+        );
+        assert_eq!(parse(L!("{\n\"a\": [1,\n 2")).unwrap_err().line, Some(3));
+        assert!(parse(L!(r#""\x""#)).is_err());
+        assert!(parse(L!(r#"{"a" 1}"#)).is_err());
+        assert!(parse(L!("[] []")).is_err());
+        let nested = |depth| WString::from("[".repeat(depth) + &"]".repeat(depth));
+        assert!(parse(&nested(64)).is_ok());
+        assert!(parse(&nested(65)).is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_import_spec() {
+        let _cleanup = test_init();
+        let name = complete_import_spec(L!(r#"{
+            "name": "spec-tool",
+            "options": [
+                {"short": "v", "long": "verbose", "description": "Be verbose"},
+                {"long": "color", "argument": {"values": ["auto", {"value": "never", "description": "No colors"}]}}
+            ],
+            "subcommands": [
+                {"name": "add", "description": "Add a thing", "arguments": {"files": true}}
+            ]
+        }"#))
+        .unwrap();
+        assert_eq!(name, "spec-tool");
+        let printed = complete_print(L!("spec-tool")).to_string();
+        assert!(printed.contains("complete spec-tool -s v -d 'Be verbose'\n"));
+        assert!(printed.contains(
+            "complete --exclusive spec-tool -l color -a 'auto never\\\\tNo\\\\ colors'\n"
+        ));
+        assert!(printed.contains("complete spec-tool --subcommand add -d 'Add a thing'\n"));
+
+        let err = complete_import_spec(L!(r#"{"name": "x", "options": [{"long": 1}]}"#));
+        assert_eq!(
+            err.unwrap_err().message,
+            "'long' must be a string or an array of strings, not number"
+        );
+        let err = complete_import_spec(L!(r#"{"options": []}"#));
+        assert_eq!(err.unwrap_err().line, None);
+    }
+}
@@ -647,6 +647,10 @@ pub fn env_init(paths: Option<&ConfigPaths>, do_uvars: bool, default_paths: bool
         EnvSetMode::new_at_early_startup(EnvMode::UNEXPORT),
         "0".into(),
     );
+    vars.set_empty(
+        L!("CMD_RUSAGE"),
+        EnvSetMode::new_at_early_startup(EnvMode::UNEXPORT),
+    );
 
     // Set up the version variable.
     let version = str2wcstring(crate::BUILD_VERSION);
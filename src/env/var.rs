@@ -1,3 +1,4 @@
+use crate::proc::ProcRusage;
 use crate::signal::Signal;
 use bitflags::bitflags;
 use fish_common::assert_sorted_by_name;
@@ -89,6 +90,10 @@ pub struct Statuses {
 
     /// Pipestatus value.
     pub pipestatus: Vec<c_int>,
+
+    /// Resource usage of each process in the last job, parallel to pipestatus.
+    /// None for processes that ran inside fish.
+    pub rusage: Vec<Option<ProcRusage>>,
 }
 
 impl Statuses {
@@ -98,6 +103,7 @@ pub fn just(status: c_int) -> Self {
             status,
             kill_signal: None,
             pipestatus: vec![status],
+            rusage: vec![None],
         }
     }
 }
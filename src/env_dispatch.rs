@@ -8,8 +8,8 @@
 use crate::prelude::*;
 use crate::reader::{
     reader_change_cursor_end_mode, reader_change_cursor_selection_mode, reader_change_history,
-    reader_current_data, reader_schedule_prompt_repaint, reader_set_autosuggestion_enabled,
-    reader_set_transient_prompt,
+    reader_current_data, reader_schedule_prompt_repaint, reader_set_async_completion,
+    reader_set_async_prompt, reader_set_autosuggestion_enabled, reader_set_transient_prompt,
 };
 use crate::screen::{
     IS_DUMB, LAYOUT_CACHE_SHARED, ONLY_GRAYSCALE, screen_set_midnight_commander_hack,
@@ -90,6 +90,11 @@ macro_rules! vars {
             L!("fish_transient_prompt"),
             vars!(handle_transient_prompt_change),
         );
+        table.add_anon(L!("fish_async_prompt"), vars!(handle_async_prompt_change));
+        table.add_anon(
+            L!("fish_async_completion"),
+            vars!(handle_async_completion_change),
+        );
         table.add_anon(
             L!("fish_use_posix_spawn"),
             vars!(handle_fish_use_posix_spawn_change),
@@ -318,6 +323,14 @@ fn handle_transient_prompt_change(vars: &EnvStack) {
     reader_set_transient_prompt(vars);
 }
 
+fn handle_async_prompt_change(vars: &EnvStack) {
+    reader_set_async_prompt(vars);
+}
+
+fn handle_async_completion_change(vars: &EnvStack) {
+    reader_set_async_completion(vars);
+}
+
 fn handle_function_path_change(_: &EnvStack) {
     function::invalidate_path();
 }
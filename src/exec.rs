@@ -3,6 +3,7 @@
 // Some of the code in this file is based on code from the Glibc manual, though the changes
 // performed have been massive.
 
+use crate::builtins::coproc::is_coprocess_fd;
 use crate::builtins::shared::{
     ErrorCode, STATUS_CMD_ERROR, STATUS_CMD_UNKNOWN, STATUS_NOT_EXECUTABLE, STATUS_READ_TOO_MUCH,
     builtin_run,
@@ -21,7 +22,7 @@
 use crate::fork_exec::PATH_BSHELL;
 use crate::fork_exec::blocked_signals_for_job;
 use crate::fork_exec::postfork::{
-    child_setup_process, execute_fork, execute_setpgid, report_setpgid_error,
+    child_apply_limits, child_setup_process, execute_fork, execute_setpgid, report_setpgid_error,
     safe_report_exec_error,
 };
 #[cfg(have_posix_spawn)]
@@ -40,8 +41,8 @@
     InternalProc, Job, JobGroupRef, ProcStatus, Process, ProcessType, hup_jobs,
     is_interactive_session, jobs_requiring_warning_on_exit, no_exec, print_exit_warning_for_jobs,
 };
-use crate::reader::{reader_run_count, safe_restore_term_mode};
-use crate::redirection::{Dup2List, dup2_list_resolve_chain};
+use crate::reader::{reader_job_title, reader_run_count, safe_restore_term_mode};
+use crate::redirection::{Dup2List, dup2_list_resolve_chain, is_named_fd};
 use crate::threads::{ThreadPool, is_forked_child};
 use crate::trace::trace_if_enabled_with_args;
 use crate::tty_handoff::TtyHandoff;
@@ -125,6 +126,12 @@ pub fn exec_job(parser: &Parser, job: &Job, block_io: IoChain) -> bool {
     // We may want to transfer tty ownership to the pgroup leader.
     let mut handoff = TtyHandoff::new(|| {});
 
+    // If so, show the job's title while it runs. This is computed before launching any process,
+    // since it may run fish script.
+    let job_title =
+        (is_interactive_session() && job.processes()[0].leads_pgrp && job.group().wants_terminal())
+            .then(|| reader_job_title(job.command(), parser));
+
     // This loop loops over every process_t in the job, starting it as appropriate. This turns out
     // to be rather complex, since a process_t can be one of many rather different things.
     //
@@ -192,6 +199,9 @@ pub fn exec_job(parser: &Parser, job: &Job, block_io: IoChain) -> bool {
         }
     }
     drop(pipe_next_read);
+    if let Some(job_title) = &job_title {
+        handoff.set_job_title(job_title);
+    }
 
     // If our pipeline was aborted before any process was successfully launched, then there is
     // nothing to reap, and we can perform an early return.
@@ -891,9 +901,12 @@ fn exec_external_command(
 
     let actual_cmd = wcs2zstring(&p.actual_cmd);
 
+    // Resource limits from the `limit` builtin are applied after fork, which posix_spawn can't do.
+    let limits = parser.libdata().process_limits;
+
     #[cfg(have_posix_spawn)]
     // Prefer to use posix_spawn, since it's faster on some systems like OS X.
-    if can_use_posix_spawn_for_job(j, &dup2s) {
+    if limits.is_empty() && can_use_posix_spawn_for_job(j, &dup2s) {
         let file = &parser.libdata().current_filename;
         let count = FORK_COUNT.fetch_add(1, Ordering::Relaxed) + 1; // spawn counts as a fork+exec
 
@@ -935,6 +948,9 @@ fn exec_external_command(
     }
 
     fork_child_for_process(j, p, &dup2s, pgroup_policy, |p| {
+        if !limits.is_empty() {
+            child_apply_limits(&limits);
+        }
         safe_launch_process(p, &actual_cmd, &argv, &envv)
     })
 }
@@ -1160,6 +1176,13 @@ fn get_performer_for_builtin(p: &Process, j: &Job, io_chain: &IoChain) -> Box<Pr
             let out_io = io_chain.io_for_fd(STDOUT_FILENO);
             let err_io = io_chain.io_for_fd(STDERR_FILENO);
 
+            // Coprocess fds and named fds are the exception to the rule below: they exist to be
+            // redirected from.
+            let stdin_from_user_fd = io_chain.io_for_fd(STDIN_FILENO).is_some_and(|inp| {
+                inp.io_mode() == IoMode::Fd
+                    && (is_coprocess_fd(inp.source_fd()) || is_named_fd(inp.source_fd()))
+            });
+
             // Figure out what fd to use for the builtin's stdin.
             let mut local_builtin_stdin = Some(BorrowedFdFile::stdin());
             if let Some(inp) = io_chain.io_for_fd(STDIN_FILENO) {
@@ -1170,7 +1193,7 @@ fn get_performer_for_builtin(p: &Process, j: &Job, io_chain: &IoChain) -> Box<Pr
                 // that we pass it on as a block IO to the code that source runs,
                 // and therefore this is not an error.
                 let fd = inp.source_fd();
-                let ignore_redirect = fd >= 3 && inp.io_mode() == IoMode::Fd;
+                let ignore_redirect = fd >= 3 && inp.io_mode() == IoMode::Fd && !stdin_from_user_fd;
                 if fd == -1 {
                     local_builtin_stdin = None;
                 } else if !ignore_redirect {
@@ -1183,7 +1206,9 @@ fn get_performer_for_builtin(p: &Process, j: &Job, io_chain: &IoChain) -> Box<Pr
             let mut streams = IoStreams::new(output_stream, errput_stream, &io_chain);
             streams.job_group = job_group;
             streams.stdin_file = local_builtin_stdin;
-            streams.stdin_is_directly_redirected = stdin_is_directly_redirected;
+            // A coprocess's output is shared with later commands, so we must not read too much.
+            streams.stdin_is_directly_redirected =
+                stdin_is_directly_redirected && !stdin_from_user_fd;
             streams.out_is_redirected = out_io.is_some();
             streams.err_is_redirected = err_io.is_some();
             streams.out_is_piped = out_io.is_some_and(|io| io.io_mode() == IoMode::Pipe);
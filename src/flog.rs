@@ -126,6 +126,8 @@ pub fn all_categories() -> Vec<&'static Category> {
 
         (profile_history, "profile-history", "History performance measurements");
 
+        (frecency, "frecency", "Reading/Writing the directory database");
+
         (synced_file_access, "synced-file-access", "Synchronized file access");
 
         (iothread, "iothread", "Background IO thread events");
@@ -187,6 +187,71 @@ pub fn child_setup_process(
     0
 }
 
+/// The most resource limits a [`ProcessLimits`] can hold.
+const MAX_PROCESS_LIMITS: usize = 24;
+
+/// Resource limits and a niceness to apply to external commands, as set by the `limit` builtin.
+/// This is a fixed-size value so it can be used after fork without allocating.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProcessLimits {
+    /// The resources to limit and their values, which become both the soft and the hard limit.
+    rlimits: [Option<(libc::c_int, libc::rlim_t)>; MAX_PROCESS_LIMITS],
+    /// The absolute niceness to set, if any.
+    pub niceness: Option<libc::c_int>,
+}
+
+impl ProcessLimits {
+    /// Return whether there is nothing to apply.
+    pub fn is_empty(&self) -> bool {
+        self.niceness.is_none() && self.rlimits.iter().all(Option::is_none)
+    }
+
+    /// Set the limit for the given resource, replacing any previous value.
+    pub fn set_rlimit(&mut self, resource: libc::c_int, value: libc::rlim_t) {
+        let slot = self
+            .rlimits
+            .iter()
+            .position(|l| l.is_some_and(|(r, _)| r == resource))
+            .or_else(|| self.rlimits.iter().position(Option::is_none))
+            .expect("Too many resource limits");
+        self.rlimits[slot] = Some((resource, value));
+    }
+
+    /// Return the resources to limit and their values.
+    pub fn rlimits(&self) -> impl Iterator<Item = (libc::c_int, libc::rlim_t)> + '_ {
+        self.rlimits.iter().flatten().copied()
+    }
+}
+
+/// Apply resource limits and niceness in the child process, before exec.
+/// The child exits if they cannot be applied, so the command never runs unconstrained.
+pub fn child_apply_limits(limits: &ProcessLimits) {
+    for (resource, value) in limits.rlimits() {
+        let rlim = libc::rlimit {
+            rlim_cur: value,
+            rlim_max: value,
+        };
+        if unsafe { libc::setrlimit(resource as _, &rlim) } < 0 {
+            flog_safe!(
+                warning,
+                "failed to set resource limit in child process, error number ",
+                errno::errno().0
+            );
+            exit_without_destructors(1);
+        }
+    }
+    if let Some(niceness) = limits.niceness {
+        if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, niceness) } < 0 {
+            flog_safe!(
+                warning,
+                "failed to set niceness in child process, error number ",
+                errno::errno().0
+            );
+            exit_without_destructors(1);
+        }
+    }
+}
+
 /// This function is a wrapper around fork. If the fork calls fails with EAGAIN, it is retried
 /// FORK_LAPS times, with a very slight delay between each lap. If fork fails even then, the process
 /// will exit with an error message.
@@ -129,7 +129,7 @@ pub fn new(
         }
 
         // Everybody gets default handlers.
-        attr.set_sigdefault(&signals_to_default)?;
+        attr.set_sigdefault(&signals_to_default())?;
 
         // Reset the sigmask.
         let mut sigmask = MaybeUninit::uninit();
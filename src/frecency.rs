@@ -0,0 +1,320 @@
+//! The directory database, which remembers the directories visited in interactive sessions and
+//! ranks them by "frecency", a combination of how often and how recently they were visited. It is
+//! used to rank the completions of `cd` and to change to a directory by part of its name, and can
+//! be queried and edited with the `frecency` builtin.
+//!
+//! The database is kept in a file in the data directory, which is shared by all fish sessions.
+//! Each line holds the rank of a directory, the time of the last visit and the URL-escaped path,
+//! separated by tabs.
+
+use crate::common::{
+    EscapeStringStyle, UnescapeStringStyle, bytes2wcstring, escape_string, unescape_string,
+    wcs2bytes,
+};
+use crate::flog::flog;
+use crate::fs::{PotentialUpdate, lock_and_load, rewrite_via_temporary_file};
+use crate::path::path_get_data;
+use crate::prelude::*;
+use crate::wutil::wstat;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The name of the database file in the data directory.
+const DATABASE_FILE_NAME: &str = "fish_dirs";
+
+/// Once the ranks of all directories add up to more than this, they are aged by scaling them down,
+/// and the directories whose rank falls below 1 are forgotten.
+const MAX_TOTAL_RANK: f64 = 10000.0;
+const AGING_FACTOR: f64 = 0.9;
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+/// A directory in the database.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DirEntry {
+    pub path: WString,
+    /// How often the directory was visited, reduced by aging.
+    pub rank: f64,
+    /// When the directory was last visited, in seconds since the epoch.
+    pub last_visit: u64,
+}
+
+impl DirEntry {
+    /// Return the frecency score of the directory at the time `now`. Recent visits count more.
+    pub fn score(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last_visit);
+        let factor = if age < HOUR {
+            4.0
+        } else if age < DAY {
+            2.0
+        } else if age < WEEK {
+            0.5
+        } else {
+            0.25
+        };
+        self.rank * factor
+    }
+}
+
+/// Return the current time in seconds since the epoch.
+pub fn frecency_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Return the path of the database file, or None if there is no data directory.
+fn database_path() -> Option<WString> {
+    let mut path = path_get_data()?;
+    path.push('/');
+    path.push_str(DATABASE_FILE_NAME);
+    Some(path)
+}
+
+/// Parse the contents of the database file, skipping invalid lines.
+fn parse(contents: &[u8]) -> Vec<DirEntry> {
+    let mut entries = vec![];
+    for line in contents.split(|&c| c == b'\n') {
+        let line = bytes2wcstring(line);
+        let fields: Vec<&wstr> = line.split('\t').collect();
+        let [rank, last_visit, path] = fields[..] else {
+            continue;
+        };
+        let (Ok(rank), Ok(last_visit), Some(path)) = (
+            rank.to_string().parse::<f64>(),
+            last_visit.to_string().parse::<u64>(),
+            unescape_string(path, UnescapeStringStyle::Url),
+        ) else {
+            continue;
+        };
+        if rank.is_finite() && rank > 0.0 && !path.is_empty() {
+            entries.push(DirEntry {
+                path,
+                rank,
+                last_visit,
+            });
+        }
+    }
+    entries
+}
+
+/// Return the contents of the database file for the given entries.
+fn serialize(entries: &[DirEntry]) -> Vec<u8> {
+    let mut contents = WString::new();
+    for entry in entries {
+        contents.push_str(&format!("{}\t{}\t", entry.rank, entry.last_visit));
+        contents.push_utfstr(&escape_string(&entry.path, EscapeStringStyle::Url));
+        contents.push('\n');
+    }
+    wcs2bytes(&contents)
+}
+
+/// Read and parse the given database file.
+fn read_entries(file: &File) -> std::io::Result<Vec<DirEntry>> {
+    let mut contents = vec![];
+    (&*file).read_to_end(&mut contents)?;
+    Ok(parse(&contents))
+}
+
+/// Record a visit of the directory `path` at the time `now`, and age the ranks if needed.
+fn add_visit(entries: &mut Vec<DirEntry>, path: &wstr, now: u64) {
+    match entries.iter_mut().find(|entry| entry.path == path) {
+        Some(entry) => {
+            entry.rank += 1.0;
+            entry.last_visit = now;
+        }
+        None => entries.push(DirEntry {
+            path: path.to_owned(),
+            rank: 1.0,
+            last_visit: now,
+        }),
+    }
+    let total: f64 = entries.iter().map(|entry| entry.rank).sum();
+    if total > MAX_TOTAL_RANK {
+        for entry in entries.iter_mut() {
+            entry.rank *= AGING_FACTOR;
+        }
+        entries.retain(|entry| entry.rank >= 1.0);
+    }
+}
+
+/// Test if the directory `path` matches the keywords. The keywords must appear in the path in
+/// order, and the last one must appear in its last component. A keyword only matches with the same
+/// case if it contains uppercase characters.
+pub fn frecency_matches(path: &wstr, keywords: &[&wstr]) -> bool {
+    let lowercase: WString = path.chars().flat_map(char::to_lowercase).collect();
+    // Lowercasing may change the length, in which case we only match the same case.
+    let lowercase = if lowercase.len() == path.len() {
+        &lowercase[..]
+    } else {
+        path
+    };
+    let last_component = path
+        .as_char_slice()
+        .iter()
+        .rposition(|&c| c == '/')
+        .map_or(0, |pos| pos + 1);
+    let mut pos = 0;
+    for (i, keyword) in keywords.iter().enumerate() {
+        let haystack = if keyword.chars().any(char::is_uppercase) {
+            path
+        } else {
+            lowercase
+        };
+        if i + 1 == keywords.len() {
+            pos = pos.max(last_component);
+        }
+        let Some(found) = haystack[pos..].find(keyword) else {
+            return false;
+        };
+        pos += found + keyword.len();
+    }
+    true
+}
+
+/// Return the directories which match the keywords and still exist, with their score, best first.
+/// The directory `exclude` is skipped.
+pub fn frecency_search(keywords: &[&wstr], exclude: Option<&wstr>) -> Vec<(DirEntry, f64)> {
+    let now = frecency_now();
+    let mut result: Vec<(DirEntry, f64)> = frecency_load()
+        .into_iter()
+        .filter(|entry| Some(&entry.path[..]) != exclude)
+        .filter(|entry| frecency_matches(&entry.path, keywords))
+        .filter(|entry| wstat(&entry.path).is_ok_and(|md| md.is_dir()))
+        .map(|entry| {
+            let score = entry.score(now);
+            (entry, score)
+        })
+        .collect();
+    result.sort_by(|(a, a_score), (b, b_score)| {
+        b_score
+            .total_cmp(a_score)
+            .then_with(|| b.last_visit.cmp(&a.last_visit))
+    });
+    result
+}
+
+/// Return all directories in the database.
+pub fn frecency_load() -> Vec<DirEntry> {
+    let Some(path) = database_path() else {
+        return vec![];
+    };
+    match lock_and_load(&path, |file, _| read_entries(file)) {
+        Ok((_, entries)) => entries,
+        Err(err) => {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                flog!(frecency, "Failed to read the directory database:", err);
+            }
+            vec![]
+        }
+    }
+}
+
+/// Modify the directories in the database with `update`, which returns a value to pass on.
+fn frecency_update<T>(update: impl Fn(&mut Vec<DirEntry>) -> T) -> std::io::Result<T> {
+    let Some(path) = database_path() else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "no data directory",
+        ));
+    };
+    let rewrite = |old_file: &File, tmp_file: &mut File| {
+        let mut entries = read_entries(old_file)?;
+        let data = update(&mut entries);
+        tmp_file.write_all(&serialize(&entries))?;
+        Ok(PotentialUpdate {
+            do_save: true,
+            data,
+        })
+    };
+    let (_, potential_update) = rewrite_via_temporary_file(&path, rewrite)?;
+    Ok(potential_update.data)
+}
+
+/// Record a visit of each of the given directories, which should be absolute paths.
+pub fn frecency_add(paths: &[WString]) -> std::io::Result<()> {
+    let now = frecency_now();
+    frecency_update(|entries| {
+        for path in paths {
+            add_visit(entries, path, now);
+        }
+    })
+}
+
+/// Forget the given directories. Returns how many were found.
+pub fn frecency_remove(paths: &[WString]) -> std::io::Result<usize> {
+    frecency_update(|entries| {
+        let count = entries.len();
+        entries.retain(|entry| !paths.contains(&entry.path));
+        count - entries.len()
+    })
+}
+
+/// Forget all directories.
+pub fn frecency_clear() -> std::io::Result<()> {
+    frecency_update(|entries| entries.clear())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DAY, DirEntry, MAX_TOTAL_RANK, add_visit, frecency_matches, parse, serialize};
+    use crate::prelude::*;
+
+    #[test]
+    fn test_frecency_matches() {
+        let path = L!("/home/me/src/Fish-Shell");
+        assert!(frecency_matches(path, &[L!("fish")]));
+        assert!(frecency_matches(path, &[L!("src"), L!("shell")]));
+        assert!(frecency_matches(path, &[L!("Fish")]));
+        assert!(frecency_matches(path, &[]));
+        assert!(!frecency_matches(path, &[L!("FISH")]));
+        assert!(!frecency_matches(path, &[L!("shell"), L!("src")]));
+        // The last keyword must match the last component.
+        assert!(!frecency_matches(path, &[L!("src")]));
+        assert!(!frecency_matches(path, &[L!("fish"), L!("me")]));
+    }
+
+    #[test]
+    fn test_frecency_database() {
+        let mut entries = vec![];
+        add_visit(&mut entries, L!("/a"), 100);
+        add_visit(&mut entries, L!("/b\tc\nd"), 200);
+        add_visit(&mut entries, L!("/a"), 300);
+        assert_eq!(
+            entries,
+            [
+                DirEntry {
+                    path: L!("/a").to_owned(),
+                    rank: 2.0,
+                    last_visit: 300,
+                },
+                DirEntry {
+                    path: L!("/b\tc\nd").to_owned(),
+                    rank: 1.0,
+                    last_visit: 200,
+                },
+            ]
+        );
+        assert_eq!(parse(&serialize(&entries)), entries);
+        assert_eq!(
+            parse(b"1\t2\n\nx\t2\t/a\n-1\t2\t/a\n0.5\t2\t/b\n"),
+            [DirEntry {
+                path: L!("/b").to_owned(),
+                rank: 0.5,
+                last_visit: 2,
+            }]
+        );
+
+        // Recent visits count more.
+        assert!(entries[1].score(200) > entries[0].score(300 + DAY));
+
+        // Rarely visited directories are forgotten when the ranks are aged.
+        entries[0].rank = MAX_TOTAL_RANK;
+        add_visit(&mut entries, L!("/c"), 400);
+        let paths: Vec<_> = entries.iter().map(|entry| entry.path.clone()).collect();
+        assert_eq!(paths, [L!("/a")]);
+    }
+}
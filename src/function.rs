@@ -410,10 +410,24 @@ pub fn copy_definition_lineno(&self) -> u32 {
     /// targets. This is to support the 'functions' builtin.
     /// Note callers must provide the function name, since the function does not know its own name.
     pub fn annotated_definition(&self, name: &wstr) -> WString {
+        self.definition(name, true)
+    }
+
+    /// Return a definition of the function like [`Self::annotated_definition`], but without its
+    /// event handlers, so that running it defines a function which does not react to events.
+    pub fn definition_without_event_handlers(&self, name: &wstr) -> WString {
+        self.definition(name, false)
+    }
+
+    fn definition(&self, name: &wstr, with_event_handlers: bool) -> WString {
         let mut out = WString::new();
         let desc = self.description.localize();
         let def = get_function_body_source(self);
-        let handlers = event::get_function_handlers(name);
+        let handlers = if with_event_handlers {
+            event::get_function_handlers(name)
+        } else {
+            vec![]
+        };
 
         out.push_str("function ");
         // Typically we prefer to specify the function name first, e.g. "function foo --description bar"
@@ -199,6 +199,7 @@ const fn make_md(name: &'static wstr, code: ReadlineCmd) -> InputFunctionMetadat
     make_md(L!("redo"), ReadlineCmd::Redo),
     make_md(L!("repaint"), ReadlineCmd::Repaint),
     make_md(L!("repaint-mode"), ReadlineCmd::RepaintMode),
+    make_md(L!("repaint-status-line"), ReadlineCmd::RepaintStatusLine),
     make_md(L!("repeat-jump"), ReadlineCmd::RepeatJump),
     make_md(L!("repeat-jump-reverse"), ReadlineCmd::ReverseRepeatJump),
     make_md(L!("scrollback-push"), ReadlineCmd::ScrollbackPush),
@@ -13,8 +13,9 @@
 use crate::prelude::*;
 use crate::reader::reader_test_and_clear_interrupted;
 use crate::tty_handoff::{
-    SCROLL_CONTENT_UP_TERMINFO_CODE, TERMINAL_OS_NAME, XTGETTCAP_QUERY_OS_NAME, XTVERSION,
-    maybe_set_kitty_keyboard_capability, maybe_set_scroll_content_up_capability,
+    BACKGROUND_COLOR_RESPONSE, CURSOR_POSITION_REPORTED, SCROLL_CONTENT_UP_TERMINFO_CODE,
+    TERMINAL_OS_NAME, XTGETTCAP_QUERY_OS_NAME, XTVERSION, maybe_set_kitty_keyboard_capability,
+    maybe_set_scroll_content_up_capability,
 };
 use crate::universal_notifier::default_notifier;
 use crate::wutil::{fish_is_pua, fish_wcstol};
@@ -24,7 +25,7 @@
 use std::collections::VecDeque;
 use std::os::fd::{BorrowedFd, RawFd};
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 // The range of key codes for inputrc-style keyboard functions.
 pub const R_END_INPUT_FUNCTIONS: usize = (ReadlineCmd::ReverseRepeatJump as usize) + 1;
@@ -120,6 +121,7 @@ pub enum ReadlineCmd {
     BeginningOfBuffer,
     EndOfBuffer,
     RepaintMode,
+    RepaintStatusLine,
     Repaint,
     ForceRepaint,
     UpLine,
@@ -345,6 +347,8 @@ pub enum ImplicitEvent {
     NewColorTheme,
     /// Window height changed.
     NewWindowHeight,
+    /// The deadline for reading input has passed.
+    ReadTimeout,
 }
 
 #[derive(Debug, Clone)]
@@ -669,6 +673,9 @@ pub struct InputData {
 
     // If set, events will be buffered until the query finishes.
     pub blocking_query: RefCell<Option<TerminalQuery>>,
+
+    // If set, give up waiting for input at this time.
+    pub read_deadline: Option<Instant>,
 }
 
 impl InputData {
@@ -683,6 +690,7 @@ pub fn new(in_fd: RawFd, blocking_query_timeout: Option<Duration>) -> Self {
             event_storage: Vec::new(),
             blocking_query_timeout,
             blocking_query: RefCell::new(None),
+            read_deadline: None,
         }
     }
 
@@ -779,6 +787,8 @@ fn readch(&mut self) -> CharEvent {
                 self.get_ioport_fd(),
                 if self.is_blocked_querying() {
                     Timeout::Duration(self.get_input_data().blocking_query_timeout.unwrap())
+                } else if let Some(deadline) = self.get_input_data().read_deadline {
+                    Timeout::Duration(deadline.saturating_duration_since(Instant::now()))
                 } else {
                     Timeout::Forever
                 },
@@ -896,6 +906,9 @@ fn readch(&mut self) -> CharEvent {
                     return key_evt;
                 }
                 InputEventTrigger::TimeoutElapsed => {
+                    if !self.is_blocked_querying() {
+                        return CharEvent::Implicit(ImplicitEvent::ReadTimeout);
+                    }
                     return CharEvent::QueryResult(QueryResultEvent::Timeout);
                 }
             }
@@ -1161,6 +1174,7 @@ fn parse_csi(&mut self, buffer: &mut Vec<u8>) -> Option<KeyEvent> {
                 };
                 flog!(reader, "Received cursor position report y:", y, "x:", x);
                 let cursor_pos = ViewportPosition { x, y };
+                CURSOR_POSITION_REPORTED.store(true);
                 self.push_query_response(QueryResponse::CursorPosition(cursor_pos));
                 return None;
             }
@@ -1388,6 +1402,7 @@ fn parse_osc(&mut self, buffer: &mut Vec<u8>) -> Option<()> {
         let buffer = buffer.strip_prefix(b"11;")?;
         let c = xterm_color::Color::parse(buffer).ok()?;
         flog!(reader, format!("Received background color {c:?}"));
+        *BACKGROUND_COLOR_RESPONSE.lock().unwrap() = Some(bytes2wcstring(buffer));
         self.push_query_response(QueryResponse::BackgroundColor(c));
         None
     }
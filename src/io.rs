@@ -920,7 +920,7 @@ pub fn is_stdin_closed(&self) -> bool {
 const NOCLOB_ERROR: &wstr = L!("The file '%s' already exists");
 
 /// Base open mode to pass to calls to open.
-const OPEN_MASK: Mode = Mode::from_bits_truncate(0o666);
+pub const OPEN_MASK: Mode = Mode::from_bits_truncate(0o666);
 
 /// Provide the fd monitor used for background fillthread operations.
 static FD_MONITOR: LazyLock<FdMonitor> = LazyLock::new(FdMonitor::new);
@@ -14,6 +14,8 @@
 pub mod autoload;
 pub mod builtins;
 pub mod complete;
+pub mod complete_cache;
+pub mod complete_spec;
 pub mod editable_line;
 pub mod env;
 pub mod env_dispatch;
@@ -26,6 +28,7 @@
 pub mod fds;
 pub mod flog;
 pub mod fork_exec;
+pub mod frecency;
 pub mod fs;
 pub mod function;
 pub mod future_feature_flags;
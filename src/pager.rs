@@ -32,6 +32,8 @@ pub struct PageRendering {
 
     pub search_field_shown: bool,
     pub search_field_line: EditableLine,
+
+    pub preview: Vec<WString>,
 }
 
 impl PageRendering {
@@ -74,6 +76,9 @@ pub enum SelectionMotion {
 /// The maximum number of columns of completion to attempt to fit onto the screen.
 const PAGER_MAX_COLS: usize = 6;
 
+/// The maximum number of rows used for the preview of the selected completion.
+const PAGER_PREVIEW_MAX_ROWS: usize = 10;
+
 /// Width of the search field.
 const PAGER_SEARCH_FIELD_WIDTH: usize = 12;
 
@@ -115,8 +120,12 @@ pub struct Pager {
     // The text of the search field.
     pub search_field_line: EditableLine,
 
-    // Extra text to display at the bottom of the pager.
+    // Extra text to display at the bottom of the pager. This is shown even if there are no
+    // completions, e.g. while they are computed in the background.
     pub extra_progress_text: WString,
+
+    // Lines previewing the selected completion, shown below the completions.
+    preview: Vec<WString>,
 }
 
 impl Pager {
@@ -175,9 +184,10 @@ fn completion_try_print(
 
         // Compute the effective term width and term height, accounting for disclosure.
         let term_width = self.available_term_width;
+        let preview_rows = self.preview_rows();
         let mut term_height = self.available_term_height
             // we always subtract 1 to make room for a comment row
-                - 1 - if self.search_field_shown { 1 } else { 0 };
+                - 1 - if self.search_field_shown { 1 } else { 0 } - preview_rows;
         if !self.fully_disclosed {
             // We disclose between half and the entirety of the terminal height,
             // but at least 4 rows.
@@ -305,6 +315,20 @@ fn completion_try_print(
             );
         }
 
+        // Add the preview of the selected completion.
+        for preview_line in &self.preview[..preview_rows] {
+            let line = rendering.screen_data.add_line();
+            let spec = HighlightSpec::with_both(HighlightRole::pager_description);
+            print_max(
+                CharOffset::None,
+                preview_line,
+                spec,
+                term_width,
+                /*has_more=*/ false,
+                line,
+            );
+        }
+
         if !self.search_field_shown {
             return true;
         }
@@ -916,6 +940,38 @@ pub fn get_selected_row_given_rows(&self, rows: usize) -> Option<usize> {
         self.selected_completion_idx.map(|idx| idx % rows)
     }
 
+    /// Set the lines previewing the selected completion. Tabs are expanded to spaces.
+    pub fn set_preview(&mut self, lines: Vec<WString>) {
+        self.preview = lines
+            .into_iter()
+            .map(|line| {
+                let mut expanded = WString::new();
+                for c in line.chars() {
+                    if c == '\t' {
+                        let width = 8 - expanded.len() % 8;
+                        expanded.extend(std::iter::repeat_n(' ', width));
+                    } else {
+                        expanded.push(c);
+                    }
+                }
+                expanded
+            })
+            .collect();
+    }
+
+    /// The number of rows used for the preview of the selected completion.
+    fn preview_rows(&self) -> usize {
+        // Leave at least half of the rows for the completions.
+        let available = self
+            .available_term_height
+            .saturating_sub(1 + usize::from(self.search_field_shown))
+            / 2;
+        self.preview
+            .len()
+            .min(PAGER_PREVIEW_MAX_ROWS)
+            .min(available)
+    }
+
     // Produces a rendering of the completions, at the given term size.
     pub fn render(&self) -> PageRendering {
         // Try to print the completions. Start by trying to print the list in PAGER_MAX_COLS columns,
@@ -925,6 +981,7 @@ pub fn render(&self) -> PageRendering {
         rendering.term_width = Some(self.available_term_width);
         rendering.term_height = Some(self.available_term_height);
         rendering.search_field_line = self.search_field_line.clone();
+        rendering.preview.clone_from(&self.preview);
         for cols in (1..=PAGER_MAX_COLS).rev() {
             // Initially empty rendering.
             rendering.screen_data.clear_lines();
@@ -968,7 +1025,10 @@ pub fn rendering_needs_update(&self, rendering: &PageRendering) -> bool {
             return true;
         }
         // Common case is no pager.
-        if self.is_empty() && rendering.screen_data.is_empty() {
+        if self.is_empty()
+            && rendering.screen_data.is_empty()
+            && self.extra_progress_text.is_empty()
+        {
             return false;
         }
 
@@ -980,7 +1040,8 @@ pub fn rendering_needs_update(&self, rendering: &PageRendering) -> bool {
            rendering.search_field_shown != self.search_field_shown ||
            *rendering.search_field_line.text() != *self.search_field_line.text() ||
            rendering.search_field_line.position() != self.search_field_line.position() ||
-           (rendering.remaining_to_disclose > 0 && self.fully_disclosed)
+           (rendering.remaining_to_disclose > 0 && self.fully_disclosed) ||
+           rendering.preview != self.preview
     }
 
     // Updates the rendering.
@@ -1006,6 +1067,7 @@ pub fn clear(&mut self) {
         self.fully_disclosed = false;
         self.search_field_shown = false;
         self.extra_progress_text.clear();
+        self.preview.clear();
         self.suggested_row_start = 0;
     }
 
@@ -1282,7 +1344,7 @@ fn process_completions_into_infos(lst: &[Completion]) -> Vec<PagerComp> {
 
 #[cfg(test)]
 mod tests {
-    use super::{Pager, SelectionMotion};
+    use super::{PageRendering, Pager, SelectionMotion};
     use crate::common::get_ellipsis_char;
     use crate::complete::{CompleteFlags, Completion};
     use crate::prelude::*;
@@ -1495,4 +1557,57 @@ macro_rules! validate {
         pager.set_completions(&c4s, true);
         validate!(&mut pager, 30, L!("{\\␊Hello")); // }
     }
+
+    #[test]
+    #[serial]
+    fn test_pager_preview() {
+        let _cleanup = test_init();
+        let line_text = |rendering: &PageRendering, i: usize| {
+            let line = rendering.screen_data.line(i);
+            WString::from(Vec::from_iter((0..line.len()).map(|i| line.char_at(i))))
+        };
+
+        let mut pager = Pager::default();
+        let completions: Vec<Completion> = (0..40)
+            .map(|i| Completion::from_completion(sprintf!("comp%02d", i)))
+            .collect();
+        pager.set_completions(&completions, true);
+        pager.set_term_size(&Termsize::new(
+            NonZeroU16::new(20).unwrap(),
+            NonZeroU16::new(12).unwrap(),
+        ));
+        let rendering = pager.render();
+        assert_eq!(rendering.screen_data.line_count(), 6);
+        assert_eq!(
+            line_text(&rendering, 5),
+            sprintf!("%cand 15 more rows", get_ellipsis_char())
+        );
+
+        // The preview is shown below the completions, and takes at most half of the rows.
+        let preview = (0..10).map(|i| sprintf!("a\tline %d", i)).collect();
+        pager.set_preview(preview);
+        let rendering = pager.render();
+        assert_eq!(rendering.screen_data.line_count(), 10);
+        assert_eq!(
+            line_text(&rendering, 4),
+            sprintf!("%cand 16 more rows", get_ellipsis_char())
+        );
+        assert_eq!(line_text(&rendering, 5), L!("a       line 0"));
+        assert_eq!(line_text(&rendering, 9), L!("a       line 4"));
+
+        pager.set_fully_disclosed();
+        let rendering = pager.render();
+        assert_eq!(rendering.screen_data.line_count(), 12);
+        assert_eq!(line_text(&rendering, 6), L!("rows 1 to 6 of 20"));
+        assert_eq!(line_text(&rendering, 7), L!("a       line 0"));
+        // Changing the preview requires a new rendering.
+        let mut rendering = PageRendering::new();
+        pager.update_rendering(&mut rendering);
+        assert!(!pager.rendering_needs_update(&rendering));
+        pager.set_preview(vec![L!("other").to_owned()]);
+        assert!(pager.rendering_needs_update(&rendering));
+
+        pager.clear();
+        assert!(pager.preview.is_empty());
+    }
 }
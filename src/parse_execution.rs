@@ -36,6 +36,7 @@
 };
 use crate::parser::{
     Block, BlockData, BlockId, BlockType, LoopStatus, Parser, ParserEnvSetMode, ProfileItem,
+    StrictMode,
 };
 use crate::parser_keywords::parser_keywords_is_subcommand;
 use crate::path::{path_as_implicit_cd, path_try_get_path};
@@ -45,12 +46,15 @@
     get_job_control_mode, job_reap, no_exec,
 };
 use crate::reader::fish_is_unwinding_for_exit;
-use crate::redirection::{RedirectionMode, RedirectionSpec, RedirectionSpecList};
+use crate::redirection::{
+    RedirectionMode, RedirectionSpec, RedirectionSpecList, close_named_fd, open_named_fd,
+};
 use crate::signal::Signal;
 use crate::timer::push_timer;
 use crate::tokenizer::{PipeOrRedir, TokenType, variable_assignment_equals_pos};
 use crate::trace::{trace_if_enabled, trace_if_enabled_with_args};
 use crate::wildcard::wildcard_match;
+use crate::wutil::fish_wcstoi;
 use fish_common::help_section;
 use fish_widestring::WExt;
 use libc::{ENOTDIR, EXIT_SUCCESS, STDERR_FILENO, STDOUT_FILENO, c_int};
@@ -94,6 +98,13 @@ pub struct ExecutionContext<'a> {
 
     /// Hack to supress non-redirectable stderr in some unit tests.
     test_only_suppress_stderr: bool,
+
+    /// The final job of the most recently run job conjunction, or None if that job was skipped.
+    /// This is used to report failed jobs.
+    last_run_job: Option<&'a ast::JobPipeline>,
+
+    /// Set if a job failed in strict mode outside of any function, so we stop executing.
+    strict_aborted: bool,
 }
 
 // Report an error, setting $status to `status`. Always returns
@@ -142,6 +153,8 @@ pub fn new(
             pipeline_node,
             block_io,
             test_only_suppress_stderr,
+            last_run_job: None,
+            strict_aborted: false,
         }
     }
 
@@ -232,7 +245,7 @@ fn check_end_execution(&self, ctx: &OperationContext<'_>) -> Option<EndExecution
         }
         let parser = ctx.parser();
         let ld = &parser.libdata();
-        if ld.exit_current_script {
+        if ld.exit_current_script || self.strict_aborted {
             return Some(EndExecutionReason::Cancelled);
         }
         if ld.returning {
@@ -1015,11 +1028,16 @@ fn run_if_statement(
             // An if condition has a job and a "tail" of andor jobs, e.g. "foo ; and bar; or baz".
             // Check the condition and the tail. We treat end_execution_reason_t::error here as failure,
             // in accordance with historic behavior.
-            let mut cond_ret =
-                self.run_job_conjunction(ctx, &if_clause.condition, associated_block);
-            if cond_ret == EndExecutionReason::Ok {
-                cond_ret = self.run_andor_job_list(ctx, &if_clause.andor_tail, associated_block);
-            }
+            let cond_ret = {
+                let _condition = ctx.parser().push_scope(|s| s.is_condition = true);
+                let mut cond_ret =
+                    self.run_job_conjunction(ctx, &if_clause.condition, associated_block, false);
+                if cond_ret == EndExecutionReason::Ok {
+                    cond_ret =
+                        self.run_andor_job_list(ctx, &if_clause.andor_tail, associated_block);
+                }
+                cond_ret
+            };
             let take_branch = cond_ret == EndExecutionReason::Ok
                 && ctx.parser().get_last_status() == EXIT_SUCCESS;
 
@@ -1220,10 +1238,15 @@ fn run_while_statement(
             first_cond_check = false;
 
             // Check the condition.
-            let mut cond_ret = self.run_job_conjunction(ctx, &header.condition, associated_block);
-            if cond_ret == EndExecutionReason::Ok {
-                cond_ret = self.run_andor_job_list(ctx, &header.andor_tail, associated_block);
-            }
+            let cond_ret = {
+                let _condition = ctx.parser().push_scope(|s| s.is_condition = true);
+                let mut cond_ret =
+                    self.run_job_conjunction(ctx, &header.condition, associated_block, false);
+                if cond_ret == EndExecutionReason::Ok {
+                    cond_ret = self.run_andor_job_list(ctx, &header.andor_tail, associated_block);
+                }
+                cond_ret
+            };
 
             // If the loop condition failed to execute, then exit the loop without modifying the exit
             // status. If the loop condition executed with a failure status, restore the status and then
@@ -1492,6 +1515,16 @@ fn determine_redirections(
             assert!(oper.is_valid(), "expected to have a valid redirection");
             let spec = RedirectionSpec::new(oper.fd, oper.mode, target);
 
+            if let Some(fd_var) = oper.fd_var.clone() {
+                // This opens or closes a named fd, without affecting this command's fds.
+                let var = &self.node_source(&redir_node.oper)[fd_var];
+                let reason = self.apply_named_fd_redirection(ctx, redir_node, var, &spec);
+                if reason != EndExecutionReason::Ok {
+                    return reason;
+                }
+                continue;
+            }
+
             // Validate this spec.
             if spec.mode == RedirectionMode::Fd
                 && !spec.is_close()
@@ -1517,6 +1550,63 @@ fn determine_redirections(
         EndExecutionReason::Ok
     }
 
+    /// Handle a redirection like `{var}>file`, which opens the file on a new fd and stores its
+    /// number in the global variable `var`, or `{var}>&-`, which closes that fd again.
+    fn apply_named_fd_redirection(
+        &self,
+        ctx: &OperationContext<'_>,
+        redir_node: &ast::Redirection,
+        var: &wstr,
+        spec: &RedirectionSpec,
+    ) -> EndExecutionReason {
+        if no_exec() {
+            return EndExecutionReason::Ok;
+        }
+        let parser = ctx.parser();
+        let mode = ParserEnvSetMode::user(EnvMode::GLOBAL);
+        if spec.is_close() {
+            let fd = parser
+                .vars()
+                .get(var)
+                .and_then(|val| fish_wcstoi(&val.as_string()).ok());
+            if !fd.is_some_and(close_named_fd) {
+                return report_error!(
+                    self,
+                    ctx,
+                    STATUS_INVALID_ARGS,
+                    redir_node,
+                    "Variable '%s' does not hold a file descriptor opened by a redirection",
+                    var
+                );
+            }
+            parser.remove_var(var, mode);
+        } else if spec.mode == RedirectionMode::Fd {
+            return report_error!(
+                self,
+                ctx,
+                STATUS_INVALID_ARGS,
+                redir_node,
+                "A named file descriptor can only be opened on a file or closed with '&-'"
+            );
+        } else {
+            match open_named_fd(spec, &parser.vars().get_pwd_slash()) {
+                Ok(fd) => parser.set_var_and_fire(var, mode, vec![fd.to_wstring()]),
+                Err(err) => {
+                    return report_error!(
+                        self,
+                        ctx,
+                        STATUS_CMD_ERROR,
+                        redir_node,
+                        "Could not open '%s': %s",
+                        &spec.target,
+                        err.desc()
+                    );
+                }
+            };
+        }
+        EndExecutionReason::Ok
+    }
+
     fn run_1_job(
         &mut self,
         ctx: &OperationContext<'_>,
@@ -1560,7 +1650,15 @@ fn run_1_job(
                     ERROR_TIME_BACKGROUND
                 );
             }
-            wants_timing.then(push_timer)
+            wants_timing.then(|| {
+                // Report each process too, if requested.
+                let per_process = ctx
+                    .parser()
+                    .vars()
+                    .get(L!("fish_time_processes"))
+                    .is_some_and(|var| !var.is_empty());
+                push_timer(per_process)
+            })
         };
 
         // When we encounter a block construct (e.g. while loop) in the general case, we create a "block
@@ -1693,6 +1791,7 @@ fn test_and_run_1_job_conjunction(
         ctx: &OperationContext<'_>,
         jc: &'a ast::JobConjunction,
         associated_block: Option<BlockId>,
+        followed_by_andor: bool,
     ) -> EndExecutionReason {
         // Test this job conjunction if it has an 'and' or 'or' decorator.
         // If it passes, then run it.
@@ -1717,23 +1816,38 @@ fn test_and_run_1_job_conjunction(
         }
         // Skipping is treated as success.
         if skip {
+            self.last_run_job = None;
             EndExecutionReason::Ok
         } else {
-            self.run_job_conjunction(ctx, jc, associated_block)
+            self.run_job_conjunction(ctx, jc, associated_block, followed_by_andor)
         }
     }
 
+    /// Run a job conjunction. If `followed_by_andor` is set, the next job is decorated with `and`
+    /// or `or`, so the status of the conjunction is a condition for that job.
     fn run_job_conjunction(
         &mut self,
         ctx: &OperationContext<'_>,
         job_expr: &'a ast::JobConjunction,
         associated_block: Option<BlockId>,
+        followed_by_andor: bool,
     ) -> EndExecutionReason {
         if let Some(reason) = self.check_end_execution(ctx) {
             return reason;
         }
-        let mut result = self.run_1_job(ctx, &job_expr.job, associated_block);
-        for jc in &job_expr.continuations {
+        // Jobs whose status decides whether the next one runs, and negated jobs, are conditions.
+        let is_condition = |job: &ast::JobPipeline, is_last: bool| {
+            !is_last || followed_by_andor || matches!(job.statement, Statement::Not(_))
+        };
+        let is_last = job_expr.continuations.is_empty();
+        let mut result = self.run_1_job_maybe_condition(
+            ctx,
+            &job_expr.job,
+            associated_block,
+            is_condition(&job_expr.job, is_last),
+        );
+        let mut last_run_job = Some(&job_expr.job);
+        for (i, jc) in job_expr.continuations.iter().enumerate() {
             if result != EndExecutionReason::Ok {
                 return result;
             }
@@ -1753,13 +1867,99 @@ fn run_job_conjunction(
                 }
                 _ => unreachable!(),
             };
-            if !skip {
-                result = self.run_1_job(ctx, &jc.job, associated_block);
+            if skip {
+                last_run_job = None;
+            } else {
+                let is_last = i + 1 == job_expr.continuations.len();
+                result = self.run_1_job_maybe_condition(
+                    ctx,
+                    &jc.job,
+                    associated_block,
+                    is_condition(&jc.job, is_last),
+                );
+                last_run_job = Some(&jc.job);
             }
         }
+        self.last_run_job = last_run_job;
         result
     }
 
+    /// Run a job. If `is_condition` is set, the job's failure is expected, so strict mode does not
+    /// apply to it or the functions it calls.
+    fn run_1_job_maybe_condition(
+        &mut self,
+        ctx: &OperationContext<'_>,
+        job: &'a ast::JobPipeline,
+        associated_block: Option<BlockId>,
+        is_condition: bool,
+    ) -> EndExecutionReason {
+        if is_condition && !ctx.parser().scope().is_condition {
+            let _condition = ctx.parser().push_scope(|s| s.is_condition = true);
+            self.run_1_job(ctx, job, associated_block)
+        } else {
+            self.run_1_job(ctx, job, associated_block)
+        }
+    }
+
+    /// Fire the fish_job_failed event if the given job, which was just run, failed.
+    /// Like the ERR trap of other shells, this does not consider negated jobs, blocks (whose
+    /// contents are considered instead), or jobs run from functions, event handlers and command
+    /// substitutions.
+    fn report_job_failure(&mut self, ctx: &OperationContext<'_>, job: &ast::JobPipeline) {
+        let parser = ctx.parser();
+        if parser.get_last_status() == 0 || !matches!(job.statement, Statement::Decorated(_)) {
+            return;
+        }
+        if parser.scope().is_event || parser.is_function() || parser.is_command_substitution() {
+            return;
+        }
+        event::fire_generic(parser, L!("fish_job_failed").to_owned(), vec![]);
+    }
+
+    /// In strict mode, abort the current function or script if the given job, which was just run,
+    /// failed. Like `set -e` in other shells, this does not consider conditions, negated jobs,
+    /// blocks (whose contents are considered instead), event handlers or command substitutions.
+    fn check_strict_mode(&mut self, ctx: &OperationContext<'_>, job: &ast::JobPipeline) {
+        let parser = ctx.parser();
+        if !matches!(job.statement, Statement::Decorated(_)) {
+            return;
+        }
+        let statuses = parser.get_last_statuses();
+        let status = match parser.strict_mode() {
+            StrictMode::Off => return,
+            StrictMode::On => statuses.status,
+            // Like pipefail in other shells, use the last failing status in the pipeline.
+            StrictMode::Pipefail => statuses
+                .pipestatus
+                .iter()
+                .rev()
+                .copied()
+                .find(|&status| status != 0)
+                .unwrap_or(statuses.status),
+        };
+        if status == 0 {
+            return;
+        }
+        let scope = parser.scope();
+        if scope.is_condition || scope.is_event || parser.is_command_substitution() {
+            return;
+        }
+        report_error!(
+            self,
+            ctx,
+            status,
+            job,
+            "Job failed with status %d in strict mode",
+            status
+        );
+        // Return from the current function, or stop evaluating this script.
+        if parser.is_function() {
+            parser.libdata_mut().returning = true;
+        } else {
+            self.strict_aborted = true;
+        }
+    }
+
     fn run_job_list(
         &mut self,
         ctx: &OperationContext<'_>,
@@ -1767,8 +1967,18 @@ fn run_job_list(
         associated_block: Option<BlockId>,
     ) -> EndExecutionReason {
         let mut result = EndExecutionReason::Ok;
-        for jc in job_list_node {
-            result = self.test_and_run_1_job_conjunction(ctx, jc, associated_block);
+        let mut jobs = job_list_node.iter().peekable();
+        while let Some(jc) = jobs.next() {
+            // A job followed by `and` or `or` is treated like one that is part of a `&&` or `||`
+            // chain: its failure is not reported.
+            let continued = jobs.peek().is_some_and(|next| next.decorator.is_some());
+            result = self.test_and_run_1_job_conjunction(ctx, jc, associated_block, continued);
+            if let Some(job) = self.last_run_job.take() {
+                if result == EndExecutionReason::Ok && !continued {
+                    self.report_job_failure(ctx, job);
+                    self.check_strict_mode(ctx, job);
+                }
+            }
         }
         // Returns the result of the last job executed or skipped.
         result
@@ -1782,7 +1992,7 @@ fn run_andor_job_list(
     ) -> EndExecutionReason {
         let mut result = EndExecutionReason::Ok;
         for aoj in job_list_node {
-            result = self.test_and_run_1_job_conjunction(ctx, &aoj.job, associated_block);
+            result = self.test_and_run_1_job_conjunction(ctx, &aoj.job, associated_block, false);
         }
         // Returns the result of the last job executed or skipped.
         result
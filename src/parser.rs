@@ -16,6 +16,7 @@
     ExpandFlags, ExpandResultCode, expand_string, replace_home_directory_with_tilde,
 };
 use crate::fds::{BEST_O_SEARCH, open_dir};
+use crate::fork_exec::postfork::ProcessLimits;
 use crate::global_safety::RelaxedAtomicBool;
 use crate::input_common::TerminalQuery;
 use crate::io::IoChain;
@@ -62,6 +63,43 @@ pub enum BlockData {
     },
 }
 
+/// How failing jobs are handled, as set with `status strict`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StrictMode {
+    /// Failing jobs are ignored.
+    Off,
+    /// A failing job aborts the current function or script.
+    On,
+    /// Like `On`, but a job also fails if any process in it fails.
+    Pipefail,
+}
+
+impl StrictMode {
+    pub fn to_wstr(self) -> &'static wstr {
+        match self {
+            StrictMode::Off => L!("off"),
+            StrictMode::On => L!("on"),
+            StrictMode::Pipefail => L!("pipefail"),
+        }
+    }
+}
+
+impl TryFrom<&wstr> for StrictMode {
+    type Error = ();
+
+    fn try_from(value: &wstr) -> Result<Self, Self::Error> {
+        if value == "off" {
+            Ok(StrictMode::Off)
+        } else if value == "on" {
+            Ok(StrictMode::On)
+        } else if value == "pipefail" {
+            Ok(StrictMode::Pipefail)
+        } else {
+            Err(())
+        }
+    }
+}
+
 /// block_t represents a block of commands.
 #[derive(Default)]
 pub struct Block {
@@ -82,6 +120,10 @@ pub struct Block {
 
     /// The node containing this block, for lazy line number computation.
     src_node: Option<NodeRef<ast::JobPipeline>>,
+
+    /// The strict mode set in this block via `status strict`, if any.
+    /// It also applies to nested blocks and the functions they call.
+    pub strict_mode: Option<StrictMode>,
 }
 
 impl Block {
@@ -232,6 +274,10 @@ pub struct ScopedData {
     /// Whether we are running an event handler.
     pub is_event: bool,
 
+    /// Whether we are running a condition, like that of an `if`, whose failure is expected.
+    /// Strict mode does not apply here.
+    pub is_condition: bool,
+
     /// Whether we are currently interactive.
     pub is_interactive: bool,
 
@@ -259,6 +305,7 @@ fn default() -> Self {
             eval_level: -1,
             is_subshell: false,
             is_event: false,
+            is_condition: false,
             readonly_commandline: false,
             is_interactive: false,
             suppress_fish_trace: false,
@@ -280,6 +327,10 @@ pub struct LibraryData {
     /// the command line.
     pub transient_commandline: Option<WString>,
 
+    /// Resource limits to apply to external commands, set by the `limit` builtin for the duration
+    /// of its command. This is not part of [`ScopedData`], which is copied for every block.
+    pub process_limits: ProcessLimits,
+
     /// A file descriptor holding the current working directory, for use in openat().
     /// This is never null and never invalid.
     pub cwd_fd: Option<Arc<OwnedFd>>,
@@ -492,6 +543,13 @@ pub fn job_add(&self, job: JobRef) {
         self.jobs_mut().insert(0, job);
     }
 
+    /// Return the strict mode in effect, as set via `status strict` in this or an enclosing block.
+    pub fn strict_mode(&self) -> StrictMode {
+        self.blocks_iter_rev()
+            .find_map(|b| b.strict_mode)
+            .unwrap_or(StrictMode::Off)
+    }
+
     /// Return whether we are currently evaluating a function.
     pub fn is_function(&self) -> bool {
         self.blocks_iter_rev()
@@ -17,6 +17,7 @@
 use crate::reader::{fish_is_unwinding_for_exit, reader_schedule_prompt_repaint};
 use crate::redirection::RedirectionSpecList;
 use crate::signal::{Signal, signal_set_handlers_once};
+use crate::timer;
 use crate::topic_monitor::{GenerationsList, Topic, topic_monitor_principal};
 use crate::wait_handle::{InternalJobId, WaitHandle, WaitHandleRef, WaitHandleStore};
 use crate::wutil::{wbasename, wperror};
@@ -42,6 +43,7 @@
 use std::rc::Rc;
 use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::{Arc, LazyLock, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
 /// Types of processes.
 #[derive(Default)]
@@ -388,6 +390,9 @@ pub struct Process {
 
     pub last_times: Cell<ProcTimes>,
 
+    /// Resource usage, for external processes which have exited.
+    pub rusage: Cell<Option<ProcRusage>>,
+
     argv: Vec<WString>,
     proc_redirection_specs: RedirectionSpecList,
 
@@ -396,6 +401,42 @@ pub struct Process {
     wait_handle: RefCell<Option<WaitHandleRef>>,
 }
 
+/// The resources used by an external process, as reported by wait4() when it was reaped.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcRusage {
+    /// CPU time spent in the process itself.
+    pub user: Duration,
+    /// CPU time spent in the kernel on behalf of the process.
+    pub sys: Duration,
+    /// Maximum resident set size, in kibibytes.
+    pub max_rss_kb: u64,
+}
+
+impl ProcRusage {
+    pub fn from_rusage(rusage: &libc::rusage) -> Self {
+        use crate::nix::timeval_to_duration as from;
+        let max_rss = u64::try_from(rusage.ru_maxrss).unwrap_or(0);
+        // macOS reports the maximum resident set size in bytes, everyone else in kibibytes.
+        let max_rss_kb = if cfg!(apple) { max_rss / 1024 } else { max_rss };
+        ProcRusage {
+            user: from(&rusage.ru_utime),
+            sys: from(&rusage.ru_stime),
+            max_rss_kb,
+        }
+    }
+
+    /// Return the value for one element of $CMD_RUSAGE: CPU times in milliseconds and the maximum
+    /// resident set size in kibibytes, separated by spaces.
+    pub fn to_var_value(&self) -> WString {
+        sprintf!(
+            "%u %u %u",
+            self.user.as_millis() as u64,
+            self.sys.as_millis() as u64,
+            self.max_rss_kb
+        )
+    }
+}
+
 #[derive(Default, Clone, Copy)]
 pub struct ProcTimes {
     /// Last time of cpu time check, in seconds (per timef).
@@ -537,11 +578,12 @@ pub fn is_completed(&self) -> bool {
     pub fn make_wait_handle(&self, jid: InternalJobId) -> Option<WaitHandleRef> {
         let pid = self.pid()?;
         if self.wait_handle.borrow().is_none() {
-            self.wait_handle.replace(Some(WaitHandle::new(
-                pid,
-                jid,
-                wbasename(&self.actual_cmd.clone()).to_owned(),
-            )));
+            let wh = WaitHandle::new(pid, jid, wbasename(&self.actual_cmd.clone()).to_owned());
+            // The process may have already been reaped.
+            if self.is_completed() {
+                wh.set_status_and_complete(self.status());
+            }
+            self.wait_handle.replace(Some(wh));
         }
         self.get_wait_handle()
     }
@@ -607,6 +649,9 @@ pub struct Job {
 
     /// Flags associated with the job.
     pub job_flags: RefCell<JobFlags>,
+
+    /// When the job was created, in seconds since the UNIX epoch.
+    pub start_time: Timepoint,
 }
 
 impl Job {
@@ -616,6 +661,7 @@ pub fn new(properties: JobProperties, command_str: WString) -> Self {
             properties,
             command_str,
             internal_job_id: NEXT_INTERNAL_JOB_ID.fetch_add(1, Ordering::Relaxed),
+            start_time: timef(),
             ..Default::default()
         }
     }
@@ -813,7 +859,9 @@ pub fn continue_job(&self, parser: &Parser, block_io: Option<&IoChain>) {
 
         // Wait for the status of our own job to change.
         while !fish_is_unwinding_for_exit() && !self.is_stopped() && !self.is_completed() {
-            process_mark_finished_children(parser, /*block_ok=*/ true, block_io);
+            process_mark_finished_children(
+                parser, /*block_ok=*/ true, /*deadline=*/ None, block_io,
+            );
         }
         if self.is_completed() {
             // Set $status only if we are in the foreground and the last process in the job has
@@ -875,6 +923,7 @@ pub fn get_statuses(&self) -> Option<Statuses> {
         let mut has_status = false;
         let mut laststatus = 0;
         st.pipestatus.resize(self.processes().len(), 0);
+        st.rusage = self.processes().iter().map(|p| p.rusage.get()).collect();
         for (i, p) in self.processes().iter().enumerate() {
             let status = p.status();
             if status.is_empty() {
@@ -969,7 +1018,9 @@ pub fn job_reap(parser: &Parser, interactive: bool, block_io: Option<&IoChain>)
         return false;
     }
 
-    process_mark_finished_children(parser, /*block_ok=*/ false, block_io);
+    process_mark_finished_children(
+        parser, /*block_ok=*/ false, /*deadline=*/ None, block_io,
+    );
     process_clean_after_marking(parser, interactive)
 }
 
@@ -1071,6 +1122,12 @@ fn handle_child_status(job: &Job, proc: &Process, status: ProcStatus) {
         proc.stopped.store(false);
     } else {
         proc.completed.store(true);
+        // Let 'wait' on this process's pid return before the rest of its job completes.
+        if let Some(wh) = proc.get_wait_handle() {
+            if !wh.is_completed() {
+                wh.set_status_and_complete(status);
+            }
+        }
     }
 
     // If the child was killed by SIGINT or SIGQUIT, then cancel the entire group if interactive. If
@@ -1101,7 +1158,21 @@ fn handle_child_status(job: &Job, proc: &Process, status: ProcStatus) {
 
 /// Wait for any process finishing, or receipt of a signal.
 pub fn proc_wait_any(parser: &Parser) {
-    process_mark_finished_children(parser, /*block_ok=*/ true, /*block_io=*/ None);
+    process_mark_finished_children(
+        parser, /*block_ok=*/ true, /*deadline=*/ None, /*block_io=*/ None,
+    );
+    let is_interactive = parser.scope().is_interactive;
+    process_clean_after_marking(parser, is_interactive);
+}
+
+/// Like proc_wait_any, but give up once `deadline` has passed.
+pub fn proc_wait_any_until(parser: &Parser, deadline: Instant) {
+    process_mark_finished_children(
+        parser,
+        /*block_ok=*/ true,
+        Some(deadline),
+        /*block_io=*/ None,
+    );
     let is_interactive = parser.scope().is_interactive;
     process_clean_after_marking(parser, is_interactive);
 }
@@ -1137,7 +1208,7 @@ pub fn hup_jobs(jobs: &JobList) {
     if !kill_list.is_empty() {
         // Sleep once for all child processes to give them a chance to handle SIGHUP if they can
         // handle SIGHUP+SIGCONT without running into SIGTTOU.
-        std::thread::sleep(std::time::Duration::from_millis(50));
+        std::thread::sleep(Duration::from_millis(50));
         for j in kill_list.drain(..) {
             j.signal(NixSignal::SIGKILL);
         }
@@ -1178,7 +1249,13 @@ fn reap_disowned_pids() {
 /// See if any reapable processes have exited, and mark them accordingly.
 /// \param block_ok if no reapable processes have exited, block until one is (or until we receive a
 /// signal).
-fn process_mark_finished_children(parser: &Parser, block_ok: bool, block_io: Option<&IoChain>) {
+/// \param deadline if blocking, give up once this has passed.
+fn process_mark_finished_children(
+    parser: &Parser,
+    block_ok: bool,
+    deadline: Option<Instant>,
+    block_io: Option<&IoChain>,
+) {
     // Get the exit and signal generations of all reapable processes.
     // The exit generation tells us if we have an exit; the signal generation allows for detecting
     // SIGHUP and SIGINT.
@@ -1204,7 +1281,11 @@ fn process_mark_finished_children(parser: &Parser, block_ok: bool, block_io: Opt
     }
 
     // Now check for changes, optionally waiting.
-    if !topic_monitor_principal().check(&reapgens, block_ok) {
+    let changed = match deadline {
+        Some(deadline) if block_ok => topic_monitor_principal().check_until(&reapgens, deadline),
+        _ => topic_monitor_principal().check(&reapgens, block_ok),
+    };
+    if !changed {
         // Nothing changed.
         return;
     }
@@ -1229,13 +1310,16 @@ fn process_mark_finished_children(parser: &Parser, block_ok: bool, block_io: Opt
             }
             proc.gens.sigchld.set(reapgens.sigchld.get());
 
-            // Ok, we are reapable. Run waitpid()!
+            // Ok, we are reapable. Run wait4(), which is waitpid() that also reports the
+            // resource usage.
             let mut statusv: libc::c_int = -1;
+            let mut rusage = std::mem::MaybeUninit::<libc::rusage>::zeroed();
             let pid = unsafe {
-                libc::waitpid(
+                libc::wait4(
                     proc.pid().unwrap().as_pid_t(),
                     &mut statusv,
                     WNOHANG | WUNTRACED | WCONTINUED,
+                    rusage.as_mut_ptr(),
                 )
             };
             if pid == 0 {
@@ -1254,6 +1338,9 @@ fn process_mark_finished_children(parser: &Parser, block_ok: bool, block_io: Opt
                 j.mut_flags().notified_of_stop = false;
             }
             if status.normal_exited() || status.signal_exited() {
+                let rusage = ProcRusage::from_rusage(unsafe { rusage.assume_init_ref() });
+                proc.rusage.set(Some(rusage));
+                timer::record_process_usage(pid, proc.argv0().unwrap(), &rusage);
                 flogf!(
                     proc_reap_external,
                     "Reaped external process '%s' (pid %d, status %d)",
@@ -1524,9 +1611,12 @@ fn save_wait_handle_for_completed_job(job: &Job, store: &mut WaitHandleStore) {
     }
 
     // Mark all wait handles as complete (but don't create just for this).
+    // Handles of processes which were reaped individually are already complete.
     for proc in job.processes().iter() {
         if let Some(wh) = proc.get_wait_handle() {
-            wh.set_status_and_complete(proc.status().status_value());
+            if !wh.is_completed() {
+                wh.set_status_and_complete(proc.status());
+            }
         }
     }
 }
@@ -0,0 +1,86 @@
+//! Support for computing completions in the background.
+//!
+//! Completions may run fish script, like the command substitutions in `complete --arguments`,
+//! which can only run on the main thread. To keep a slow completion from blocking the reader, the
+//! asynchronous completion instead computes them in a separate fish process. In addition to the
+//! variables and functions of the interactive session, it gets the completions that were not
+//! loaded from completion files. Cached completion arguments are shared through the cache file.
+
+use super::fish_process::{FishProcessHandle, get_fish_process_runner, session_script};
+use crate::common::{bytes2wcstring, escape};
+use crate::complete::{Completion, complete_print_unautoloaded};
+use crate::env::Environment;
+use crate::prelude::*;
+use std::sync::Arc;
+
+/// The result of an asynchronous completion request.
+pub(super) struct AsyncCompletionResult {
+    /// The generation of the request which produced this result.
+    pub generation: u64,
+    /// The completions, or None if the completion process could not be run.
+    pub completions: Option<Vec<Completion>>,
+}
+
+/// Return a script which sets up the state of the interactive session and then prints the
+/// completions for the given command line.
+pub(super) fn async_completion_script(vars: &dyn Environment, cmdline: &wstr) -> WString {
+    let mut script = session_script(vars);
+    script.push_utfstr(&complete_print_unautoloaded());
+    sprintf!(=> &mut script, "complete --json --do-complete=%s\n", escape(cmdline));
+    script
+}
+
+/// Return a function which runs the completion script in a separate fish process. The function
+/// may be invoked on a background thread.
+pub(super) fn get_async_completion_performer(
+    vars: &dyn Environment,
+    script: WString,
+    generation: u64,
+    handle: Arc<FishProcessHandle>,
+) -> impl FnOnce() -> AsyncCompletionResult + use<> {
+    let runner = get_fish_process_runner(vars, script, handle);
+    move || {
+        let completions = runner().map(|output| {
+            output
+                .split(|&c| c == b'\n')
+                .filter_map(|line| Completion::from_json(&bytes2wcstring(line)))
+                .collect()
+        });
+        AsyncCompletionResult {
+            generation,
+            completions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::async_completion_script;
+    use crate::complete::{CompleteFlags, CompleteOptionType, CompletionMode, complete_add};
+    use crate::prelude::*;
+    use crate::tests::prelude::*;
+
+    #[test]
+    #[serial]
+    fn test_async_completion_script() {
+        let _cleanup = test_init();
+        let parser = TestParser::new();
+        complete_add(
+            L!("async_complete_cmd").to_owned(),
+            false,
+            WString::new(),
+            CompleteOptionType::ArgsOnly,
+            CompletionMode::default(),
+            vec![],
+            vec![],
+            L!("(slow-lookup)").to_owned(),
+            WString::new(),
+            CompleteFlags::empty(),
+            None,
+        );
+        let script = async_completion_script(parser.vars(), L!("async_complete_cmd 'a b"));
+        let script = script.to_string();
+        assert!(script.contains("complete async_complete_cmd"));
+        assert!(script.ends_with("complete --json --do-complete=\"async_complete_cmd 'a b\"\n"));
+    }
+}
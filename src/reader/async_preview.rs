@@ -0,0 +1,75 @@
+//! Support for computing the preview of the selected completion in the background.
+//!
+//! The preview is computed by fish_completion_preview, which is fish script. So that it does not
+//! block typing, it runs in a separate fish process, like the asynchronous prompt. The process
+//! can't see the command line, so the part before the cursor is passed as an argument.
+
+use super::fish_process::{
+    FishProcessHandle, get_fish_process_runner, session_script, split_lines,
+};
+use crate::env::Environment;
+use crate::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The result of a completion preview request.
+pub(super) struct CompletionPreviewResult {
+    /// The generation of the request which produced this result.
+    pub generation: u64,
+    /// The lines of the preview, or None if the preview process could not be run.
+    pub preview: Option<Vec<WString>>,
+}
+
+/// Return a script which sets up the state of the interactive session and then runs the given
+/// preview command.
+pub(super) fn completion_preview_script(vars: &dyn Environment, preview_cmd: &wstr) -> WString {
+    let mut script = session_script(vars);
+    script.push_utfstr(preview_cmd);
+    script.push('\n');
+    script
+}
+
+/// Return a function which waits for `delay` and then runs the preview script in a separate fish
+/// process, unless the request was canceled in the meantime. The function may be invoked on a
+/// background thread.
+pub(super) fn get_completion_preview_performer(
+    vars: &dyn Environment,
+    script: WString,
+    generation: u64,
+    delay: Duration,
+    handle: Arc<FishProcessHandle>,
+) -> impl FnOnce() -> CompletionPreviewResult + use<> {
+    let runner = get_fish_process_runner(vars, script, handle);
+    move || {
+        std::thread::sleep(delay);
+        CompletionPreviewResult {
+            generation,
+            preview: runner().map(|output| split_lines(&output)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::completion_preview_script;
+    use crate::env::{EnvMode, EnvSetMode};
+    use crate::prelude::*;
+    use crate::tests::prelude::*;
+
+    #[test]
+    #[serial]
+    fn test_completion_preview_script() {
+        let _cleanup = test_init();
+        let parser = TestParser::new();
+        let vars = parser.vars();
+        vars.set_one(
+            L!("preview_var"),
+            EnvSetMode::new(EnvMode::GLOBAL, false),
+            L!("x").to_owned(),
+        );
+        let script = completion_preview_script(vars, L!("fish_completion_preview a b 'cmd a'"));
+        let script = script.to_string();
+        assert!(script.contains("set -g preview_var x\n"));
+        assert!(script.ends_with("\nfish_completion_preview a b 'cmd a'\n"));
+    }
+}
@@ -0,0 +1,132 @@
+//! Support for computing the prompt in the background.
+//!
+//! Prompt functions are fish script, which can only run on the main thread. To avoid blocking the
+//! reader, the asynchronous prompt instead runs them in a separate fish process.
+
+use super::fish_process::{
+    FishProcessHandle, get_fish_process_runner, session_script, split_lines,
+};
+use crate::env::{Environment, Statuses};
+use crate::prelude::*;
+use fish_wcstringutil::join_strings;
+use std::sync::Arc;
+
+/// The result of an asynchronous prompt computation.
+pub(super) struct AsyncPromptResult {
+    /// The generation of the request which produced this result.
+    pub generation: u64,
+    /// The left and right prompt, or None if the prompt process could not be run.
+    pub prompts: Option<(WString, WString)>,
+}
+
+/// The name of the function which is used to restore $status and $pipestatus in the prompt process.
+const STATUS_FUNCTION_NAME: &wstr = L!("__fish_async_prompt_status");
+
+/// Return a script which sets up the state of the interactive session and then runs the given prompt
+/// functions, separating their outputs by a NUL byte.
+pub(super) fn async_prompt_script(
+    vars: &dyn Environment,
+    statuses: &Statuses,
+    left_prompt_cmd: &wstr,
+    right_prompt_cmd: Option<&wstr>,
+) -> WString {
+    let mut script = session_script(vars);
+
+    // Restore $pipestatus and $status by running a pipeline of functions which return them.
+    sprintf!(=> &mut script, "function %s; return $argv[1]; end\n", STATUS_FUNCTION_NAME);
+    let mut restore_status = WString::new();
+    if statuses.pipestatus.is_empty() {
+        sprintf!(=> &mut restore_status, "%s %d\n", STATUS_FUNCTION_NAME, statuses.status);
+    } else {
+        for (i, status) in statuses.pipestatus.iter().enumerate() {
+            if i > 0 {
+                restore_status.push_str(" | ");
+            }
+            sprintf!(=> &mut restore_status, "%s %d", STATUS_FUNCTION_NAME, *status);
+        }
+        restore_status.push('\n');
+    }
+
+    script.push_utfstr(&restore_status);
+    script.push_utfstr(left_prompt_cmd);
+    script.push_str("\nprintf '\\\\0'\n");
+    if let Some(right_prompt_cmd) = right_prompt_cmd {
+        script.push_utfstr(&restore_status);
+        script.push_utfstr(right_prompt_cmd);
+        script.push('\n');
+    }
+    script
+}
+
+/// Return a function which runs the prompt script in a separate fish process, with the given
+/// working directory and exported variables. The function may be invoked on a background thread.
+pub(super) fn get_async_prompt_performer(
+    vars: &dyn Environment,
+    script: WString,
+    generation: u64,
+    handle: Arc<FishProcessHandle>,
+) -> impl FnOnce() -> AsyncPromptResult + use<> {
+    let runner = get_fish_process_runner(vars, script, handle);
+    move || {
+        let prompts = runner().map(|output| {
+            let (left, right) = match output.iter().position(|&c| c == b'\0') {
+                Some(pos) => (&output[..pos], &output[pos + 1..]),
+                None => (&output[..], &[][..]),
+            };
+            let left = split_lines(left);
+            let left = join_strings(&left, '\n');
+            let right = WString::from_iter(split_lines(right));
+            (left, right)
+        });
+        AsyncPromptResult {
+            generation,
+            prompts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::async_prompt_script;
+    use crate::env::{EnvMode, EnvSetMode, Statuses};
+    use crate::prelude::*;
+    use crate::tests::prelude::*;
+
+    #[test]
+    #[serial]
+    fn test_async_prompt_script() {
+        let _cleanup = test_init();
+        let parser = TestParser::new();
+        let vars = parser.vars();
+        vars.set_one(
+            L!("prompt_var"),
+            EnvSetMode::new(EnvMode::GLOBAL, false),
+            L!("a b").to_owned(),
+        );
+        vars.set_one(
+            L!("prompt_exported_var"),
+            EnvSetMode::new(EnvMode::GLOBAL | EnvMode::EXPORT, false),
+            L!("c").to_owned(),
+        );
+        let statuses = Statuses {
+            status: 0,
+            kill_signal: None,
+            pipestatus: vec![1, 0],
+            rusage: vec![None, None],
+        };
+        let script = async_prompt_script(
+            vars,
+            &statuses,
+            L!("fish_prompt"),
+            Some(L!("fish_right_prompt")),
+        );
+        let script = script.to_string();
+        assert!(script.contains("set -g prompt_var 'a b'\n"));
+        assert!(!script.contains("prompt_exported_var"));
+        assert!(!script.contains("set -g status "));
+        let restore = "__fish_async_prompt_status 1 | __fish_async_prompt_status 0\n";
+        let expected =
+            format!("{restore}fish_prompt\nprintf '\\\\0'\n{restore}fish_right_prompt\n");
+        assert!(script.ends_with(&expected));
+    }
+}
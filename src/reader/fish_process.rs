@@ -0,0 +1,211 @@
+//! Support for running fish script in a separate fish process.
+//!
+//! Fish script can only run on the main thread. To run it without blocking the reader, it instead
+//! runs in a separate fish process, which is started from one of the reader's iothreads. The
+//! process receives a snapshot of the variables and functions of the interactive session as a
+//! script on its stdin.
+
+use crate::common::{bytes2wcstring, escape, wcs2bytes, wcs2osstring};
+use crate::env::config_paths::{FishPath, get_fish_path};
+use crate::env::{EnvMode, Environment, is_read_only};
+use crate::function;
+use crate::prelude::*;
+use std::ffi::OsStr;
+use std::io::Write;
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+/// Allows canceling a fish process by killing its process group.
+#[derive(Default)]
+pub(super) struct FishProcessHandle {
+    /// Whether the process was canceled, and its pid if it is running.
+    state: Mutex<(bool, Option<libc::pid_t>)>,
+}
+
+impl FishProcessHandle {
+    /// Cancel the computation. This kills the fish process, along with anything it started.
+    pub fn cancel(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.0 = true;
+        if let Some(pid) = state.1.take() {
+            unsafe { libc::kill(-pid, libc::SIGTERM) };
+        }
+    }
+
+    /// Record the pid of the running fish process.
+    /// Returns false if the computation was already canceled.
+    fn set_pid(&self, pid: Option<libc::pid_t>) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.0 {
+            return false;
+        }
+        state.1 = pid;
+        true
+    }
+}
+
+/// Return a script which sets up the variables and functions of the session. It is also used by
+/// the parallel builtin to run functions.
+pub(crate) fn session_script(vars: &dyn Environment) -> WString {
+    let mut script = WString::new();
+
+    // Exported variables are passed via the environment.
+    // Universal variables are not read by the fish process, so pass them as globals.
+    let mut names = vars.get_names(EnvMode::GLOBAL | EnvMode::UNIVERSAL | EnvMode::UNEXPORT);
+    names.sort();
+    for name in names {
+        if is_read_only(&name) {
+            continue;
+        }
+        let Some(var) = vars.get(&name) else {
+            continue;
+        };
+        if var.exports() {
+            continue;
+        }
+        script.push_str("set -g");
+        if var.is_pathvar() {
+            script.push_str(" --path");
+        }
+        script.push(' ');
+        script.push_utfstr(&escape(&name));
+        for value in var.as_list() {
+            script.push(' ');
+            script.push_utfstr(&escape(value));
+        }
+        script.push('\n');
+    }
+
+    // Autoloaded functions are found via $fish_function_path. Event handlers are left out, because
+    // the fish process should not react to events like fish_exit.
+    let mut names = function::get_names(true, vars);
+    names.sort();
+    for name in names {
+        let Some(props) = function::get_props(&name) else {
+            continue;
+        };
+        if props.is_autoload.load() {
+            continue;
+        }
+        script.push_utfstr(&props.definition_without_event_handlers(&name));
+    }
+    script
+}
+
+/// Split output into lines like command substitutions do.
+pub(super) fn split_lines(output: &[u8]) -> Vec<WString> {
+    let output = output.strip_suffix(b"\n").unwrap_or(output);
+    if output.is_empty() {
+        return vec![];
+    }
+    output.split(|&c| c == b'\n').map(bytes2wcstring).collect()
+}
+
+/// Return a function which runs the script in a separate fish process, with the given working
+/// directory and exported variables, and returns its output. The function may be invoked on a
+/// background thread. It returns None if the process could not be run or was canceled.
+pub(super) fn get_fish_process_runner(
+    vars: &dyn Environment,
+    script: WString,
+    handle: Arc<FishProcessHandle>,
+) -> impl FnOnce() -> Option<Vec<u8>> + use<> {
+    let pwd = wcs2osstring(&vars.get_pwd_slash());
+    let mut exports = vec![];
+    for name in vars.get_names(EnvMode::EXPORT) {
+        if let Some(var) = vars.get(&name) {
+            exports.push((wcs2osstring(&name), wcs2osstring(&var.as_string())));
+        }
+    }
+    move || {
+        // Don't start the process if the computation was canceled while it was queued.
+        if !handle.set_pid(None) {
+            return None;
+        }
+        let fish_path: &OsStr = match get_fish_path() {
+            FishPath::Absolute(path) => path.as_os_str(),
+            FishPath::LookUpInPath => OsStr::new("fish"),
+        };
+        let mut child = Command::new(fish_path)
+            .arg("--no-config")
+            .current_dir(pwd)
+            .env_clear()
+            .envs(exports)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            // Use a separate process group, so that the terminal's signals don't reach it and
+            // canceling kills anything the script started.
+            .process_group(0)
+            .spawn()
+            .ok()?;
+        let pid = child.id() as libc::pid_t;
+        if !handle.set_pid(Some(pid)) {
+            unsafe { libc::kill(-pid, libc::SIGTERM) };
+        }
+        // fish reads the entire script before running it, so this cannot deadlock.
+        let mut stdin = child.stdin.take().unwrap();
+        let _ = stdin.write_all(&wcs2bytes(&script));
+        drop(stdin);
+        let output = child.wait_with_output();
+        if !handle.set_pid(None) {
+            return None;
+        }
+        let output = output.ok()?;
+        // There is no exit code if the process was killed by a signal.
+        output.status.code()?;
+        Some(output.stdout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{session_script, split_lines};
+    use crate::env::{EnvMode, EnvSetMode};
+    use crate::io::IoChain;
+    use crate::prelude::*;
+    use crate::tests::prelude::*;
+
+    #[test]
+    fn test_split_lines() {
+        assert_eq!(split_lines(b""), Vec::<WString>::new());
+        assert_eq!(split_lines(b"foo\n"), vec![L!("foo").to_owned()]);
+        assert_eq!(
+            split_lines(b"foo\n\nbar"),
+            vec![
+                L!("foo").to_owned(),
+                L!("").to_owned(),
+                L!("bar").to_owned()
+            ]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_session_script() {
+        let _cleanup = test_init();
+        let parser = TestParser::new();
+        let vars = parser.vars();
+        vars.set_one(
+            L!("session_var"),
+            EnvSetMode::new(EnvMode::GLOBAL, false),
+            L!("a b").to_owned(),
+        );
+        vars.set_one(
+            L!("session_exported_var"),
+            EnvSetMode::new(EnvMode::GLOBAL | EnvMode::EXPORT, false),
+            L!("c").to_owned(),
+        );
+        parser.eval(
+            L!("function session_handler --on-event session_event; echo handled; end"),
+            &IoChain::new(),
+        );
+        let script = session_script(vars).to_string();
+        assert!(script.contains("set -g session_var 'a b'\n"));
+        assert!(!script.contains("session_exported_var"));
+        assert!(!script.contains("set -g status "));
+        assert!(script.contains("function session_handler\n"));
+        assert!(script.contains("echo handled"));
+        assert!(!script.contains("session_event"));
+    }
+}
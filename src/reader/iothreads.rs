@@ -1,6 +1,9 @@
 //! A thread pool for handling operations related to the interactive reader
 //! which might block, such as file I/O or completions.
 
+use super::async_complete::AsyncCompletionResult;
+use super::async_preview::CompletionPreviewResult;
+use super::async_prompt::AsyncPromptResult;
 use super::{Reader, reader};
 use crate::fd_monitor::FdEventSignaller;
 use crate::threads::ThreadPool;
@@ -26,6 +29,12 @@ pub(super) struct Debouncers {
     // Debounce history pager computations. This holds a callback, not a single value,
     // both to demonstrate the technique and because the callback can capture local variables.
     pub history_pager: Debounce<Callback>,
+    // Debounce asynchronous prompt computations.
+    pub prompt: Debounce<AsyncPromptResult>,
+    // Debounce asynchronous completion requests.
+    pub completion: Debounce<AsyncCompletionResult>,
+    // Debounce previews of the selected completion.
+    pub completion_preview: Debounce<CompletionPreviewResult>,
 }
 
 impl Debouncers {
@@ -37,10 +46,16 @@ pub fn new() -> Self {
         const HIGHLIGHT_TIMEOUT: Duration = Duration::from_millis(500);
         const HISTORY_PAGER_TIMEOUT: Duration = Duration::from_millis(500);
         const AUTOSUGGEST_TIMEOUT: Duration = Duration::from_millis(500);
+        const PROMPT_TIMEOUT: Duration = Duration::from_millis(500);
+        const COMPLETION_TIMEOUT: Duration = Duration::from_millis(500);
+        const COMPLETION_PREVIEW_TIMEOUT: Duration = Duration::from_millis(500);
         Self {
             autosuggestions: Debounce::new(&pool, &event_signaller, AUTOSUGGEST_TIMEOUT),
             highlight: Debounce::new(&pool, &event_signaller, HIGHLIGHT_TIMEOUT),
             history_pager: Debounce::new(&pool, &event_signaller, HISTORY_PAGER_TIMEOUT),
+            prompt: Debounce::new(&pool, &event_signaller, PROMPT_TIMEOUT),
+            completion: Debounce::new(&pool, &event_signaller, COMPLETION_TIMEOUT),
+            completion_preview: Debounce::new(&pool, &event_signaller, COMPLETION_PREVIEW_TIMEOUT),
             event_signaller,
         }
     }
@@ -1,3 +1,7 @@
+mod async_complete;
+mod async_preview;
+mod async_prompt;
+pub(crate) mod fish_process;
 mod history_search;
 
 mod input;
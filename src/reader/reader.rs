@@ -17,6 +17,14 @@
 //! control-C from generating SIGINT, so failing to disable these would prevent cancellation of wildcard
 //! expansion, etc.
 
+use super::async_complete::{
+    AsyncCompletionResult, async_completion_script, get_async_completion_performer,
+};
+use super::async_preview::{
+    CompletionPreviewResult, completion_preview_script, get_completion_preview_performer,
+};
+use super::async_prompt::{AsyncPromptResult, async_prompt_script, get_async_prompt_performer};
+use super::fish_process::FishProcessHandle;
 use super::history_search::{ReaderHistorySearch, SearchMode, smartcase_flags};
 use super::iothreads::{self, Debouncers};
 use super::word_motion::{MoveWordDir, MoveWordStateMachine, MoveWordStyle};
@@ -27,9 +35,10 @@
 use crate::builtins::shared::STATUS_CMD_OK;
 use crate::common::ScopeGuarding;
 use crate::common::{
-    EscapeFlags, EscapeStringStyle, ScopeGuard, bytes2wcstring, escape, escape_string,
-    exit_without_destructors, get_ellipsis_char, get_obfuscation_read_char, get_program_name,
-    restore_term_foreground_process_group_for_exit, shell_modes, write_loop,
+    EscapeFlags, EscapeStringStyle, ScopeGuard, UnescapeFlags, UnescapeStringStyle, bytes2wcstring,
+    escape, escape_string, exit_without_destructors, get_ellipsis_char, get_ellipsis_str,
+    get_obfuscation_read_char, get_program_name, restore_term_foreground_process_group_for_exit,
+    shell_modes, unescape_string, write_loop,
 };
 use crate::complete::{
     CompleteFlags, Completion, CompletionList, CompletionRequestOptions, complete, complete_load,
@@ -87,7 +96,7 @@
     HAVE_PROC_STAT, hup_jobs, is_interactive_session, job_reap, jobs_requiring_warning_on_exit,
     print_exit_warning_for_jobs, proc_update_jiffies,
 };
-use crate::screen::{CharOffset, Screen, is_dumb, screen_force_clear_to_end};
+use crate::screen::{CharOffset, PromptLinePosition, Screen, is_dumb, screen_force_clear_to_end};
 use crate::should_flog;
 use crate::signal::{
     signal_check_cancel, signal_clear_cancel, signal_reset_handlers, signal_set_handlers,
@@ -110,7 +119,9 @@
 use crate::tty_handoff::SCROLL_CONTENT_UP_TERMINFO_CODE;
 use crate::tty_handoff::XTGETTCAP_QUERY_OS_NAME;
 use crate::tty_handoff::{
-    TtyHandoff, get_tty_protocols_active, initialize_tty_protocols, safe_deactivate_tty_protocols,
+    INITIAL_QUERY_OUTCOME, InitialQueryOutcome, TtyHandoff, get_tty_protocols_active,
+    have_shell_window_title, initialize_tty_protocols, safe_deactivate_tty_protocols,
+    set_shell_window_title,
 };
 use crate::wildcard::wildcard_has;
 use crate::wutil::{fstat, perror, write_to_fd, wstat};
@@ -265,6 +276,7 @@ pub fn terminal_init(vars: &dyn Environment, inputfd: RawFd) -> TerminalInitResu
     });
 
     if !querying_allowed(vars) {
+        INITIAL_QUERY_OUTCOME.get_or_init(|| InitialQueryOutcome::Disabled);
         return TerminalInitResult {
             input_queue,
             background_color,
@@ -291,6 +303,7 @@ pub fn terminal_init(vars: &dyn Environment, inputfd: RawFd) -> TerminalInitResu
             Implicit(Eof) => reader_sighup(),
             Implicit(CheckExit) => {}
             CharEvent::QueryResult(Response(QueryResponse::PrimaryDeviceAttribute)) => {
+                INITIAL_QUERY_OUTCOME.get_or_init(|| InitialQueryOutcome::Answered);
                 break;
             }
             CharEvent::QueryResult(Response(QueryResponse::BackgroundColor(bg))) => {
@@ -300,6 +313,7 @@ pub fn terminal_init(vars: &dyn Environment, inputfd: RawFd) -> TerminalInitResu
             }
             CharEvent::QueryResult(Response(QueryResponse::CursorPosition(_))) => (),
             CharEvent::QueryResult(Timeout) => {
+                INITIAL_QUERY_OUTCOME.get_or_init(|| InitialQueryOutcome::TimedOut);
                 let program = get_program_name();
                 flog!(
                     warning,
@@ -321,7 +335,10 @@ pub fn terminal_init(vars: &dyn Environment, inputfd: RawFd) -> TerminalInitResu
                     .replace(Duration::from_millis(30));
                 break;
             }
-            CharEvent::QueryResult(Interrupted) => break,
+            CharEvent::QueryResult(Interrupted) => {
+                INITIAL_QUERY_OUTCOME.get_or_init(|| InitialQueryOutcome::Interrupted);
+                break;
+            }
             Key(_) | Readline(_) | Command(_) | Implicit(_) => panic!(),
         }
     }
@@ -451,6 +468,13 @@ pub struct ReaderConfig {
     /// Right prompt command, typically fish_right_prompt.
     pub right_prompt_cmd: WString,
 
+    /// Command for the right prompt on the first line of a multi-line prompt, typically
+    /// fish_top_right_prompt.
+    pub top_right_prompt_cmd: WString,
+
+    /// Command for the status line below the command line, typically fish_status_line.
+    pub status_line_cmd: WString,
+
     /// Name of the event to trigger once we're set up.
     pub event: &'static wstr,
 
@@ -469,6 +493,12 @@ pub struct ReaderConfig {
     /// Whether to reexecute prompt function before final rendering.
     pub transient_prompt: bool,
 
+    /// Whether to compute the left and right prompt in the background.
+    pub async_prompt: bool,
+
+    /// Whether to compute completions in the background.
+    pub async_completion: bool,
+
     /// Whether to expand abbreviations.
     pub expand_abbrev_ok: bool,
 
@@ -480,6 +510,9 @@ pub struct ReaderConfig {
     /// If set, do not show what is typed.
     pub in_silent_mode: bool,
 
+    /// If set, give up reading after this long.
+    pub timeout: Option<Duration>,
+
     /// The fd for stdin, default to actual stdin.
     pub inputfd: RawFd,
 }
@@ -563,6 +596,20 @@ enum CompletionAction {
     InsertedUnique,
 }
 
+/// A completion request which is computed in the background.
+struct AsyncCompletionRequest {
+    /// The handle to the completion process.
+    handle: Arc<FishProcessHandle>,
+    /// The command which requested the completions.
+    cmd: ReadlineCmd,
+    /// The range of the token which is completed.
+    token_range: Range<usize>,
+    /// The command line and cursor position when the request was made.
+    /// The completions are discarded if these have changed.
+    command_line: WString,
+    position: usize,
+}
+
 /// readline_loop_state_t encapsulates the state used in a readline loop.
 struct ReadlineLoopState {
     /// The last command that was executed.
@@ -636,6 +683,8 @@ struct LayoutData {
     left_prompt_buff: WString,
     mode_prompt_buff: WString,
     right_prompt_buff: WString,
+    top_right_prompt_buff: WString,
+    status_line_buff: WString,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -672,6 +721,13 @@ pub struct ReaderData {
     pager: Pager,
     /// The output of the pager.
     current_page_rendering: PageRendering,
+    /// The command line for which the pager shows the preview of the selected completion.
+    previewed_command_line: Option<WString>,
+    /// The generation of the most recent completion preview request.
+    /// Results for older selections are discarded.
+    completion_preview_generation: u64,
+    /// The handle to the in-flight completion preview computation, if any.
+    in_flight_completion_preview: Option<Arc<FishProcessHandle>>,
     /// When backspacing, we temporarily suppress autosuggestions.
     suppress_autosuggestion: bool,
 
@@ -712,6 +768,29 @@ pub struct ReaderData {
     mode_prompt_buff: WString,
     /// The output of the last evaluation of the right prompt command.
     right_prompt_buff: WString,
+    /// The output of the last evaluation of the top right prompt command.
+    top_right_prompt_buff: WString,
+    /// The output of the last evaluation of the status line command.
+    status_line_buff: WString,
+
+    /// Whether the left and right prompt have been computed, so that they may be shown while an
+    /// asynchronous prompt computation is in flight.
+    have_full_prompt: bool,
+    /// The generation of the most recent asynchronous prompt request.
+    /// Results from older requests are discarded.
+    async_prompt_generation: u64,
+    /// The handle to the in-flight asynchronous prompt computation, if any.
+    in_flight_async_prompt: Option<Arc<FishProcessHandle>>,
+    /// The generation of the most recent asynchronous completion request.
+    /// Results from older requests are discarded.
+    async_completion_generation: u64,
+    /// The in-flight asynchronous completion request, if any.
+    in_flight_async_completion: Option<AsyncCompletionRequest>,
+    /// Where the final rendering of the prompt line of the executing command is shown, if known.
+    executed_prompt_line: Option<PromptLinePosition>,
+    /// The output of the transient right prompt once the previous command finished, to be written
+    /// over its final rendering when the next prompt is shown.
+    finished_right_prompt: Option<(PromptLinePosition, WString)>,
 
     /// When navigating the pager, we modify the command line.
     /// This is the saved command line before modification.
@@ -723,6 +802,8 @@ pub struct ReaderData {
     /// If this is true, exit reader even if there are running jobs. This happens if we press e.g.
     /// ^D twice.
     did_warn_for_bg_jobs: bool,
+    /// Whether the last call to readline gave up because its timeout expired.
+    read_timed_out: bool,
     /// The current contents of the top item in the kill ring.
     kill_item: WString,
 
@@ -785,6 +866,15 @@ pub(super) fn service_debounced_results(&mut self) {
         if let Some(cb) = self.debouncers.history_pager.take_result() {
             cb(self);
         }
+        if let Some(r) = self.debouncers.prompt.take_result() {
+            self.async_prompt_completed(r);
+        }
+        if let Some(r) = self.debouncers.completion.take_result() {
+            self.async_completion_completed(r);
+        }
+        if let Some(r) = self.debouncers.completion_preview.take_result() {
+            self.completion_preview_completed(r);
+        }
     }
 }
 
@@ -823,6 +913,8 @@ fn read_i(parser: &Parser) {
         expand_abbrev_ok: true,
         autosuggest_ok: check_bool_var(parser.vars(), L!("fish_autosuggestion_enabled"), true),
         transient_prompt: check_bool_var(parser.vars(), L!("fish_transient_prompt"), false),
+        async_prompt: check_bool_var(parser.vars(), L!("fish_async_prompt"), false),
+        async_completion: check_bool_var(parser.vars(), L!("fish_async_completion"), false),
         ..Default::default()
     };
 
@@ -832,6 +924,8 @@ fn read_i(parser: &Parser) {
     } else {
         conf.left_prompt_cmd = LEFT_PROMPT_FUNCTION_NAME.to_owned();
         conf.right_prompt_cmd = RIGHT_PROMPT_FUNCTION_NAME.to_owned();
+        conf.top_right_prompt_cmd = TOP_RIGHT_PROMPT_FUNCTION_NAME.to_owned();
+        conf.status_line_cmd = STATUS_LINE_FUNCTION_NAME.to_owned();
     }
 
     let mut data = reader_push(parser, &history_session_id(parser.vars()), conf);
@@ -873,6 +967,7 @@ fn read_i(parser: &Parser) {
         BufferedOutputter::new(Outputter::stdoutput()).write_command(Osc133CommandFinished {
             exit_status: parser.get_last_status(),
         });
+        data.exec_finished_right_prompt();
         event::fire_generic(parser, L!("fish_postexec").to_owned(), vec![command]);
         // Allow any pending history items to be returned in the history array.
         data.history.resolve_pending();
@@ -1121,6 +1216,28 @@ pub fn reader_set_transient_prompt(vars: &dyn Environment) {
     }
 }
 
+/// Enable or disable asynchronous prompt based on the associated variable.
+pub fn reader_set_async_prompt(vars: &dyn Environment) {
+    // We don't need to _change_ if we're not initialized yet.
+    if let Some(data) = current_data() {
+        data.conf.async_prompt = check_bool_var(vars, L!("fish_async_prompt"), false);
+        if !data.conf.async_prompt {
+            data.cancel_async_prompt();
+        }
+    }
+}
+
+/// Enable or disable asynchronous completion based on the associated variable.
+pub fn reader_set_async_completion(vars: &dyn Environment) {
+    // We don't need to _change_ if we're not initialized yet.
+    if let Some(data) = current_data() {
+        data.conf.async_completion = check_bool_var(vars, L!("fish_async_completion"), false);
+        if !data.conf.async_completion {
+            data.cancel_async_completion();
+        }
+    }
+}
+
 /// Tell the reader that it needs to re-exec the prompt and repaint.
 /// This may be called in response to e.g. a color variable change.
 pub fn reader_schedule_prompt_repaint() {
@@ -1159,6 +1276,7 @@ pub fn reader_execute_readline_cmd(parser: &Parser, ch: CharEvent) {
         readline_cmd_evt.cmd,
         ReadlineCmd::ClearScreenAndRepaint
             | ReadlineCmd::RepaintMode
+            | ReadlineCmd::RepaintStatusLine
             | ReadlineCmd::Repaint
             | ReadlineCmd::ForceRepaint
     ) {
@@ -1206,6 +1324,17 @@ pub fn reader_reading_interrupted(data: &mut ReaderData) -> i32 {
     res
 }
 
+/// The outcome of reader_readline().
+pub enum ReadlineResult {
+    /// A line was read.
+    Line(WString),
+    /// The timeout given in the reader's configuration expired. This holds the text that was
+    /// entered so far.
+    TimedOut(WString),
+    /// Reading was canceled, for example by an interrupt or end of file.
+    Canceled,
+}
+
 /// Read one line of input. Before calling this function, reader_push() must have been called in
 /// order to set up a valid reader environment. If nchars is given, return after reading that many
 /// characters even if a full line has not yet been read. Note: the returned value may be longer
@@ -1215,10 +1344,16 @@ pub fn reader_readline(
     parser: &Parser,
     old_modes: Option<Termios>,
     nchars: Option<NonZeroUsize>,
-) -> Option<WString> {
+) -> ReadlineResult {
     let data = current_data().unwrap();
     let mut reader = Reader { parser, data };
-    reader.readline(old_modes, nchars)
+    match reader.readline(old_modes, nchars) {
+        Some(line) => ReadlineResult::Line(line),
+        None if reader.read_timed_out => {
+            ReadlineResult::TimedOut(reader.command_line.text().to_owned())
+        }
+        None => ReadlineResult::Canceled,
+    }
 }
 
 /// Get the command line state. This may be fetched on a background thread.
@@ -1275,6 +1410,7 @@ pub fn reader_status_count() -> u64 {
 // Name of the variable that tells how long it took, in milliseconds, for the previous
 // interactive command to complete.
 const ENV_CMD_DURATION: &wstr = L!("CMD_DURATION");
+const ENV_CMD_RUSAGE: &wstr = L!("CMD_RUSAGE");
 
 /// Maximum length of prefix string when printing completion list. Longer prefixes will be
 /// ellipsized.
@@ -1290,12 +1426,30 @@ pub fn reader_status_count() -> u64 {
 /// The name of the function that prints the fish right prompt (RPROMPT).
 const RIGHT_PROMPT_FUNCTION_NAME: &wstr = L!("fish_right_prompt");
 
+/// The name of the function that prints the right prompt on the first line of a multi-line prompt.
+const TOP_RIGHT_PROMPT_FUNCTION_NAME: &wstr = L!("fish_top_right_prompt");
+
+/// The name of the function that prints the status line below the command line.
+const STATUS_LINE_FUNCTION_NAME: &wstr = L!("fish_status_line");
+
+/// The name of the function that prints a preview of the selected completion.
+const COMPLETION_PREVIEW_FUNCTION_NAME: &wstr = L!("fish_completion_preview");
+/// How long the selected completion must stay selected before it is previewed, so that cycling
+/// through the completions does not run the preview function for each of them.
+const COMPLETION_PREVIEW_DELAY: Duration = Duration::from_millis(100);
+
 /// The name of the function to use in place of the left prompt if we're in the debugger context.
 const DEBUG_PROMPT_FUNCTION_NAME: &wstr = L!("fish_breakpoint_prompt");
 
 /// The name of the function for getting the input mode indicator.
 const MODE_PROMPT_FUNCTION_NAME: &wstr = L!("fish_mode_prompt");
 
+/// The name of the function that prints the compact form of the left prompt for transient prompts.
+const TRANSIENT_LEFT_PROMPT_FUNCTION_NAME: &wstr = L!("fish_transient_left_prompt");
+
+/// The name of the function that prints the compact form of the right prompt for transient prompts.
+const TRANSIENT_RIGHT_PROMPT_FUNCTION_NAME: &wstr = L!("fish_transient_right_prompt");
+
 /// The default title for the reader. This is used by reader_readline.
 const DEFAULT_TITLE: &wstr = L!("echo (status current-command) \" \" $PWD");
 
@@ -1370,6 +1524,9 @@ fn new(
             saved_autosuggestion: Default::default(),
             pager: Default::default(),
             current_page_rendering: Default::default(),
+            previewed_command_line: None,
+            completion_preview_generation: 0,
+            in_flight_completion_preview: None,
             suppress_autosuggestion: Default::default(),
             reset_loop_state: Default::default(),
             first_prompt: true,
@@ -1387,10 +1544,20 @@ fn new(
             left_prompt_buff: Default::default(),
             mode_prompt_buff: Default::default(),
             right_prompt_buff: Default::default(),
+            top_right_prompt_buff: Default::default(),
+            status_line_buff: Default::default(),
+            have_full_prompt: false,
+            async_prompt_generation: 0,
+            in_flight_async_prompt: None,
+            async_completion_generation: 0,
+            in_flight_async_completion: None,
+            executed_prompt_line: None,
+            finished_right_prompt: None,
             cycle_command_line: Default::default(),
             cycle_cursor_pos: Default::default(),
             exit_loop_requested: Default::default(),
             did_warn_for_bg_jobs: Default::default(),
+            read_timed_out: false,
             kill_item: Default::default(),
             force_exec_prompt_and_repaint: Default::default(),
             last_jump_target: Default::default(),
@@ -1766,6 +1933,14 @@ fn is_repaint_needed(&self, mcolors: Option<&[HighlightSpec]>) -> bool {
                 self.right_prompt_buff != last.right_prompt_buff,
                 "right_prompt",
             )
+            || check(
+                self.top_right_prompt_buff != last.top_right_prompt_buff,
+                "top_right_prompt",
+            )
+            || check(
+                self.status_line_buff != last.status_line_buff,
+                "status_line",
+            )
             || check(
                 self.pager
                     .rendering_needs_update(&self.current_page_rendering),
@@ -1789,6 +1964,8 @@ fn make_layout_data(&self) -> LayoutData {
         result.left_prompt_buff = self.left_prompt_buff.clone();
         result.mode_prompt_buff = self.mode_prompt_buff.clone();
         result.right_prompt_buff = self.right_prompt_buff.clone();
+        result.top_right_prompt_buff = self.top_right_prompt_buff.clone();
+        result.status_line_buff = self.status_line_buff.clone();
         result
     }
 
@@ -1899,6 +2076,8 @@ fn paint_layout(&mut self, reason: &wstr, is_final_rendering: bool) {
             // Prepend the mode prompt to the left prompt.
             &(self.data.mode_prompt_buff.clone() + &self.data.left_prompt_buff[..]),
             &self.data.right_prompt_buff,
+            &self.data.top_right_prompt_buff,
+            &self.data.status_line_buff,
             &full_line,
             autosuggested_range,
             colors,
@@ -2557,6 +2736,9 @@ fn readline(
         // SIZE_MAX.
         self.rls_mut().nchars = nchars;
 
+        self.read_timed_out = false;
+        self.input_data.read_deadline = self.conf.timeout.map(|timeout| Instant::now() + timeout);
+
         // The command line before completion.
         self.cycle_command_line.clear();
         self.cycle_cursor_pos = 0;
@@ -2600,17 +2782,30 @@ fn readline(
 
         // Disable tty protocols now that we're going to execute a command.
         tty.disable_tty_protocols();
+        self.input_data.read_deadline = None;
+
+        // A prompt or completions which are still being computed are stale now.
+        self.cancel_async_prompt();
+        self.cancel_async_completion();
 
         if self.conf.transient_prompt {
             self.exec_prompt(true, true);
         }
 
-        // Redraw the command line. This is what ensures the autosuggestion is hidden, etc. after the
-        // user presses enter.
-        if self.is_repaint_needed(None) || self.screen.scrolled || self.conf.inputfd != STDIN_FILENO
+        // Redraw the command line. This is what ensures the autosuggestion and status line are
+        // hidden, etc. after the user presses enter.
+        if self.is_repaint_needed(None)
+            || self.screen.scrolled
+            || !self.status_line_buff.is_empty()
+            || self.conf.inputfd != STDIN_FILENO
         {
             self.layout_and_repaint_before_execution();
         }
+        self.executed_prompt_line = if self.conf.transient_prompt {
+            self.screen.prompt_line_position()
+        } else {
+            None
+        };
 
         // Finish syntax highlighting (but do not wait forever).
         if self.rls().finished {
@@ -2744,6 +2939,7 @@ fn color_suggest_repaint_now(&mut self) {
             self.update_autosuggestion();
             self.super_highlight_me_plenty();
         }
+        self.update_completion_preview();
         if self.is_repaint_needed(None) {
             self.layout_and_repaint(L!("toplevel"));
         }
@@ -2809,6 +3005,9 @@ fn handle_char_event(&mut self, injected_event: Option<CharEvent>) -> ControlFlo
                 }
 
                 let readline_cmd = readline_cmd_evt.cmd;
+                if command_cancels_async_completion(readline_cmd) {
+                    self.cancel_async_completion();
+                }
                 if readline_cmd == ReadlineCmd::Cancel && self.is_navigating_pager_contents() {
                     self.clear_transient_edit();
                 }
@@ -2841,6 +3040,8 @@ fn handle_char_event(&mut self, injected_event: Option<CharEvent>) -> ControlFlo
                 self.run_input_command_scripts(&command);
             }
             CharEvent::Key(kevt) => {
+                // Typing cancels the completions which are being computed.
+                self.cancel_async_completion();
                 // Ordinary char.
                 if kevt.input_style == CharInputStyle::NotFirst
                     && self.active_edit_line().1.position() == 0
@@ -2893,6 +3094,11 @@ fn handle_char_event(&mut self, injected_event: Option<CharEvent>) -> ControlFlo
                             ..Default::default()
                         });
                     }
+                    ReadTimeout => {
+                        flog!(reader, "Giving up reading after timeout");
+                        self.read_timed_out = true;
+                        self.exit_loop_requested = true;
+                    }
                 }
             }
             CharEvent::QueryResult(query_result) => {
@@ -2945,6 +3151,9 @@ fn handle_char_event(&mut self, injected_event: Option<CharEvent>) -> ControlFlo
                                 }
                             });
                             self.screen.set_position_in_viewport(whence, y);
+                            if reason == NewPrompt {
+                                self.write_finished_right_prompt(y);
+                            }
                         }
                         if let Some(background_color_query) = query.background_color {
                             if let Some(background_color) = &background_color_query.result {
@@ -3166,6 +3375,24 @@ fn handle_readline_command(&mut self, c: ReadlineCmd) {
                 self.force_exec_prompt_and_repaint = false;
                 self.parser.libdata_mut().is_repaint = false;
             }
+            rl::RepaintStatusLine => {
+                // Only rerun the status line. Since we don't reset the screen, only the lines
+                // of the status line are redrawn, and the prompt and command line stay as they are.
+                self.queued_repaint = false;
+                self.parser.libdata_mut().is_repaint = true;
+                {
+                    // The same setup as in exec_prompt().
+                    let _suppress_trace = self.parser.push_scope(|s| s.suppress_fish_trace = true);
+                    let _noninteractive = self.parser.push_scope(|s| s.is_interactive = false);
+                    let mut scoped_tty = TtyHandoff::new(reader_save_screen_state);
+                    scoped_tty.disable_tty_protocols();
+                    self.exec_status_line();
+                }
+                if self.is_repaint_needed(None) {
+                    self.layout_and_repaint(L!("status line"));
+                }
+                self.parser.libdata_mut().is_repaint = false;
+            }
             rl::Complete | rl::CompleteAndSearch => {
                 if !self.conf.complete_ok {
                     return;
@@ -4567,6 +4794,8 @@ impl ReaderData {
     // Ensure we have no pager contents.
     fn clear_pager(&mut self) {
         self.pager.clear();
+        self.previewed_command_line = None;
+        self.cancel_completion_preview();
         self.history_pager = None;
         self.clear(EditableLineTag::SearchField);
         self.command_line_transient_edit = None;
@@ -5033,7 +5262,7 @@ fn write_title(
         osc: fn(&[WString]) -> TerminalCommand<'_>,
         function_name: &wstr,
         fallback_title: Option<&wstr>,
-    ) -> bool {
+    ) -> Option<Vec<WString>> {
         let mut title_function_call;
         let mut title_command = fallback_title;
         if function::exists(function_name, parser) {
@@ -5047,9 +5276,7 @@ fn write_title(
             }
             title_command = Some(&title_function_call);
         }
-        let Some(title_command) = title_command else {
-            return false;
-        };
+        let title_command = title_command?;
         let mut title_buffer = vec![];
         let _ = exec_subshell(
             title_command,
@@ -5058,11 +5285,11 @@ fn write_title(
             /*apply_exit_status=*/ false,
         );
 
-        if !title_buffer.is_empty() {
-            out.write_command(osc(&title_buffer));
-            return true;
+        if title_buffer.is_empty() {
+            return None;
         }
-        false
+        out.write_command(osc(&title_buffer));
+        Some(title_buffer)
     }
 
     let _scoped = parser.push_scope(|s| {
@@ -5071,8 +5298,7 @@ fn write_title(
     });
 
     let mut out = BufferedOutputter::new(Outputter::stdoutput());
-    let mut written = false;
-    written |= write_title(
+    let title = write_title(
         parser,
         &mut out,
         cmd,
@@ -5080,6 +5306,9 @@ fn write_title(
         L!("fish_title"),
         Some(DEFAULT_TITLE),
     );
+    let mut written = title.is_some();
+    // Without a title of our own, job titles would never be undone.
+    set_shell_window_title(title.as_deref().unwrap_or_default());
     written |= write_title(
         parser,
         &mut out,
@@ -5087,7 +5316,8 @@ fn write_title(
         |title_buffer| Osc1TabTitle(title_buffer),
         L!("fish_tab_title"),
         /*default_title=*/ None,
-    );
+    )
+    .is_some();
 
     out.reset_text_face();
 
@@ -5097,10 +5327,59 @@ fn write_title(
     }
 }
 
+/// Return the window title to show while a foreground job runs, which is the output of the
+/// fish_job_title function. This is empty if the function does not exist, or if fish has not set
+/// a title of its own which could be restored afterwards.
+///
+/// \param job_command The command of the job, which is passed to fish_job_title.
+/// \param parser The parser to use for autoloading fish_job_title.
+pub fn reader_job_title(job_command: &wstr, parser: &Parser) -> Vec<WString> {
+    if !have_shell_window_title() || !function::exists(L!("fish_job_title"), parser) {
+        return vec![];
+    }
+    let _scoped = parser.push_scope(|s| {
+        s.is_interactive = false;
+        s.suppress_fish_trace = true;
+    });
+    let mut title_function_call = L!("fish_job_title ").to_owned();
+    title_function_call.push_utfstr(&escape_string(
+        job_command,
+        EscapeStringStyle::Script(EscapeFlags::NO_QUOTED | EscapeFlags::NO_TILDE),
+    ));
+    let mut title_buffer = vec![];
+    let _ = exec_subshell(
+        &title_function_call,
+        parser,
+        Some(&mut title_buffer),
+        /*apply_exit_status=*/ false,
+    );
+    title_buffer
+}
+
 impl<'a> Reader<'a> {
+    /// Return the function which prints the compact form of the given prompt command for the final
+    /// rendering, if it is defined.
+    fn transient_prompt_cmd(&self, prompt_cmd: &wstr) -> Option<&'static wstr> {
+        let transient_prompt_cmd = if prompt_cmd == LEFT_PROMPT_FUNCTION_NAME {
+            TRANSIENT_LEFT_PROMPT_FUNCTION_NAME
+        } else if prompt_cmd == RIGHT_PROMPT_FUNCTION_NAME {
+            TRANSIENT_RIGHT_PROMPT_FUNCTION_NAME
+        } else {
+            return None;
+        };
+        function::exists(transient_prompt_cmd, self.parser).then_some(transient_prompt_cmd)
+    }
+
     fn exec_prompt_cmd(&self, prompt_cmd: &wstr, final_prompt: bool) -> Vec<WString> {
         let mut output = vec![];
-        let prompt_cmd = if final_prompt && function::exists(prompt_cmd, self.parser) {
+        let transient_prompt_cmd = if final_prompt {
+            self.transient_prompt_cmd(prompt_cmd)
+        } else {
+            None
+        };
+        let prompt_cmd = if let Some(transient_prompt_cmd) = transient_prompt_cmd {
+            Cow::Borrowed(transient_prompt_cmd)
+        } else if final_prompt && function::exists(prompt_cmd, self.parser) {
             Cow::Owned(prompt_cmd.to_owned() + L!(" --final-rendering"))
         } else {
             Cow::Borrowed(prompt_cmd)
@@ -5125,14 +5404,26 @@ fn exec_prompt(&mut self, full_prompt: bool, final_prompt: bool) {
         // This allows prompts to react to $COLUMNS.
         reader_update_termsize(self.parser);
 
+        // The compact form of the left prompt replaces the mode prompt.
+        let compact_left_prompt = final_prompt
+            && self
+                .transient_prompt_cmd(&self.conf.left_prompt_cmd)
+                .is_some();
+
         self.mode_prompt_buff.clear();
-        if function::exists(MODE_PROMPT_FUNCTION_NAME, self.parser) {
+        if !compact_left_prompt && function::exists(MODE_PROMPT_FUNCTION_NAME, self.parser) {
             // We do not support multiline mode indicators, so just concatenate all of them.
             self.mode_prompt_buff =
                 WString::from_iter(self.exec_prompt_cmd(MODE_PROMPT_FUNCTION_NAME, final_prompt));
         }
 
-        if full_prompt {
+        if full_prompt && !final_prompt && self.can_exec_prompt_async() {
+            // Keep showing the previous prompt until the new one arrives.
+            self.exec_prompt_async();
+            self.exec_top_right_prompt(final_prompt);
+            self.exec_status_line();
+        } else if full_prompt {
+            self.cancel_async_prompt();
             self.left_prompt_buff.clear();
             self.right_prompt_buff.clear();
 
@@ -5155,13 +5446,28 @@ fn exec_prompt(&mut self, full_prompt: bool, final_prompt: bool) {
             // Don't execute the right prompt if it is undefined fish_right_prompt
             if !self.conf.right_prompt_cmd.is_empty()
                 && (self.conf.right_prompt_cmd != RIGHT_PROMPT_FUNCTION_NAME
-                    || function::exists(&self.conf.right_prompt_cmd, self.parser))
+                    || function::exists(&self.conf.right_prompt_cmd, self.parser)
+                    || (final_prompt
+                        && self
+                            .transient_prompt_cmd(&self.conf.right_prompt_cmd)
+                            .is_some()))
             {
                 // Right prompt does not support multiple lines, so just concatenate all of them.
                 self.right_prompt_buff = WString::from_iter(
                     self.exec_prompt_cmd(&self.conf.right_prompt_cmd, final_prompt),
                 );
             }
+
+            self.have_full_prompt = true;
+            if compact_left_prompt {
+                self.top_right_prompt_buff.clear();
+            } else {
+                self.exec_top_right_prompt(final_prompt);
+            }
+
+            if !final_prompt {
+                self.exec_status_line();
+            }
         }
 
         // Write the screen title. Do not reset the cursor position: exec_prompt is called when there
@@ -5181,6 +5487,250 @@ fn exec_prompt(&mut self, full_prompt: bool, final_prompt: bool) {
     }
 }
 
+impl<'a> Reader<'a> {
+    /// Run the transient right prompt with a `--finished` argument once a command has finished.
+    /// Its output replaces the final rendering of the right prompt of that command.
+    fn exec_finished_right_prompt(&mut self) {
+        self.finished_right_prompt = None;
+        let Some(position) = self.executed_prompt_line.take() else {
+            return;
+        };
+        if self.conf.right_prompt_cmd != RIGHT_PROMPT_FUNCTION_NAME {
+            return;
+        }
+        let Some(prompt_cmd) = self.transient_prompt_cmd(&self.conf.right_prompt_cmd) else {
+            return;
+        };
+
+        // The same setup as in exec_prompt().
+        let _suppress_trace = self.parser.push_scope(|s| s.suppress_fish_trace = true);
+        let _noninteractive = self.parser.push_scope(|s| s.is_interactive = false);
+
+        let mut output = vec![];
+        let prompt_cmd = prompt_cmd.to_owned() + L!(" --finished");
+        let _ = exec_subshell(&prompt_cmd, self.parser, Some(&mut output), false);
+        self.finished_right_prompt = Some((position, WString::from_iter(output)));
+    }
+
+    /// Write the output of the transient right prompt of the previous command, now that we know
+    /// where the new prompt is shown.
+    fn write_finished_right_prompt(&mut self, prompt_y: Option<usize>) {
+        let Some((position, right_prompt)) = self.finished_right_prompt.take() else {
+            return;
+        };
+        if prompt_y.is_none() {
+            return;
+        }
+        if !self
+            .screen
+            .rewrite_right_prompt(position, &right_prompt, termsize_last())
+        {
+            flog!(
+                reader,
+                "Not rewriting the right prompt of the previous command"
+            );
+        }
+    }
+}
+
+impl<'a> Reader<'a> {
+    /// Execute the top right prompt command. The output is inserted into top_right_prompt_buff.
+    fn exec_top_right_prompt(&mut self, final_prompt: bool) {
+        self.top_right_prompt_buff.clear();
+        if !self.conf.top_right_prompt_cmd.is_empty()
+            && function::exists(&self.conf.top_right_prompt_cmd, self.parser)
+        {
+            // Like the right prompt, this is a single line.
+            self.top_right_prompt_buff = WString::from_iter(
+                self.exec_prompt_cmd(&self.conf.top_right_prompt_cmd, final_prompt),
+            );
+        }
+    }
+
+    /// Return whether the left and right prompt may be computed in the background.
+    /// The first prompt is always computed synchronously, so that there is something to show.
+    fn can_exec_prompt_async(&self) -> bool {
+        self.conf.async_prompt
+            && self.have_full_prompt
+            && self.conf.left_prompt_cmd == LEFT_PROMPT_FUNCTION_NAME
+            && function::exists(LEFT_PROMPT_FUNCTION_NAME, self.parser)
+    }
+
+    /// Start computing the left and right prompt in the background, canceling any computation
+    /// which is still in flight. The result is handled by async_prompt_completed().
+    fn exec_prompt_async(&mut self) {
+        self.cancel_async_prompt();
+        self.async_prompt_generation += 1;
+        let right_prompt_cmd = (!self.conf.right_prompt_cmd.is_empty()
+            && (self.conf.right_prompt_cmd != RIGHT_PROMPT_FUNCTION_NAME
+                || function::exists(&self.conf.right_prompt_cmd, self.parser)))
+        .then_some(self.conf.right_prompt_cmd.as_utfstr());
+        let script = async_prompt_script(
+            self.parser.vars(),
+            &self.parser.get_last_statuses(),
+            &self.conf.left_prompt_cmd,
+            right_prompt_cmd,
+        );
+        let handle = Arc::new(FishProcessHandle::default());
+        self.in_flight_async_prompt = Some(Arc::clone(&handle));
+        flog!(reader_render, "Computing prompt asynchronously");
+        let performer = get_async_prompt_performer(
+            self.parser.vars(),
+            script,
+            self.async_prompt_generation,
+            handle,
+        );
+        self.debouncers.prompt.perform(performer);
+    }
+
+    /// Handle the result of an asynchronous prompt computation.
+    fn async_prompt_completed(&mut self, result: AsyncPromptResult) {
+        assert_is_main_thread();
+        if result.generation != self.async_prompt_generation {
+            return;
+        }
+        self.in_flight_async_prompt = None;
+        let Some((left_prompt, right_prompt)) = result.prompts else {
+            return;
+        };
+        self.left_prompt_buff = left_prompt;
+        self.right_prompt_buff = right_prompt;
+        if self.is_repaint_needed(None) {
+            self.screen.reset_line(/*repaint_prompt=*/ true);
+            self.layout_and_repaint(L!("async prompt"));
+        }
+    }
+}
+
+impl ReaderData {
+    /// Cancel the in-flight asynchronous prompt computation, if any.
+    /// Its result will be discarded.
+    fn cancel_async_prompt(&mut self) {
+        if let Some(handle) = self.in_flight_async_prompt.take() {
+            handle.cancel();
+            self.async_prompt_generation += 1;
+        }
+    }
+
+    /// Cancel the in-flight completion preview computation, if any.
+    /// Its result will be discarded.
+    fn cancel_completion_preview(&mut self) {
+        if let Some(handle) = self.in_flight_completion_preview.take() {
+            handle.cancel();
+            self.completion_preview_generation += 1;
+        }
+    }
+
+    /// Cancel the in-flight asynchronous completion request, if any.
+    /// Its result will be discarded.
+    fn cancel_async_completion(&mut self) {
+        if let Some(request) = self.in_flight_async_completion.take() {
+            request.handle.cancel();
+            self.async_completion_generation += 1;
+            self.pager.extra_progress_text.clear();
+        }
+    }
+}
+
+impl<'a> Reader<'a> {
+    /// Start computing the output of fish_completion_preview for the selected completion in the
+    /// pager in the background, once the selection has not changed for COMPLETION_PREVIEW_DELAY.
+    /// Any computation for a previous selection is canceled. The result is handled by
+    /// completion_preview_completed().
+    fn update_completion_preview(&mut self) {
+        let selected = (self.history_pager.is_none()
+            && self
+                .pager
+                .selected_completion(&self.current_page_rendering)
+                .is_some())
+        .then(|| self.command_line.text().to_owned());
+        if selected == self.previewed_command_line {
+            return;
+        }
+        self.previewed_command_line = selected;
+        self.cancel_completion_preview();
+        self.completion_preview_generation += 1;
+        if self.previewed_command_line.is_none()
+            || !function::exists(COMPLETION_PREVIEW_FUNCTION_NAME, self.parser)
+        {
+            self.pager.set_preview(vec![]);
+            return;
+        }
+        let description = self
+            .pager
+            .selected_completion(&self.current_page_rendering)
+            .map(|comp| comp.description.clone())
+            .unwrap_or_default();
+
+        // The selected completion has been inserted into the command line, maybe followed by a
+        // space.
+        let text = self.command_line.text();
+        let position = self.command_line.position();
+        let (token_range, prev_token_range) = get_token_extent(text, position);
+        let token = if token_range.is_empty() {
+            &text[prev_token_range]
+        } else {
+            &text[token_range]
+        };
+        let token = unescape_string(
+            token,
+            UnescapeStringStyle::Script(UnescapeFlags::INCOMPLETE),
+        )
+        .unwrap_or_else(|| token.to_owned());
+        let cmd = sprintf!(
+            "%s %s %s %s",
+            COMPLETION_PREVIEW_FUNCTION_NAME,
+            escape(&token),
+            escape(&description),
+            escape(&text[..position])
+        );
+
+        // The previous preview is kept until then, so that the pager does not jump around.
+        let script = completion_preview_script(self.parser.vars(), &cmd);
+        let handle = Arc::new(FishProcessHandle::default());
+        self.in_flight_completion_preview = Some(Arc::clone(&handle));
+        let performer = get_completion_preview_performer(
+            self.parser.vars(),
+            script,
+            self.completion_preview_generation,
+            COMPLETION_PREVIEW_DELAY,
+            handle,
+        );
+        self.debouncers.completion_preview.perform(performer);
+    }
+
+    /// Show the result of a completion preview computation, unless the selection changed since.
+    fn completion_preview_completed(&mut self, result: CompletionPreviewResult) {
+        assert_is_main_thread();
+        if result.generation != self.completion_preview_generation {
+            return;
+        }
+        self.in_flight_completion_preview = None;
+        let Some(preview) = result.preview else {
+            return;
+        };
+        self.pager.set_preview(preview);
+        if self.is_repaint_needed(None) {
+            self.layout_and_repaint(L!("completion preview"));
+        }
+    }
+
+    /// Execute the status line command. The output is inserted into status_line_buff.
+    /// The status line is not shown in the final rendering, so it is never run with
+    /// `--final-rendering`.
+    fn exec_status_line(&mut self) {
+        self.status_line_buff.clear();
+        if !self.conf.status_line_cmd.is_empty()
+            && function::exists(&self.conf.status_line_cmd, self.parser)
+        {
+            self.status_line_buff = join_strings(
+                &self.exec_prompt_cmd(&self.conf.status_line_cmd, false),
+                '\n',
+            );
+        }
+    }
+}
+
 #[derive(Default, Clone, PartialEq, Debug)]
 pub(super) struct Autosuggestion {
     /// The text to use, as an extension/replacement of the current line.
@@ -6144,6 +6694,23 @@ fn expand_abbreviation_at_cursor(&mut self, cursor_backtrack: usize) -> bool {
     }
 }
 
+/// Return whether the given command cancels an in-flight asynchronous completion request.
+/// Only commands which just redraw the screen keep it going.
+fn command_cancels_async_completion(c: ReadlineCmd) -> bool {
+    #[allow(non_camel_case_types)]
+    type rl = ReadlineCmd;
+    !matches!(
+        c,
+        rl::Repaint
+            | rl::ForceRepaint
+            | rl::RepaintMode
+            | rl::RepaintStatusLine
+            | rl::ClearScreenAndRepaint
+            | rl::BeginUndoGroup
+            | rl::EndUndoGroup
+    )
+}
+
 /// Indicates if the given command char ends paging.
 fn command_ends_paging(c: ReadlineCmd, focused_on_search_field: bool) -> bool {
     #[allow(non_camel_case_types)]
@@ -6177,6 +6744,7 @@ fn command_ends_paging(c: ReadlineCmd, focused_on_search_field: bool) -> bool {
         | rl::UpLine
         | rl::DownLine
         | rl::Repaint
+        | rl::RepaintStatusLine
         | rl::SuppressAutosuggestion
         | rl::BeginningOfHistory
         | rl::EndOfHistory =>
@@ -6258,6 +6826,7 @@ fn command_ends_history_search(c: ReadlineCmd) -> bool {
             | rl::BeginningOfHistory
             | rl::EndOfHistory
             | rl::Repaint
+            | rl::RepaintStatusLine
             | rl::ForceRepaint
     )
 }
@@ -6348,6 +6917,22 @@ fn reader_run_command(parser: &Parser, cmd: &wstr) -> EvalRes {
             ParserEnvSetMode::new(EnvMode::UNEXPORT),
             duration.as_millis().to_wstring(),
         );
+        // The resources used by each process of the last job, like $pipestatus.
+        let rusage = parser
+            .get_last_statuses()
+            .rusage
+            .iter()
+            .map(|rusage| {
+                rusage
+                    .map(|rusage| rusage.to_var_value())
+                    .unwrap_or_default()
+            })
+            .collect();
+        parser.set_var(
+            ENV_CMD_RUSAGE,
+            ParserEnvSetMode::new(EnvMode::UNEXPORT),
+            rusage,
+        );
     }
 
     term_steal(eval_res.status.is_success());
@@ -6921,7 +7506,13 @@ fn compute_and_apply_completions(&mut self, c: ReadlineCmd) {
         // up to the end of the token we're completing.
         let cmdsub = &el.text()[cmdsub_range.start..token_range.end];
 
-        let (mut comp, _needs_load) = complete(
+        if self.conf.async_completion {
+            let cmdsub = cmdsub.to_owned();
+            self.complete_async(c, token_range, &cmdsub);
+            return;
+        }
+
+        let (comp, _needs_load) = complete(
             cmdsub,
             CompletionRequestOptions::normal(),
             &self.parser.context(),
@@ -6933,6 +7524,64 @@ fn compute_and_apply_completions(&mut self, c: ReadlineCmd) {
         token_range.start = std::cmp::min(token_range.start, el.text().len());
         token_range.end = std::cmp::min(token_range.end, el.text().len());
 
+        self.apply_completions(c, token_range, comp);
+    }
+
+    /// Start computing the completions for the given command substitution in the background,
+    /// canceling any request which is still in flight. The result is handled by
+    /// async_completion_completed().
+    fn complete_async(&mut self, c: ReadlineCmd, token_range: Range<usize>, cmdsub: &wstr) {
+        self.cancel_async_completion();
+        self.async_completion_generation += 1;
+        let script = async_completion_script(self.parser.vars(), cmdsub);
+        let handle = Arc::new(FishProcessHandle::default());
+        self.in_flight_async_completion = Some(AsyncCompletionRequest {
+            handle: Arc::clone(&handle),
+            cmd: c,
+            token_range,
+            command_line: self.command_line.text().to_owned(),
+            position: self.command_line.position(),
+        });
+        self.pager.extra_progress_text = wgettext_fmt!("Completing%s", get_ellipsis_str());
+        flog!(reader_render, "Computing completions asynchronously");
+        let performer = get_async_completion_performer(
+            self.parser.vars(),
+            script,
+            self.async_completion_generation,
+            handle,
+        );
+        self.debouncers.completion.perform(performer);
+    }
+
+    /// Handle the result of an asynchronous completion request.
+    fn async_completion_completed(&mut self, result: AsyncCompletionResult) {
+        assert_is_main_thread();
+        if result.generation != self.async_completion_generation {
+            return;
+        }
+        let Some(request) = self.in_flight_async_completion.take() else {
+            return;
+        };
+        self.pager.extra_progress_text.clear();
+        // Bindings which run fish script may have changed the command line without canceling
+        // the request.
+        if let Some(comp) = result.completions {
+            if self.command_line.text() == request.command_line
+                && self.command_line.position() == request.position
+            {
+                self.apply_completions(request.cmd, request.token_range, comp);
+            }
+        }
+        self.layout_and_repaint(L!("async completion"));
+    }
+
+    /// Sort the completions for the given token and insert or show them.
+    fn apply_completions(
+        &mut self,
+        c: ReadlineCmd,
+        token_range: Range<usize>,
+        mut comp: Vec<Completion>,
+    ) {
         // Munge our completions.
         sort_and_prioritize(&mut comp, CompletionRequestOptions::default());
 
@@ -1,10 +1,13 @@
 //! This file supports specifying and applying redirections.
 
-use crate::io::IoChain;
+use crate::fds::{heightenize_fd, wopen_cloexec};
+use crate::io::{IoChain, OPEN_MASK};
+use crate::path::path_apply_working_directory;
 use crate::prelude::*;
 use crate::wutil::fish_wcstoi;
 use nix::fcntl::OFlag;
-use std::os::fd::RawFd;
+use std::os::fd::{AsRawFd, OwnedFd, RawFd};
+use std::sync::Mutex;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum RedirectionMode {
@@ -88,6 +91,46 @@ pub fn oflags(&self) -> OFlag {
 
 pub type RedirectionSpecList = Vec<RedirectionSpec>;
 
+/// The files opened by redirections like `{var}>file`. Unlike other redirections, these stay open
+/// in fish until closed with `{var}>&-`.
+static NAMED_FDS: Mutex<Vec<OwnedFd>> = Mutex::new(Vec::new());
+
+/// Open the target of the file redirection `spec` on a newly allocated fd, outside the range of
+/// fds that users redirect directly. Return the fd, which stays open until [`close_named_fd`].
+pub fn open_named_fd(spec: &RedirectionSpec, pwd: &wstr) -> nix::Result<RawFd> {
+    let path = path_apply_working_directory(&spec.target, pwd);
+    let file = match wopen_cloexec(&path, spec.oflags(), OPEN_MASK) {
+        Err(_) if spec.mode == RedirectionMode::TryInput => {
+            wopen_cloexec(L!("/dev/null"), spec.oflags(), OPEN_MASK)?
+        }
+        res => res?,
+    };
+    let fd = heightenize_fd(file.into(), true)?;
+    let raw_fd = fd.as_raw_fd();
+    NAMED_FDS.lock().unwrap().push(fd);
+    Ok(raw_fd)
+}
+
+/// Close an fd opened by [`open_named_fd`]. Return false if `fd` was not opened that way.
+pub fn close_named_fd(fd: RawFd) -> bool {
+    let mut fds = NAMED_FDS.lock().unwrap();
+    let Some(idx) = fds.iter().position(|f| f.as_raw_fd() == fd) else {
+        return false;
+    };
+    fds.remove(idx);
+    true
+}
+
+/// Return whether the given fd was opened by [`open_named_fd`].
+/// Unlike fish's other internal fds, these may be used in redirections.
+pub fn is_named_fd(fd: RawFd) -> bool {
+    NAMED_FDS
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|f| f.as_raw_fd() == fd)
+}
+
 /// Produce a dup_fd_list_t from an io_chain. This may not be called before fork().
 /// The result contains the list of fd actions (dup2 and close), as well as the list
 /// of fds opened.
@@ -147,6 +147,21 @@ pub struct Cursor {
     y: usize,
 }
 
+/// Where the line with the right prompt was shown in the terminal window, so that the right prompt
+/// can be replaced once the command has finished.
+#[derive(Clone, Copy)]
+pub struct PromptLinePosition {
+    /// The line within the terminal window.
+    y: usize,
+    /// How many columns the left prompt and the command line occupy on that line.
+    used_width: usize,
+    /// The width of the screen at the time.
+    screen_width: usize,
+    /// The line within the terminal window where the next prompt is shown if the command does not
+    /// write anything.
+    next_prompt_y: usize,
+}
+
 /// A class representing screen contents.
 #[derive(Clone, Default)]
 pub struct ScreenData {
@@ -161,6 +176,24 @@ pub struct ScreenData {
 
     /// Number of prompt lines rendered on the screen.
     visible_prompt_lines: usize,
+
+    /// The status line rendered below the command line, if any.
+    status_line: Option<StatusLine>,
+}
+
+/// A status line, shown below the command line.
+#[derive(Clone, Default, Eq, PartialEq)]
+struct StatusLine {
+    /// Index of the first screen line occupied by the status line.
+    start: usize,
+    /// The truncated text, one entry per screen line.
+    lines: Vec<WString>,
+}
+
+impl StatusLine {
+    fn rows(&self) -> Range<usize> {
+        self.start..self.start + self.lines.len()
+    }
 }
 
 impl ScreenData {
@@ -229,6 +262,8 @@ pub struct Screen {
     actual: ScreenData,
     /// A string containing the prompt which was last printed to the screen.
     actual_left_prompt: Option<WString>,
+    /// The top right prompt which was last printed to the screen.
+    actual_top_right_prompt: WString,
     /// Last right prompt width.
     last_right_prompt_width: usize,
     /// If we support soft wrapping, we can output to this location without any cursor motion.
@@ -259,6 +294,7 @@ fn default() -> Self {
             desired: Default::default(),
             actual: Default::default(),
             actual_left_prompt: Default::default(),
+            actual_top_right_prompt: Default::default(),
             last_right_prompt_width: Default::default(),
             soft_wrap_location: Default::default(),
             need_clear_lines: Default::default(),
@@ -276,6 +312,8 @@ impl Screen {
     ///
     /// \param left_prompt the prompt to prepend to the command line
     /// \param right_prompt the right prompt, or NULL if none
+    /// \param top_right_prompt the right prompt for the first line of a multi-line left prompt
+    /// \param status_line the status line to render below the command line
     /// \param commandline the command line
     /// \param explicit_len the number of characters of the "explicit" (non-autosuggestion) portion
     /// of the command line \param colors the colors to use for the commanad line \param indent the
@@ -287,6 +325,8 @@ pub fn write(
         curr_termsize: Termsize,
         left_prompt: &wstr,
         right_prompt: &wstr,
+        top_right_prompt: &wstr,
+        status_line: &wstr,
         commandline: &wstr,
         autosuggested_range: Range<usize>,
         mut colors: Vec<HighlightSpec>,
@@ -346,6 +386,8 @@ struct ScrolledCursor {
             self.viewport_y,
             left_prompt,
             right_prompt,
+            top_right_prompt,
+            status_line,
             explicit_before_suggestion,
             &mut colors,
             &mut indent,
@@ -476,6 +518,13 @@ struct ScrolledCursor {
             } else {
                 0
             };
+        // The status line is not shown while the command line is scrolled, or in the final rendering.
+        let status_line_lines = if scrolled_cursor.scroll_amount != 0 || is_final_rendering {
+            0
+        } else {
+            layout.status_line.len()
+        };
+        let full_line_count = full_line_count + status_line_lines;
         fn saturating_sub(m: NonZeroU16, s: usize) -> NonZeroU16 {
             NonZeroU16::new(std::cmp::max(
                 1,
@@ -509,6 +558,15 @@ fn saturating_sub(m: NonZeroU16, s: usize) -> NonZeroU16 {
             }
         };
 
+        // Append empty lines for the status line. Its contents are written in update().
+        self.desired.status_line = (status_line_lines != 0).then(|| StatusLine {
+            start: self.desired.line_count(),
+            lines: layout.status_line.clone(),
+        });
+        for _ in 0..status_line_lines {
+            self.desired.add_line();
+        }
+
         // Re-render our completions page if necessary. Limit the term size of the pager to the true
         // term size, minus the number of lines consumed by our string.
         pager.set_term_size(&Termsize::new(
@@ -531,7 +589,12 @@ fn saturating_sub(m: NonZeroU16, s: usize) -> NonZeroU16 {
                 });
 
         self.with_buffered_output(|zelf| {
-            zelf.update(vars, &layout.left_prompt, &layout.right_prompt);
+            zelf.update(
+                vars,
+                &layout.left_prompt,
+                &layout.right_prompt,
+                &layout.top_right_prompt,
+            );
         });
         self.save_status();
     }
@@ -622,6 +685,101 @@ pub fn command_line_y_given_cursor_y(&mut self, viewport_cursor_y: usize) -> usi
         })
     }
 
+    /// Return where the line with the right prompt is shown, or None if that is unknown.
+    pub fn prompt_line_position(&self) -> Option<PromptLinePosition> {
+        let viewport_y = self.viewport_y?;
+        let screen_width = self.actual.screen_width?;
+        if self.scrolled || self.actual.visible_prompt_lines == 0 {
+            return None;
+        }
+        let prompt_last_line = self.actual.visible_prompt_lines - 1;
+        if prompt_last_line >= self.actual.line_count() {
+            return None;
+        }
+        let left_prompt = self.actual_left_prompt.as_ref()?;
+        let prompt_last_line_width = LAYOUT_CACHE_SHARED
+            .lock()
+            .unwrap()
+            .calc_prompt_layout(left_prompt, None, usize::MAX)
+            .last_line_width;
+        Some(PromptLinePosition {
+            y: viewport_y + prompt_last_line,
+            used_width: prompt_last_line_width
+                + self
+                    .actual
+                    .line(prompt_last_line)
+                    .wcswidth_min_0(usize::MAX),
+            screen_width,
+            // The reader emits a newline after the command line, see cursor_is_wrapped_to_own_line().
+            next_prompt_y: viewport_y
+                + self.actual.cursor.y
+                + usize::from(!self.cursor_is_wrapped_to_own_line()),
+        })
+    }
+
+    /// Replace the right prompt on a line above the current prompt, which was shown at the given
+    /// position. Nothing is written unless the current prompt starts right below the command line,
+    /// since any output of the command may have scrolled or cleared the screen, and we can't tell
+    /// what is on the line now. Nor is anything written if the current prompt reaches the last line,
+    /// which may have scrolled the terminal. Returns whether the right prompt was written.
+    pub fn rewrite_right_prompt(
+        &mut self,
+        position: PromptLinePosition,
+        right_prompt: &wstr,
+        curr_termsize: Termsize,
+    ) -> bool {
+        let Some(viewport_y) = self.viewport_y else {
+            return false;
+        };
+        let screen_width = curr_termsize.width();
+        if viewport_y != position.next_prompt_y
+            || position.y >= viewport_y
+            || position.screen_width != screen_width
+            || viewport_y + self.actual.line_count().max(1) >= curr_termsize.height()
+        {
+            return false;
+        }
+        let cursor_y = viewport_y + self.actual.cursor.y;
+        let right_prompt_width = LAYOUT_CACHE_SHARED
+            .lock()
+            .unwrap()
+            .calc_prompt_layout(right_prompt, None, usize::MAX)
+            .last_line_width;
+        // Leave at least one column of space after the command line.
+        if position.used_width + 1 + right_prompt_width > screen_width {
+            return false;
+        }
+        let lines_up = cursor_y - position.y;
+        let mut out = BufferedOutputter::new(self.outp);
+        out.write_command(CursorMove(CardinalDirection::Up, lines_up));
+        out.write_bytes(b"\r");
+        if position.used_width > 0 {
+            out.write_command(CursorMove(CardinalDirection::Right, position.used_width));
+        }
+        out.write_command(ClearToEndOfLine);
+        let padding = screen_width - position.used_width - right_prompt_width;
+        if padding > 0 {
+            out.write_command(CursorMove(CardinalDirection::Right, padding));
+        }
+        out.write_wstr(right_prompt);
+        out.reset_text_face();
+        // As when rendering the right prompt, we don't know whether the cursor sticks to the last
+        // column, so move back onto the line before going to its start.
+        if right_prompt_width > 0 {
+            out.write_command(CursorMove(CardinalDirection::Left, right_prompt_width));
+        }
+        out.write_bytes(b"\r");
+        for _ in 0..lines_up {
+            out.write_command(CursorDown);
+        }
+        // The cursor-down sequence may be a newline, so go to the start of the line either way.
+        out.write_bytes(b"\r");
+        if self.actual.cursor.x > 0 {
+            out.write_command(CursorMove(CardinalDirection::Right, self.actual.cursor.x));
+        }
+        true
+    }
+
     pub fn offset_in_cmdline_given_cursor(
         &mut self,
         viewport_position: ViewportPosition,
@@ -1052,7 +1210,13 @@ fn with_buffered_output(&mut self, f: impl FnOnce(&mut Self)) {
     }
 
     /// Update the screen to match the desired output.
-    fn update(&mut self, vars: &dyn Environment, left_prompt: &wstr, right_prompt: &wstr) {
+    fn update(
+        &mut self,
+        vars: &dyn Environment,
+        left_prompt: &wstr,
+        right_prompt: &wstr,
+        top_right_prompt: &wstr,
+    ) {
         // Helper function to set a resolved color, using the caching resolver.
         let mut color_resolver = HighlightColorResolver::new();
         let mut set_color = |zelf: &mut Self, c| {
@@ -1069,6 +1233,20 @@ fn update(&mut self, vars: &dyn Environment, left_prompt: &wstr, right_prompt: &
         let right_prompt_width = cached_layouts
             .calc_prompt_layout(right_prompt, None, usize::MAX)
             .last_line_width;
+        let top_right_prompt_width = cached_layouts
+            .calc_prompt_layout(top_right_prompt, None, usize::MAX)
+            .last_line_width;
+        let status_line_widths: Vec<usize> = self
+            .desired
+            .status_line
+            .iter()
+            .flat_map(|status| &status.lines)
+            .map(|line| {
+                cached_layouts
+                    .calc_prompt_layout(line, None, usize::MAX)
+                    .last_line_width
+            })
+            .collect();
 
         // Figure out how many following lines we need to clear (probably 0).
         let actual_lines_before_reset = self.actual_lines_before_reset;
@@ -1111,6 +1289,7 @@ fn update(&mut self, vars: &dyn Environment, left_prompt: &wstr, right_prompt: &
                 .actual_left_prompt
                 .as_ref()
                 .is_none_or(|p| p != left_prompt)
+                || self.actual_top_right_prompt != top_right_prompt
                 || self.actual.visible_prompt_lines != self.desired.visible_prompt_lines);
 
         let prompt_last_line_screen_wide =
@@ -1138,7 +1317,27 @@ fn update(&mut self, vars: &dyn Environment, left_prompt: &wstr, right_prompt: &
                     if i == 0 {
                         self.write_command(Osc133PromptStart);
                     }
-                    self.write_str(&left_prompt[start..next_line]);
+                    if i == 0 && prompt_first_visible_line == 0 && top_right_prompt_width > 0 {
+                        // Output the top right prompt at the end of the first line, before its
+                        // line separator. Like the right prompt, go back from the last column by
+                        // moving left before the carriage return, in case the cursor wrapped.
+                        let separator = next_line - 1;
+                        self.write_str(&left_prompt[start..separator]);
+                        self.write_str(L!("\r"));
+                        self.write_command(CursorMove(
+                            CardinalDirection::Right,
+                            screen_width.unwrap() - top_right_prompt_width,
+                        ));
+                        self.write_str(top_right_prompt);
+                        self.write_command(CursorMove(
+                            CardinalDirection::Left,
+                            top_right_prompt_width,
+                        ));
+                        self.write_str(L!("\r"));
+                        self.write_str(&left_prompt[separator..next_line]);
+                    } else {
+                        self.write_str(&left_prompt[start..next_line]);
+                    }
                     start = next_line;
                 }
             } else {
@@ -1148,6 +1347,7 @@ fn update(&mut self, vars: &dyn Environment, left_prompt: &wstr, right_prompt: &
             self.write_str(&left_prompt[start..]);
             self.write_command(Osc133PromptEnd);
             self.actual_left_prompt = Some(left_prompt.to_owned());
+            self.actual_top_right_prompt = top_right_prompt.to_owned();
             self.actual.cursor.x = prompt_last_line_width;
             self.actual.cursor.y = prompt_last_line;
             if prompt_last_line_should_wrap {
@@ -1171,10 +1371,49 @@ fn s_line(zelf: &Screen, i: usize) -> &Line {
             zelf.actual.line(i)
         }
 
+        // The status line needs to be rewritten if it moved or changed, or if its lines may have
+        // been cleared.
+        let status_line_unchanged =
+            self.actual.status_line == self.desired.status_line && !need_clear_lines;
+        let actual_status_rows = self
+            .actual
+            .status_line
+            .as_ref()
+            .map_or(0..0, StatusLine::rows);
+
         // Output all lines.
         let commandline_start = prompt_last_line + if prompt_last_line_screen_wide { 1 } else { 0 };
         for i in commandline_start..self.desired.line_count() {
             self.actual.create_line(i);
+            // If this is the last line, maybe we should clear the screen.
+            // Don't issue clr_eos if we think the cursor will end up in the last column - see #6951.
+            let should_clear_screen_this_line = need_clear_screen
+                && i + 1 == self.desired.line_count()
+                && !(self.desired.cursor.x == 0
+                    && self.desired.cursor.y == self.desired.line_count());
+
+            let status_line_idx = self
+                .desired
+                .status_line
+                .as_ref()
+                .and_then(|status| status.rows().contains(&i).then(|| i - status.start));
+            if let Some(idx) = status_line_idx {
+                if !status_line_unchanged || has_cleared_screen || should_clear_screen_this_line {
+                    let line = self.desired.status_line.as_ref().unwrap().lines[idx].clone();
+                    set_color(self, HighlightSpec::new());
+                    self.r#move(0, i);
+                    self.write_command(if should_clear_screen_this_line {
+                        ClearToEndOfScreen
+                    } else {
+                        ClearToEndOfLine
+                    });
+                    has_cleared_screen |= should_clear_screen_this_line;
+                    self.write_str(&line);
+                    self.actual.cursor.x = status_line_widths[idx];
+                }
+                continue;
+            }
+
             let is_prompt_line = is_prompt_visible && i == prompt_last_line;
             let start_pos = if is_prompt_line {
                 prompt_last_line_width
@@ -1184,18 +1423,14 @@ fn s_line(zelf: &Screen, i: usize) -> &Line {
             let mut current_width = 0;
             let mut has_cleared_line = false;
 
-            // If this is the last line, maybe we should clear the screen.
-            // Don't issue clr_eos if we think the cursor will end up in the last column - see #6951.
-            let should_clear_screen_this_line = need_clear_screen
-                && i + 1 == self.desired.line_count()
-                && !(self.desired.cursor.x == 0
-                    && self.desired.cursor.y == self.desired.line_count());
-
             // skip_remaining is how many columns are unchanged on this line.
             // Note that skip_remaining is a width, not a character count.
             let mut skip_remaining = start_pos;
 
-            let previously_prompt_line = self.actual.visible_prompt_lines > i + 1;
+            // Lines that used to hold (parts of) the prompt or the status line are not tracked in
+            // our model of the actual screen, so they must be cleared.
+            let previously_prompt_line =
+                self.actual.visible_prompt_lines > i + 1 || actual_status_rows.contains(&i);
 
             let shared_prefix = if self.scrolled || previously_prompt_line {
                 0
@@ -1952,6 +2187,10 @@ struct ScreenLayout {
     pub(crate) left_prompt_space: usize,
     // The right prompt.
     pub(crate) right_prompt: WString,
+    // The right prompt on the first line of a multi-line left prompt.
+    pub(crate) top_right_prompt: WString,
+    // The lines of the status line below the command line.
+    pub(crate) status_line: Vec<WString>,
     // The autosuggestion.
     pub(crate) autosuggestion: WString,
 }
@@ -1964,6 +2203,8 @@ fn compute_layout(
     screen_viewport_y: Option<usize>,
     left_untrunc_prompt: &wstr,
     right_untrunc_prompt: &wstr,
+    top_right_untrunc_prompt: &wstr,
+    status_untrunc_line: &wstr,
     commandline_before_suggestion: &wstr,
     colors: &mut Vec<HighlightSpec>,
     indent: &mut Vec<i32>,
@@ -1984,9 +2225,44 @@ fn compute_layout(
         screen_width,
     );
 
+    let mut top_right_prompt = WString::new();
+    let top_right_prompt_layout = LAYOUT_CACHE_SHARED.lock().unwrap().calc_prompt_layout(
+        top_right_untrunc_prompt,
+        Some(&mut top_right_prompt),
+        screen_width,
+    );
+
+    let mut status_line = WString::new();
+    LAYOUT_CACHE_SHARED.lock().unwrap().calc_prompt_layout(
+        status_untrunc_line,
+        Some(&mut status_line),
+        screen_width,
+    );
+    let status_line: Vec<WString> = if status_line.is_empty() {
+        vec![]
+    } else {
+        status_line
+            .as_char_slice()
+            .split(|&c| c == '\n' || c == '\x0C')
+            .map(|line| wstr::from_char_slice(line).to_owned())
+            .collect()
+    };
+
     let left_prompt_width = left_prompt_layout.last_line_width;
     let right_prompt_width = right_prompt_layout.last_line_width;
 
+    // The top right prompt is shown at the end of the first prompt line, if there are several
+    // and it fits. Unlike the right prompt it never competes with the command line for space.
+    let top_right_prompt_visible = left_prompt_layout.line_starts.len() > 1 && {
+        let first_line = &left_prompt[..left_prompt_layout.line_starts[1] - 1];
+        let first_line_width = LAYOUT_CACHE_SHARED
+            .lock()
+            .unwrap()
+            .calc_prompt_layout(first_line, None, usize::MAX)
+            .last_line_width;
+        first_line_width + top_right_prompt_layout.last_line_width <= screen_width
+    };
+
     // Get the width of the first line, and if there is more than one line.
     let first_command_line_width: usize = line_at_cursor(commandline_before_suggestion, 0)
         .chars()
@@ -2018,6 +2294,11 @@ fn compute_layout(
         left_prompt,
         left_prompt_space: left_prompt_width,
         left_prompt_lines: left_prompt_layout.line_starts.len(),
+        top_right_prompt: if top_right_prompt_visible {
+            top_right_prompt
+        } else {
+            WString::new()
+        },
         ..Default::default()
     };
 
@@ -2034,7 +2315,9 @@ fn compute_layout(
 
     let mut available_vertical_space = screen_viewport_y.map_or(
         1, // Cursor position report not implemented in the terminal?
-        |screen_viewport_y| screen_height.saturating_sub(screen_viewport_y + cursor_y),
+        |screen_viewport_y| {
+            screen_height.saturating_sub(screen_viewport_y + cursor_y + status_line.len())
+        },
     );
     let mut truncated_vertically = false;
     let mut suggestion_start = commandline_before_suggestion.len();
@@ -2159,6 +2442,7 @@ fn consumed_lines_or_truncated_suggestion(
         }
     }
     result.autosuggestion = autosuggestion;
+    result.status_line = status_line;
 
     result
 }
@@ -2462,6 +2746,8 @@ macro_rules! validate {
                         /*screen_viewport_y=*/ Some(0),
                         L!($left_untrunc_prompt),
                         L!($right_untrunc_prompt),
+                        L!(""),
+                        L!(""),
                         L!($commandline_before_suggestion),
                         &mut colors,
                         &mut indent,
@@ -2473,6 +2759,7 @@ macro_rules! validate {
                         left_prompt_lines: 1,
                         right_prompt: L!($right_prompt).to_owned(),
                         autosuggestion: L!($autosuggestion).to_owned(),
+                        ..Default::default()
                     }
                 );
                 indent
@@ -2642,4 +2929,46 @@ macro_rules! validate {
             )
         );
     }
+
+    #[test]
+    fn test_compute_layout_top_right_prompt_and_status_line() {
+        let layout = |screen_width, left_prompt: &wstr, top_right_prompt: &wstr| {
+            compute_layout(
+                '…',
+                screen_width,
+                /*screen_height=*/ 24,
+                /*screen_viewport_y=*/ Some(0),
+                left_prompt,
+                L!(""),
+                top_right_prompt,
+                L!("jobs: 1\nlast: 2s and then some"),
+                L!(""),
+                &mut vec![],
+                &mut vec![],
+                L!(""),
+            )
+        };
+
+        // The top right prompt needs a multi-line left prompt.
+        assert_eq!(layout(80, L!("left>"), L!("<top")).top_right_prompt, L!(""));
+        assert_eq!(
+            layout(80, L!("first\nleft>"), L!("<top")).top_right_prompt,
+            L!("<top")
+        );
+        // It is hidden if it does not fit next to the first line.
+        assert_eq!(
+            layout(10, L!("first\nleft>"), L!("<top")).top_right_prompt,
+            L!("<top")
+        );
+        assert_eq!(
+            layout(8, L!("first\nleft>"), L!("<top")).top_right_prompt,
+            L!("")
+        );
+
+        // Each line of the status line is truncated to the screen width, like prompts are.
+        assert_eq!(
+            layout(12, L!("left>"), L!("")).status_line,
+            vec![L!("jobs: 1").to_owned(), L!("…d then some").to_owned()]
+        );
+    }
 }
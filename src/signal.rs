@@ -13,8 +13,8 @@
 use errno::{errno, set_errno};
 use nix::sys::signal::{SaFlags, SigAction, SigHandler, SigSet, SigmaskHow, sigprocmask};
 use std::sync::{
-    LazyLock,
-    atomic::{AtomicI32, Ordering},
+    LazyLock, Mutex,
+    atomic::{AtomicBool, AtomicI32, Ordering},
 };
 
 /// Store the "main" pid. This allows us to reliably determine if we are in a forked child.
@@ -131,6 +131,10 @@ pub fn signal_reset_handlers() {
     let act = SigAction::new(SigHandler::SigDfl, SaFlags::empty(), SigSet::empty()).into();
 
     for data in SIGNAL_TABLE.iter() {
+        // Signals ignored via `trap '' SIG` stay ignored in child processes.
+        if signal_is_ignored(data.signal) {
+            continue;
+        }
         if data.signal == libc::SIGHUP {
             let mut oact = MaybeUninit::uninit();
             unsafe { libc::sigaction(libc::SIGHUP, std::ptr::null(), oact.as_mut_ptr()) };
@@ -278,7 +282,7 @@ pub fn signal_handle(sig: Signal) {
     sigaction(sig, &act, std::ptr::null_mut());
 }
 
-pub static signals_to_default: LazyLock<libc::sigset_t> = LazyLock::new(|| {
+static SIGNALS_TO_DEFAULT: LazyLock<libc::sigset_t> = LazyLock::new(|| {
     let mut set = MaybeUninit::uninit();
     unsafe { libc::sigemptyset(set.as_mut_ptr()) };
     for data in SIGNAL_TABLE.iter() {
@@ -298,6 +302,81 @@ pub fn signal_handle(sig: Signal) {
     unsafe { set.assume_init() }
 });
 
+/// Return the set of signals whose handlers should be reset to the default in spawned processes.
+/// Signals ignored via `trap '' SIG` are left out, so they stay ignored.
+pub fn signals_to_default() -> libc::sigset_t {
+    let mut set = *SIGNALS_TO_DEFAULT;
+    for (sig, ignored) in IGNORED_SIGNALS.iter().enumerate() {
+        if ignored.load(Ordering::Relaxed) {
+            unsafe { libc::sigdelset(&mut set, sig as libc::c_int) };
+        }
+    }
+    set
+}
+
+const IGNORED_SIGNAL_COUNT: usize = 65;
+
+/// Signals that `trap` has asked to be ignored.
+/// This is inspected after fork, so it must be async-signal safe to read.
+static IGNORED_SIGNALS: [AtomicBool; IGNORED_SIGNAL_COUNT] =
+    [const { AtomicBool::new(false) }; IGNORED_SIGNAL_COUNT];
+
+/// The dispositions signals had before `trap` first changed them.
+static SAVED_DISPOSITIONS: Mutex<Vec<(i32, libc::sigaction)>> = Mutex::new(Vec::new());
+
+/// Return whether the given signal is ignored via `trap '' SIG`.
+pub fn signal_is_ignored(sig: Signal) -> bool {
+    IGNORED_SIGNALS
+        .get(usize::from(sig))
+        .is_some_and(|b| b.load(Ordering::Relaxed))
+}
+
+/// Remember the current disposition of the given signal, so that a later
+/// [`signal_restore_disposition`] can put it back. Only the first call for each signal has an effect.
+pub fn signal_save_disposition(sig: Signal) {
+    let mut saved = SAVED_DISPOSITIONS.lock().unwrap();
+    if saved.iter().any(|(s, _)| *s == sig.code()) {
+        return;
+    }
+    let mut oact: libc::sigaction = unsafe { std::mem::zeroed() };
+    unsafe { libc::sigaction(sig.code(), std::ptr::null(), &mut oact) };
+    saved.push((sig.code(), oact));
+}
+
+/// Ignore the given signal, both in fish and in the processes it launches.
+/// SIGCHLD is never ignored, since that would prevent fish from reaping its children.
+pub fn signal_ignore(sig: Signal) {
+    if sig == libc::SIGCHLD {
+        return;
+    }
+    let Some(ignored) = IGNORED_SIGNALS.get(usize::from(sig)) else {
+        return;
+    };
+    signal_save_disposition(sig);
+    let mut act: libc::sigaction = unsafe { std::mem::zeroed() };
+    unsafe { libc::sigemptyset(&mut act.sa_mask) };
+    act.sa_sigaction = libc::SIG_IGN;
+    sigaction(sig.code(), &act, std::ptr::null_mut());
+    ignored.store(true, Ordering::Relaxed);
+}
+
+/// Undo [`signal_ignore`] and [`signal_save_disposition`], restoring the disposition the signal
+/// had before. If event handlers still observe the signal, fish's handler is installed instead.
+pub fn signal_restore_disposition(sig: Signal) {
+    if let Some(ignored) = IGNORED_SIGNALS.get(usize::from(sig)) {
+        ignored.store(false, Ordering::Relaxed);
+    }
+    let mut saved = SAVED_DISPOSITIONS.lock().unwrap();
+    if let Some(idx) = saved.iter().position(|(s, _)| *s == sig.code()) {
+        let (_, oact) = saved.remove(idx);
+        sigaction(sig.code(), &oact, std::ptr::null_mut());
+    }
+    drop(saved);
+    if is_signal_observed(sig.code()) {
+        signal_handle(sig);
+    }
+}
+
 /// Ensure we did not inherit any blocked signals. See issue #3964.
 pub fn signal_unblock_all() {
     sigprocmask(SigmaskHow::SIG_SETMASK, Some(&SigSet::empty()), None).unwrap();
@@ -517,6 +596,14 @@ pub fn desc(&self) -> &'static wstr {
     pub fn code(&self) -> i32 {
         self.0.into()
     }
+
+    /// Return all known signals, ordered by their code. Aliases are only listed once.
+    pub fn all() -> Vec<Signal> {
+        let mut result: Vec<Signal> = SIGNAL_TABLE.iter().map(|entry| entry.signal).collect();
+        result.sort();
+        result.dedup();
+        result
+    }
     /// Parses a string into the equivalent [`Signal`] sharing the same name.
     /// Accepts both `SIGABC` and `ABC` to match against `Signal::SIGABC`. If the signal name is not
     /// recognized, `None` is returned.
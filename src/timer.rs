@@ -14,11 +14,15 @@
 //! but it's still the best we can do because we don't know how long of a time might elapse between
 //! `TimerSnapshot` instances and need to avoid rollover.
 
+use std::cell::RefCell;
 use std::fmt::Write as FmtWrite;
 use std::io::Write;
 use std::time::{Duration, Instant};
 
+use crate::common::wcs2bytes;
 use crate::nix::{RUsage, getrusage};
+use crate::prelude::*;
+use crate::proc::{Pid, ProcRusage};
 
 enum Unit {
     Minutes,
@@ -33,14 +37,46 @@ struct TimerSnapshot {
     cpu_children: libc::rusage,
 }
 
+/// An external process which exited while being timed.
+struct TimedProcess {
+    pid: Pid,
+    name: WString,
+    usage: ProcRusage,
+}
+
+thread_local! {
+    /// For each active timer which reports per-process usage, the processes that exited so far.
+    static TIMED_PROCESSES: RefCell<Vec<Vec<TimedProcess>>> = const { RefCell::new(Vec::new()) };
+}
+
 /// Create a `TimerSnapshot` and return a `PrintElapsedOnDrop` object that will print upon
 /// being dropped the delta between now and the time that it is dropped at.
-pub fn push_timer() -> PrintElapsedOnDrop {
+/// If `per_process` is set, it also prints the resource usage of each external process that
+/// exited in the meantime.
+pub fn push_timer(per_process: bool) -> PrintElapsedOnDrop {
+    if per_process {
+        TIMED_PROCESSES.with_borrow_mut(|timers| timers.push(Vec::new()));
+    }
     PrintElapsedOnDrop {
         start: TimerSnapshot::take(),
+        per_process,
     }
 }
 
+/// Record the resource usage of an external process which was just reaped, for any active timers
+/// which report per-process usage.
+pub fn record_process_usage(pid: Pid, name: &wstr, usage: &ProcRusage) {
+    TIMED_PROCESSES.with_borrow_mut(|timers| {
+        for processes in timers.iter_mut() {
+            processes.push(TimedProcess {
+                pid,
+                name: name.to_owned(),
+                usage: *usage,
+            });
+        }
+    });
+}
+
 impl TimerSnapshot {
     pub fn take() -> TimerSnapshot {
         TimerSnapshot {
@@ -124,9 +160,58 @@ pub fn get_delta(t1: &TimerSnapshot, t2: &TimerSnapshot, verbose: bool) -> Strin
     }
 }
 
+/// Returns a formatted table of the resource usage of each process, or an empty string if there
+/// are none.
+fn get_process_lines(processes: &[TimedProcess]) -> String {
+    let mut output = String::new();
+    let Some(max_cpu) = processes
+        .iter()
+        .map(|p| p.usage.user.max(p.usage.sys))
+        .max()
+    else {
+        return output;
+    };
+    // Share the same unit for all CPU times, so they can be compared at a glance.
+    let cpu_unit = Unit::for_micros(max_cpu.as_micros() as i64);
+    let unit = cpu_unit.short_name();
+    let cpu_width = 7 + unit.len();
+    let max_rss: Vec<String> = processes
+        .iter()
+        .map(|p| format!("{} KiB", p.usage.max_rss_kb))
+        .collect();
+    let rss_width = max_rss
+        .iter()
+        .map(String::len)
+        .max()
+        .unwrap()
+        .max("max rss".len());
+
+    write!(
+        output,
+        "\n    pid  {:<cpu_width$}  {:<cpu_width$}  {:>rss_width$}  command",
+        "usr time", "sys time", "max rss"
+    )
+    .unwrap();
+    for (p, max_rss) in processes.iter().zip(&max_rss) {
+        let usr_time = cpu_unit.convert_micros(p.usage.user.as_micros() as i64);
+        let sys_time = cpu_unit.convert_micros(p.usage.sys.as_micros() as i64);
+        let name = String::from_utf8_lossy(&wcs2bytes(&p.name)).into_owned();
+        write!(
+            output,
+            "\n{:>7}  {usr_time:6.2} {unit}  {sys_time:6.2} {unit}  {max_rss:>rss_width$}  {name}",
+            p.pid
+        )
+        .unwrap();
+    }
+    output += "\n";
+
+    output
+}
+
 /// When dropped, prints to stderr the time that has elapsed since it was initialized.
 pub struct PrintElapsedOnDrop {
     start: TimerSnapshot,
+    per_process: bool,
 }
 
 impl Drop for PrintElapsedOnDrop {
@@ -135,7 +220,11 @@ fn drop(&mut self) {
 
         // Well, this is awkward. By defining `time` as a decorator and not a built-in, there's
         // no associated stream for its output!
-        let output = TimerSnapshot::get_delta(&self.start, &end, true);
+        let mut output = TimerSnapshot::get_delta(&self.start, &end, true);
+        if self.per_process {
+            let processes = TIMED_PROCESSES.with_borrow_mut(|timers| timers.pop().unwrap());
+            output += &get_process_lines(&processes);
+        }
         let mut stderr = std::io::stderr().lock();
         // There is no bubbling up of errors in a Drop implementation, and it's absolutely forbidden
         // to panic.
@@ -186,7 +275,9 @@ fn convert_micros(&self, micros: i64) -> f64 {
 
 #[cfg(test)]
 mod tests {
-    use super::TimerSnapshot;
+    use super::{TimedProcess, TimerSnapshot, get_process_lines};
+    use crate::prelude::*;
+    use crate::proc::{Pid, ProcRusage};
     use std::time::Duration;
 
     #[test]
@@ -217,4 +308,36 @@ fn timer_format_and_alignment() {
         let actual = TimerSnapshot::get_delta(&t1, &t2, true);
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn timer_process_lines() {
+        assert_eq!(get_process_lines(&[]), "");
+
+        let processes = [
+            TimedProcess {
+                pid: Pid::new(123),
+                name: L!("yes").to_owned(),
+                usage: ProcRusage {
+                    user: Duration::from_millis(1500),
+                    sys: Duration::from_millis(20),
+                    max_rss_kb: 1024,
+                },
+            },
+            TimedProcess {
+                pid: Pid::new(4567),
+                name: L!("grep").to_owned(),
+                usage: ProcRusage {
+                    user: Duration::from_micros(500),
+                    sys: Duration::ZERO,
+                    max_rss_kb: 123456,
+                },
+            },
+        ];
+        let expected = r#"
+    pid  usr time     sys time        max rss  command
+    123    1.50 secs    0.02 secs    1024 KiB  yes
+   4567    0.00 secs    0.00 secs  123456 KiB  grep
+"#;
+        assert_eq!(get_process_lines(&processes), expected);
+    }
 }
@@ -98,6 +98,10 @@ pub struct PipeOrRedir {
     // For example &| or &>
     pub stderr_merge: bool,
 
+    // For a redirection like {var}>file, which opens the file on a newly allocated fd and stores
+    // its number in var, the range of the variable name.
+    pub fd_var: Option<Range<usize>>,
+
     // Number of characters consumed when parsing the string.
     pub consumed: usize,
 }
@@ -407,7 +411,8 @@ fn next(&mut self) -> Option<Self::Item> {
                 Some(result)
             }
             '{' if self.brace_statement_parser.as_ref()
-                    .is_some_and(|parser| parser.at_command_position) =>
+                    .is_some_and(|parser| parser.at_command_position)
+                    && PipeOrRedir::try_from(buff).is_err() =>
             {
                 self.brace_statement_parser.as_mut().unwrap().unclosed_brace_statements += 1;
                 let mut result = Tok::new(TokenType::LeftBrace);
@@ -514,9 +519,11 @@ fn next(&mut self) -> Option<Self::Item> {
                 }
             }
                 _ => {
-                    // Maybe a redirection like '2>&1', maybe a pipe like 2>|, maybe just a string.
+                    // Maybe a redirection like '2>&1' or '{var}>', maybe a pipe like 2>|, maybe
+                    // just a string. Note '{var}' must be a valid variable name and directly followed
+                    // by '>' or '<', so brace expansions like '{a,b}>file' stay strings.
                     let error_location = self.token_cursor;
-                    let redir_or_pipe = if this_char.is_ascii_digit() {
+                    let redir_or_pipe = if this_char.is_ascii_digit() || this_char == '{' {
                         PipeOrRedir::try_from(buff).ok()
                     } else {
                         None
@@ -978,12 +985,37 @@ impl TryFrom<&wstr> for PipeOrRedir {
     ///     cmd &> file      redirection with stderr merge
     ///     cmd ^ file       caret (stderr) redirection, perhaps disabled via feature flags
     ///     cmd ^^ file      caret (stderr) redirection, perhaps disabled via feature flags
+    ///     cmd {var}> file  redirection to a newly allocated fd, stored in var
+    ///     cmd {var}>&-     closing the fd stored in var
     /// ```
     fn try_from(buff: &wstr) -> Result<PipeOrRedir, ()> {
+        // Extract a leading variable name in braces, like {var}>.
+        let mut fd_var = None;
+        let mut fd_start = 0;
+        if buff.char_at(0) == '{' {
+            let len = buff[1..]
+                .chars()
+                .take_while(|&c| valid_var_name_char(c))
+                .count();
+            if len == 0 || buff.char_at(len + 1) != '}' {
+                return Err(());
+            }
+            fd_var = Some(1..len + 1);
+            fd_start = len + 2;
+        }
+
         // Extract a range of leading fd.
-        let mut cursor = buff.chars().take_while(|c| c.is_ascii_digit()).count();
-        let fd_buff = &buff[..cursor];
+        let mut cursor = fd_start
+            + buff[fd_start..]
+                .chars()
+                .take_while(|c| c.is_ascii_digit())
+                .count();
+        let fd_buff = &buff[fd_start..cursor];
         let has_fd = !fd_buff.is_empty();
+        if fd_var.is_some() && (has_fd || !matches!(buff.char_at(cursor), '>' | '<')) {
+            // Like {var}2> or {var}|
+            return Err(());
+        }
 
         // Try consuming a given character.
         // Return true if consumed. On success, advances cursor.
@@ -1008,6 +1040,7 @@ fn try_from(buff: &wstr) -> Result<PipeOrRedir, ()> {
             is_pipe: false,
             mode: RedirectionMode::Overwrite,
             stderr_merge: false,
+            fd_var: None,
             consumed: 0,
         };
         match c {
@@ -1114,6 +1147,14 @@ fn try_from(buff: &wstr) -> Result<PipeOrRedir, ()> {
             }
         }
 
+        if fd_var.is_some() {
+            if result.is_pipe {
+                // Like {var}>|
+                return Err(());
+            }
+            result.fd_var = fd_var;
+        }
+
         result.consumed = cursor;
         assert!(
             result.consumed > 0,
@@ -1380,5 +1421,21 @@ macro_rules! get_redir_mode {
         assert_eq!(get_redir_mode!("2>&3"), RedirectionMode::Fd);
         assert_eq!(get_redir_mode!("3<&0"), RedirectionMode::Fd);
         assert_eq!(get_redir_mode!("3</tmp/filetxt"), RedirectionMode::Input);
+
+        assert_eq!(pipe_or_redir!("{fd}>").fd_var, Some(1..3));
+        assert_eq!(get_redir_mode!("{fd}>>"), RedirectionMode::Append);
+        assert_eq!(get_redir_mode!("{fd}<"), RedirectionMode::Input);
+        assert_eq!(get_redir_mode!("{fd}>&-"), RedirectionMode::Fd);
+        assert_eq!(pipe_or_redir!("{fd}>&-").consumed, 6);
+        assert!(pipe_or_redir!(">").fd_var.is_none());
+        for s in [
+            "{}>", "{fd}", "{fd}2>", "{fd}|", "{fd}>|", "{fd}&>", "{fd-x}>", "{fd>", "{a,b}>",
+            "{fd} >", "{fd}x>",
+        ] {
+            assert!(
+                PipeOrRedir::try_from(&*WString::from_str(s)).is_err(),
+                "{s} should not parse"
+            );
+        }
     }
 }
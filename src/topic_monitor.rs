@@ -31,6 +31,7 @@
 use std::os::fd::AsRawFd;
 use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::Instant;
 #[cfg(target_os = "linux")]
 use std::{cell::UnsafeCell, pin::Pin};
 
@@ -259,6 +260,50 @@ pub fn wait(&self) {
         }
     }
 
+    /// Wait for a post, giving up once `deadline` has passed.
+    /// Return true if there was a post, false on timeout.
+    pub fn wait_until(&self, deadline: Instant) -> bool {
+        match self {
+            #[cfg(target_os = "linux")]
+            Self::Semaphore(sem) => {
+                // sem_timedwait measures against the realtime clock.
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                let mut abstime: libc::timespec = unsafe { std::mem::zeroed() };
+                unsafe { libc::clock_gettime(libc::CLOCK_REALTIME, &mut abstime) };
+                let nanos = abstime.tv_nsec as u64 + u64::from(remaining.subsec_nanos());
+                abstime.tv_sec += (remaining.as_secs() + nanos / 1_000_000_000) as libc::time_t;
+                abstime.tv_nsec = (nanos % 1_000_000_000) as _;
+                loop {
+                    match unsafe { libc::sem_timedwait(sem.get(), &abstime) } {
+                        0.. => return true,
+                        _ if Errno::last() == Errno::EINTR => continue,
+                        _ if Errno::last() == Errno::ETIMEDOUT => return false,
+                        _ => self.die("sem_timedwait"),
+                    }
+                }
+            }
+            Self::Pipes(pipes) => {
+                let fd = pipes.read.as_raw_fd();
+                loop {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if !FdReadableSet::is_fd_readable(fd, Timeout::Duration(remaining)) {
+                        if remaining.is_zero() {
+                            return false;
+                        }
+                        continue;
+                    }
+                    let mut ignored: u8 = 0;
+                    match unistd::read(&pipes.read, std::slice::from_mut(&mut ignored)) {
+                        Ok(1) => return true,
+                        Ok(_) => continue,
+                        Err(Errno::EINTR) | Err(Errno::EAGAIN) => continue,
+                        Err(_) => self.die("read"),
+                    }
+                }
+            }
+        }
+    }
+
     pub fn die(&self, msg: &str) {
         perror(msg);
         panic!("die");
@@ -466,7 +511,12 @@ pub fn generation_for_topic(self: &TopicMonitor, topic: Topic) -> Generation {
     /// indicating we should become the reader. Now it is our responsibility to wait on the
     /// semaphore and notify on a change via the condition variable. If `gens` is current, and
     /// there is already a reader, then wait until the reader notifies us and try again.
-    fn try_update_gens_maybe_becoming_reader(&self, gens: &mut GenerationsList) -> bool {
+    /// If `deadline` passes while waiting for the reader, return false without updating `gens`.
+    fn try_update_gens_maybe_becoming_reader(
+        &self,
+        gens: &mut GenerationsList,
+        deadline: Option<Instant>,
+    ) -> bool {
         let mut become_reader = false;
         let mut data = self.data_.lock().unwrap();
         loop {
@@ -484,7 +534,15 @@ fn try_update_gens_maybe_becoming_reader(&self, gens: &mut GenerationsList) -> b
             // reader.
             if data.has_reader {
                 // We already have a reader, wait for it to notify us and loop again.
-                data = self.data_notifier_.wait(data).unwrap();
+                let Some(deadline) = deadline else {
+                    data = self.data_notifier_.wait(data).unwrap();
+                    continue;
+                };
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    break;
+                }
+                data = self.data_notifier_.wait_timeout(data, remaining).unwrap().0;
                 continue;
             } else {
                 // We will try to become the reader.
@@ -521,12 +579,19 @@ fn try_update_gens_maybe_becoming_reader(&self, gens: &mut GenerationsList) -> b
         become_reader
     }
 
-    /// Wait for some entry in the list of generations to change.
-    /// Return the new gens.
-    fn await_gens(&self, input_gens: &GenerationsList) -> GenerationsList {
+    /// Wait for some entry in the list of generations to change, or for `deadline` to pass.
+    /// Return the new gens, which are unchanged on timeout.
+    fn await_gens(
+        &self,
+        input_gens: &GenerationsList,
+        deadline: Option<Instant>,
+    ) -> GenerationsList {
         let mut gens = input_gens.clone();
         while &gens == input_gens {
-            let become_reader = self.try_update_gens_maybe_becoming_reader(&mut gens);
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
+            }
+            let become_reader = self.try_update_gens_maybe_becoming_reader(&mut gens, deadline);
             if become_reader {
                 // Now we are the reader. Read from the pipe, and then update with any changes.
                 // Note we no longer hold the lock.
@@ -536,7 +601,27 @@ fn await_gens(&self, input_gens: &GenerationsList) -> GenerationsList {
                 );
 
                 // Wait to be woken up.
-                self.sema_.wait();
+                match deadline {
+                    None => self.sema_.wait(),
+                    Some(deadline) => {
+                        // On timeout, stop waiting by clearing the wakeup bit. If that fails, a
+                        // post() arrived in the meantime and will post (or has posted) to the
+                        // semaphore, which we must consume.
+                        if !self.sema_.wait_until(deadline)
+                            && self
+                                .status_
+                                .compare_exchange(
+                                    STATUS_NEEDS_WAKEUP,
+                                    0,
+                                    Ordering::SeqCst,
+                                    Ordering::SeqCst,
+                                )
+                                .is_err()
+                        {
+                            self.sema_.wait();
+                        }
+                    }
+                }
 
                 // We are finished waiting. We must stop being the reader, and post on the condition
                 // variable to wake up any other threads waiting for us to finish reading.
@@ -558,6 +643,15 @@ fn await_gens(&self, input_gens: &GenerationsList) -> GenerationsList {
     /// Return true if some topic changed, false if none did.
     /// On a true return, this updates the generation list `gens`.
     pub fn check(&self, gens: &GenerationsList, wait: bool) -> bool {
+        self.check_impl(gens, wait, None)
+    }
+
+    /// Like check() with waiting, but give up once `deadline` has passed.
+    pub fn check_until(&self, gens: &GenerationsList, deadline: Instant) -> bool {
+        self.check_impl(gens, true, Some(deadline))
+    }
+
+    fn check_impl(&self, gens: &GenerationsList, wait: bool, deadline: Option<Instant>) -> bool {
         if !gens.any_valid() {
             return false;
         }
@@ -580,12 +674,12 @@ pub fn check(&self, gens: &GenerationsList, wait: bool) -> bool {
             }
 
             // If we're not waiting, or something changed, then we're done.
-            if !wait || changed {
+            if !wait || changed || deadline.is_some_and(|deadline| Instant::now() >= deadline) {
                 break;
             }
 
             // Wait until our gens change.
-            current = self.await_gens(&current);
+            current = self.await_gens(&current, deadline);
         }
         changed
     }
@@ -614,6 +708,7 @@ mod tests {
         Arc,
         atomic::{AtomicU32, Ordering},
     };
+    use std::time::{Duration, Instant};
 
     #[test]
     #[serial]
@@ -639,6 +734,19 @@ fn test_topic_monitor() {
         let changed = monitor.check(&gens, true /* wait */);
         assert!(changed);
         assert_eq!(gens.sigchld.get(), 2);
+
+        // Waiting with a deadline gives up if nothing is posted.
+        let start = Instant::now();
+        let changed = monitor.check_until(&gens, start + Duration::from_millis(50));
+        assert!(!changed);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert_eq!(gens.sigchld.get(), 2);
+
+        // A post after timing out is still seen.
+        monitor.post(t);
+        let changed = monitor.check_until(&gens, Instant::now() + Duration::from_secs(10));
+        assert!(changed);
+        assert_eq!(gens.sigchld.get(), 3);
     }
 
     #[test]
@@ -2,20 +2,21 @@
 //! and reclaiming it after.
 
 use crate::common::{self, safe_write_loop};
-use crate::env::Environment;
+use crate::env::{Environment, FISH_TERMINAL_COLOR_THEME_VAR};
 use crate::env_dispatch::MIDNIGHT_COMMANDER_SID;
 use crate::flog::{flog, flogf};
 use crate::global_safety::RelaxedAtomicBool;
 use crate::job_group::JobGroup;
 use crate::prelude::*;
 use crate::proc::JobGroupRef;
+use crate::terminal::TerminalCommand::Osc0WindowTitle;
 use crate::terminal::TerminalCommand::{
     self, ApplicationKeypadModeDisable, ApplicationKeypadModeEnable, DecrstBracketedPaste,
     DecrstColorThemeReporting, DecrstFocusReporting, DecsetBracketedPaste,
     DecsetColorThemeReporting, DecsetFocusReporting, KittyKeyboardProgressiveEnhancementsDisable,
     KittyKeyboardProgressiveEnhancementsEnable, ModifyOtherKeysDisable, ModifyOtherKeysEnable,
 };
-use crate::terminal::{Output, Outputter};
+use crate::terminal::{BufferedOutputter, Output, Outputter};
 use crate::threads::assert_is_main_thread;
 use crate::wutil::{perror, wcstoi};
 use fish_widestring::ToWString;
@@ -24,7 +25,7 @@
 use nix::unistd::getpgrp;
 use std::os::fd::BorrowedFd;
 use std::sync::{
-    OnceLock,
+    Mutex, OnceLock,
     atomic::{AtomicBool, AtomicPtr, Ordering},
 };
 
@@ -60,6 +61,27 @@ pub fn xtversion() -> Option<&'static wstr> {
     XTVERSION.get().as_ref().map(|s| s.as_utfstr())
 }
 
+/// The outcome of the initial terminal query at startup.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum InitialQueryOutcome {
+    // Querying is disabled, e.g. because of a dumb terminal or the query-term feature flag.
+    Disabled,
+    // The terminal answered the Primary Device Attribute query.
+    Answered,
+    // The terminal did not answer in time.
+    TimedOut,
+    // The user interrupted the query.
+    Interrupted,
+}
+
+pub static INITIAL_QUERY_OUTCOME: OnceLock<InitialQueryOutcome> = OnceLock::new();
+
+/// Whether the terminal has answered a cursor position query.
+pub static CURSOR_POSITION_REPORTED: RelaxedAtomicBool = RelaxedAtomicBool::new(false);
+
+/// The most recent answer to the background color query, as sent by the terminal.
+pub static BACKGROUND_COLOR_RESPONSE: Mutex<Option<WString>> = Mutex::new(None);
+
 // Facts that affect how we communicate with the TTY.
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum TtyQuirks {
@@ -94,6 +116,20 @@ fn detect(vars: &dyn Environment, xtversion: &wstr) -> Self {
     }
 }
 
+impl TtyQuirks {
+    // A description for `status terminal-report`.
+    fn describe(self) -> WString {
+        use TtyQuirks::*;
+        match self {
+            None => L!("none").to_owned(),
+            PreCsiMidnightCommander => L!("pre-csi-midnight-commander").to_owned(),
+            PreKittyIterm2 => L!("pre-kitty-iterm2").to_owned(),
+            Tmux((major, minor)) => sprintf!("tmux %d.%d", major, minor),
+            Wezterm => L!("wezterm").to_owned(),
+        }
+    }
+}
+
 // Helper to determine which keyboard protocols to enable.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum ProtocolKind {
@@ -103,6 +139,18 @@ enum ProtocolKind {
     None, // No protocols
 }
 
+impl ProtocolKind {
+    // A description for `status terminal-report`.
+    fn describe(self) -> &'static wstr {
+        match self {
+            ProtocolKind::KittyKeyboard => L!("kitty-keyboard"),
+            ProtocolKind::Other => L!("modify-other-keys"),
+            ProtocolKind::WorkAroundWezTerm => L!("application-keypad"),
+            ProtocolKind::None => L!("none"),
+        }
+    }
+}
+
 // Commands to emit to enable or disable TTY protocols. Each of these contains
 // the full serialized command sequence as bytes. It's structured in this awkward
 // way so that we can use it from a signal handler - no need to allocate or deallocate
@@ -263,6 +311,84 @@ pub fn initialize_tty_protocols(vars: &dyn Environment) {
     }
 }
 
+/// A detected capability of the terminal, along with the query which detected it.
+pub struct TerminalReportEntry {
+    pub name: &'static wstr,
+    pub value: WString,
+    pub query: &'static wstr,
+}
+
+/// Return everything we know about the terminal, for `status terminal-report`.
+pub fn terminal_report(vars: &dyn Environment) -> Vec<TerminalReportEntry> {
+    let entry = |name, value, query| TerminalReportEntry { name, value, query };
+    let yes_no = |b: bool| if b { L!("yes") } else { L!("no") }.to_owned();
+    let or_unknown = |s: Option<&wstr>| s.unwrap_or(L!("unknown")).to_owned();
+
+    let outcome = match INITIAL_QUERY_OUTCOME.get() {
+        Some(InitialQueryOutcome::Disabled) => L!("disabled"),
+        Some(InitialQueryOutcome::Answered) => L!("answered"),
+        Some(InitialQueryOutcome::TimedOut) => L!("timed-out"),
+        Some(InitialQueryOutcome::Interrupted) => L!("interrupted"),
+        None => L!("not-queried"),
+    };
+    let term = vars.get(L!("TERM")).map(|term| term.as_string());
+    let background_color = BACKGROUND_COLOR_RESPONSE.lock().unwrap().clone();
+    let color_theme = vars
+        .get(FISH_TERMINAL_COLOR_THEME_VAR)
+        .map(|theme| theme.as_string());
+    let protocols = tty_protocols();
+
+    vec![
+        entry(L!("term"), or_unknown(term.as_deref()), L!("$TERM")),
+        entry(
+            L!("primary-device-attribute"),
+            outcome.to_owned(),
+            L!("CSI 0 c"),
+        ),
+        entry(L!("xtversion"), or_unknown(xtversion()), L!("CSI > 0 q")),
+        entry(
+            L!("kitty-keyboard"),
+            yes_no(KITTY_KEYBOARD_SUPPORTED.get() == Some(&true)),
+            L!("CSI ? u"),
+        ),
+        entry(
+            L!("scroll-content-up"),
+            yes_no(get_scroll_content_up_capability() == Some(true)),
+            L!("XTGETTCAP indn"),
+        ),
+        entry(
+            L!("os-name"),
+            or_unknown(TERMINAL_OS_NAME.get().and_then(|name| name.as_deref())),
+            L!("XTGETTCAP query-os-name"),
+        ),
+        entry(
+            L!("background-color"),
+            or_unknown(background_color.as_deref()),
+            L!("OSC 11 ; ?"),
+        ),
+        entry(
+            L!("color-theme"),
+            or_unknown(color_theme.as_deref()),
+            L!("OSC 11 ; ?"),
+        ),
+        entry(
+            L!("cursor-position-report"),
+            yes_no(CURSOR_POSITION_REPORTED.load()),
+            L!("CSI 6 n"),
+        ),
+        entry(
+            L!("quirks"),
+            or_unknown(protocols.map(|p| p.quirks.describe()).as_deref()),
+            L!("CSI > 0 q, $MC_SID"),
+        ),
+        entry(
+            L!("keyboard-protocol"),
+            or_unknown(protocols.map(|p| p.quirks.safe_get_supported_protocol().describe())),
+            L!("CSI ? u"),
+        ),
+    ]
+}
+
 // A marker of the current state of the tty protocols.
 static TTY_PROTOCOLS_ACTIVE: AtomicBool = AtomicBool::new(false);
 
@@ -346,6 +472,19 @@ pub fn safe_mark_tty_invalid() {
     TTY_INVALID.store(true);
 }
 
+/// The window title most recently written for the shell itself, restored after a job's title.
+static SHELL_WINDOW_TITLE: Mutex<Vec<WString>> = Mutex::new(vec![]);
+
+/// Record the window title that fish wrote for itself.
+pub fn set_shell_window_title(title: &[WString]) {
+    *SHELL_WINDOW_TITLE.lock().unwrap() = title.to_vec();
+}
+
+/// Return whether fish has written a window title for itself, which can be restored after a job's.
+pub fn have_shell_window_title() -> bool {
+    !SHELL_WINDOW_TITLE.lock().unwrap().is_empty()
+}
+
 // Allows transferring the tty to a job group, while it runs, in a scoped fashion.
 // This has several responsibilities:
 //   - Invoking tcsetpgrp() to transfer the tty to the job group.
@@ -354,6 +493,7 @@ pub fn safe_mark_tty_invalid() {
 //     then it stops in the background, we want to record the tty modes
 //     it has in the job, so that we can restore them when the job is resumed.
 //   - Managing enabling and disabling terminal protocols (bracketed paste, etc).
+//   - Showing the title of the job while it owns the tty, and restoring the shell's title after.
 //  Note it only ever makes sense to run this on the main thread.
 pub struct TtyHandoff {
     // The job group which owns the tty, or empty if none.
@@ -367,6 +507,8 @@ pub struct TtyHandoff {
     tty_protocols_applied: bool,
     // Called after writing to the TTY.
     on_write: fn(),
+    // Whether we wrote the title of the owning job, so the shell's title must be restored.
+    job_title_written: bool,
 }
 
 impl TtyHandoff {
@@ -377,6 +519,7 @@ pub fn new(on_write: fn()) -> Self {
             tty_protocols_initial: protocols_active,
             tty_protocols_applied: protocols_active,
             on_write,
+            job_title_written: false,
         }
     }
 
@@ -407,6 +550,17 @@ pub fn to_job_group(&mut self, jg: &JobGroupRef) {
         }
     }
 
+    /// Show the given window title while the job group owns the terminal, if we transferred it.
+    /// The shell's title is restored when the terminal is reclaimed, so nothing is shown unless
+    /// the shell has a title of its own.
+    pub fn set_job_title(&mut self, title: &[WString]) {
+        if self.owner.is_none() || title.is_empty() || !have_shell_window_title() {
+            return;
+        }
+        BufferedOutputter::new(Outputter::stdoutput()).write_command(Osc0WindowTitle(title));
+        self.job_title_written = true;
+    }
+
     /// Save the current tty modes into the owning job group, if we are transferred.
     pub fn save_tty_modes(&mut self) {
         let Some(ref mut owner) = self.owner else {
@@ -584,6 +738,14 @@ fn drop(&mut self) {
             }
             self.owner = None;
         }
+        if self.job_title_written {
+            let title = SHELL_WINDOW_TITLE.lock().unwrap();
+            if !title.is_empty() {
+                BufferedOutputter::new(Outputter::stdoutput())
+                    .write_command(Osc0WindowTitle(&title));
+            }
+            self.job_title_written = false;
+        }
         // Restore the terminal protocols. Note this does nothing if they were unchanged.
         if self.tty_protocols_initial {
             self.enable_tty_protocols();
@@ -1,5 +1,5 @@
 use crate::prelude::*;
-use crate::proc::Pid;
+use crate::proc::{Pid, ProcStatus};
 use std::cell::Cell;
 use std::rc::Rc;
 
@@ -21,7 +21,7 @@ pub struct WaitHandle {
     pub base_name: WString,
 
     /// The status, if completed; None if not completed.
-    status: Cell<Option<i32>>,
+    status: Cell<Option<ProcStatus>>,
 }
 
 impl WaitHandle {
@@ -29,13 +29,18 @@ impl WaitHandle {
     pub fn is_completed(&self) -> bool {
         self.status.get().is_some()
     }
-    pub fn set_status_and_complete(&self, status: i32) {
+    pub fn set_status_and_complete(&self, status: ProcStatus) {
         assert!(!self.is_completed(), "wait handle already completed");
         self.status.set(Some(status));
     }
 
-    /// Return the status, or None if not yet completed.
+    /// Return the status value (as for $status), or None if not yet completed.
     pub fn status(&self) -> Option<i32> {
+        self.status.get().map(|status| status.status_value())
+    }
+
+    /// Return the full process status, or None if not yet completed.
+    pub fn proc_status(&self) -> Option<ProcStatus> {
         self.status.get()
     }
 }
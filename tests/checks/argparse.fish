@@ -330,6 +330,75 @@ begin
     or echo unexpected argparse return status $status >&2
 end
 
+# Value types
+begin
+    argparse 'n/num=:int(1..10)' 'r/ratio=:float(0..1)' 'c/color=:choice(red,green,blue)' \
+        'd/dir=:dir' 'v/version=:regex(^\d+\.\d+$)' -- -n 3 --ratio=0.5 -c red -d / -v 1.2
+    set -l
+    # CHECK: _flag_c red
+    # CHECK: _flag_color red
+    # CHECK: _flag_d /
+    # CHECK: _flag_dir /
+    # CHECK: _flag_n 3
+    # CHECK: _flag_num 3
+    # CHECK: _flag_r 0.5
+    # CHECK: _flag_ratio 0.5
+    # CHECK: _flag_v 1.2
+    # CHECK: _flag_version 1.2
+    # CHECK: argv
+    # CHECK: argv_opts '-n' '3' '--ratio=0.5' '-c' 'red' '-d' '/' '-v' '1.2'
+end
+
+begin
+    argparse 'n/num=:int(1..10)' -- -n 11
+    echo $status
+    # CHECKERR: argparse: Value '11' for flag 'n' greater than max allowed of '10'
+    # CHECK: 1
+    argparse 'n/num=+:int(0..)' -- --num 1 --num=-1
+    # CHECKERR: argparse: Value '-1' for flag 'num' less than min allowed of '0'
+    argparse 'n/num=:int' -- -n 1.5
+    # CHECKERR: argparse: Value '1.5' for flag 'n' is not an integer
+    argparse 'r/ratio=:float(..1)' -- -r 1.5
+    # CHECKERR: argparse: Value '1.5' for flag 'r' greater than max allowed of '1'
+    argparse 'r/ratio=:float' -- -r half
+    # CHECKERR: argparse: Value 'half' for flag 'r' is not a number
+    argparse 'c/color=:choice(red,green,blue)' -- --color=pink
+    # CHECKERR: argparse: Value 'pink' for flag 'color' is not one of: red,green,blue
+    argparse 'f/file=:file' -- -f /
+    # CHECKERR: argparse: Value '/' for flag 'f' is not an existing file
+    argparse 'd/dir=:dir' -- -d /nonexistent
+    # CHECKERR: argparse: Value '/nonexistent' for flag 'd' is not an existing directory
+    argparse 'v/version=:regex(^\d+\.\d+$)' -- -v 1.2.3
+    # CHECKERR: argparse: Value '1.2.3' for flag 'v' does not match '^\d+\.\d+$'
+    # Optional values are only checked when given.
+    argparse 'n/num=?:int' -- -n
+    and set -q _flag_num
+    and echo no value
+    # CHECK: no value
+end
+
+begin
+    argparse 'n/num=:integer' --
+    # CHECKERR: argparse: Invalid value type 'integer' in option spec 'n/num=:integer'
+    # CHECKERR: {{.*}}checks/argparse.fish (line {{\d+}}):
+    # CHECKERR: argparse 'n/num=:integer' --
+    # CHECKERR: ^
+    # CHECKERR: (Type 'help argparse' for related documentation)
+    argparse 'n/num:int' --
+    # CHECKERR: argparse: Invalid option spec 'n/num:int' at char ':'
+    # CHECKERR: {{.*}}checks/argparse.fish (line {{\d+}}):
+    # CHECKERR: argparse 'n/num:int' --
+    # CHECKERR: ^
+    # CHECKERR: (Type 'help argparse' for related documentation)
+end
+
+fish_opt -s n -l num -r --type 'int(1..10)' --description 'How many'
+# CHECK: n/num=:int(1..10)	How many
+fish_opt -s n --type int
+# CHECKERR: fish_opt: The --type flag requires the --required-val, --optional-value, or --multiple-vals flag
+fish_opt -s n -r --type int -v _validate_int
+# CHECKERR: fish_opt: type v/validate: options cannot be used together
+
 # Errors use function name by default
 function notargparse
     argparse a/alpha -- --banana
@@ -655,6 +724,146 @@ fish_opt -s h -om
 or echo unexpected status $status
 #CHECK: h=*
 
+# A description, after the validation
+fish_opt -s n -l name --description 'How many' -rv _validate_int
+or echo unexpected status $status
+#CHECK: n/name=!_validate_int	How many
+
+### Help from option specs
+fish_opt -s n -l name -r --placeholder WHO --description 'Who is greeting'
+#CHECK: n/name=WHO	Who is greeting
+fish_opt -s n --placeholder WHO
+#CHECKERR: fish_opt: The --placeholder flag requires the --required-val, --optional-value, or --multiple-vals flag
+
+function greet --description 'Greet people by name, loudly or quietly, as many times as you like'
+    argparse --usage 'greet [OPTIONS] NAME ...' --usage 'greet --version' --min-args 1 \
+        n/name=WHO\t'Who is greeting, which is shown before the names, unless the greeting is very quiet' \
+        l/loud\tShout 'count=?N'\t'How often' v x= '#num' very-very-long-option\t'A long option' -- $argv
+    or return
+    echo Hello $argv
+end
+
+set -g COLUMNS 60
+set -g LINES 20
+greet --help
+echo $status
+# CHECK: Usage: greet [OPTIONS] NAME ...
+# CHECK:        greet --version
+# CHECK:
+# CHECK: Greet people by name, loudly or quietly, as many times as
+# CHECK: you like
+# CHECK:
+# CHECK: Options:
+# CHECK:   -n, --name=WHO   Who is greeting, which is shown before
+# CHECK:                    the names, unless the greeting is very
+# CHECK:                    quiet
+# CHECK:   -l, --loud       Shout
+# CHECK:       --count[=N]  How often
+# CHECK:   -v, --v
+# CHECK:   -x VALUE
+# CHECK:   -NUM, --num
+# CHECK:       --very-very-long-option
+# CHECK:                    A long option
+# CHECK:   -h, --help       Show this help
+# CHECK: 3
+
+greet -x
+echo $status
+# CHECKERR: greet: -x: option requires an argument
+# CHECKERR: Usage: greet [OPTIONS] NAME ...
+# CHECKERR:        greet --version
+# CHECKERR: Try 'greet --help' for more information.
+# CHECK: 2
+
+greet
+# CHECKERR: greet: expected >= 1 arguments; got 0
+# CHECKERR: Usage: greet [OPTIONS] NAME ...
+# CHECKERR:        greet --version
+# CHECKERR: Try 'greet --help' for more information.
+
+greet fish
+# CHECK: Hello fish
+
+set -e COLUMNS LINES
+
+# If -h is taken, only --help is added.
+begin
+    argparse --usage 'demo' h/host= -- --help
+    echo $status
+end
+# CHECK: Usage: demo
+# CHECK:
+# CHECK: Options:
+# CHECK:   -h, --host=HOST
+# CHECK:       --help       Show this help
+# CHECK: 3
+
+### Completions from option specs
+begin
+    # Descriptions are ignored when parsing.
+    argparse h/help\t'Show help' n/name=\t'Name to greet' -- -h --name=fish
+    set -l
+    # CHECK: _flag_h -h
+    # CHECK: _flag_help -h
+    # CHECK: _flag_n fish
+    # CHECK: _flag_name fish
+    # CHECK: argv
+    # CHECK: argv_opts '-h' '--name=fish'
+end
+
+argparse --complete-for greet -x loud,quiet --max-args 0 \
+    h/help\t'Show help' n/name=\t'Name to greet' l/loud\t'Shout' q/quiet \
+    'count=!_validate_int'\t'How often' '#num'
+complete -c greet
+# CHECK: complete --no-files greet
+# CHECK: complete --require-parameter greet -l count -d 'How often'
+# CHECK: complete greet -l quiet -n 'not __fish_contains_opt -s l loud'
+# CHECK: complete greet -s q -n 'not __fish_contains_opt -s l loud'
+# CHECK: complete --require-parameter greet -l name -d 'Name to greet'
+# CHECK: complete --require-parameter greet -s n -d 'Name to greet'
+# CHECK: complete greet -l loud -d Shout -n 'not __fish_contains_opt -s q quiet'
+# CHECK: complete greet -s l -d Shout -n 'not __fish_contains_opt -s q quiet'
+# CHECK: complete greet -l help -d 'Show help'
+# CHECK: complete greet -s h -d 'Show help'
+
+# Registering again replaces the completions for the same flags.
+argparse --complete-for greet h/help\t'Print help' --
+complete -c greet | string match '*help*'
+# CHECK: complete greet -l help -d 'Print help'
+# CHECK: complete greet -s h -d 'Print help'
+complete -C'greet -'
+# CHECK: -h	Print help
+# CHECK: -l	Shout
+# CHECK: -n	Name to greet
+# CHECK: -q
+# CHECK: --count	How often
+# CHECK: --help	Print help
+# CHECK: --loud	Shout
+# CHECK: --name	Name to greet
+# CHECK: --quiet
+complete -C'greet --loud -'
+# CHECK: -h	Print help
+# CHECK: -l	Shout
+# CHECK: -n	Name to greet
+# CHECK: --count	How often
+# CHECK: --help	Print help
+# CHECK: --loud	Shout
+# CHECK: --name	Name to greet
+
+# Value types are used for the values offered.
+argparse --complete-for paint 'c/color=:choice(red,green,blue)'\t'Color to use' 'w/width=:int' 'd/dir=:dir' --
+complete -c paint
+# CHECK: complete --exclusive paint -l width
+# CHECK: complete --exclusive paint -s w
+# CHECK: complete --exclusive paint -l dir -a '(__fish_complete_directories)'
+# CHECK: complete --exclusive paint -s d -a '(__fish_complete_directories)'
+# CHECK: complete --exclusive paint -l color -d 'Color to use' -a 'red green blue'
+# CHECK: complete --exclusive paint -s c -d 'Color to use' -a 'red green blue'
+complete -C'paint --color '
+# CHECK: blue	Color to use
+# CHECK: green	Color to use
+# CHECK: red	Color to use
+
 function wrongargparse
     argparse -foo -- banana
     argparse a-b
@@ -691,3 +691,185 @@ complete command-line-aware-completions -xa "(commandline --cursor; commandline
 complete -C"command-line-aware-completions "
 # CHECK: 31
 # CHECK: command-line-aware-completions
+
+# Cached arguments are only expanded again after the TTL or when the cache is cleared.
+function slow-packages
+    set -g slow_calls (math $slow_calls + 1)
+    printf '%s\n' alpha beta
+end
+set -g slow_calls 0
+function cached-cmd; end
+complete cached-cmd -xa '(slow-packages)' --cache-ttl 60
+complete cached-cmd
+# CHECK: complete --exclusive cached-cmd -a '(slow-packages)' --cache-ttl 60
+complete -C'cached-cmd '
+# CHECK: alpha
+# CHECK: beta
+complete -C'cached-cmd b'
+# CHECK: beta
+echo $slow_calls
+# CHECK: 1
+complete --clear-cache -c cached-cmd
+complete -C'cached-cmd a'
+# CHECK: alpha
+echo $slow_calls
+# CHECK: 2
+
+# A cache key shares the arguments between commands.
+function cached-cmd2; end
+complete cached-cmd2 -s p -xa '(slow-packages)' --cache-ttl 60 --cache-key slow
+complete cached-cmd3 -xa '(slow-packages)' --cache-ttl 60 --cache-key slow
+complete cached-cmd2
+# CHECK: complete --exclusive cached-cmd2 -s p -a '(slow-packages)' --cache-ttl 60 --cache-key slow
+complete -C'cached-cmd2 -p' >/dev/null
+complete -C'cached-cmd3 ' >/dev/null
+echo $slow_calls
+# CHECK: 3
+complete --clear-cache --cache-key slow
+complete -C'cached-cmd3 ' >/dev/null
+echo $slow_calls
+# CHECK: 4
+complete --clear-cache
+complete -C'cached-cmd ' >/dev/null
+complete -C'cached-cmd3 ' >/dev/null
+echo $slow_calls
+# CHECK: 6
+
+# A TTL of 0 disables caching.
+complete cached-cmd4 -xa '(slow-packages)' --cache-ttl 0
+complete -C'cached-cmd4 ' >/dev/null
+complete -C'cached-cmd4 ' >/dev/null
+echo $slow_calls
+# CHECK: 8
+
+complete cached-cmd --cache-key slow -a foo
+# CHECKERR: complete: invalid option combination, --cache-key requires --cache-ttl
+complete cached-cmd --cache-ttl 60
+# CHECKERR: complete: invalid option combination, --cache-ttl requires --arguments
+complete cached-cmd --cache-ttl soon -a foo
+# CHECKERR: complete: soon: invalid integer
+# CHECKERR: {{.*}}checks/complete.fish (line {{\d+}}):
+# CHECKERR: complete cached-cmd --cache-ttl soon -a foo
+# CHECKERR: ^
+# CHECKERR: (Type 'help complete' for related documentation)
+
+# JSON output exposes the flags and match of each completion.
+complete -c json-cmd -f -a 'bar\tdesc baz'
+complete -c json-cmd -l long -d 'A long option'
+complete -C'json-cmd b' --json
+# CHECK: {"completion": "ar", "description": "desc", "token": "bar", "flags": ["dont_escape_tildes"], "rank": 4, "match": {"type": "prefix", "case": "sensitive", "from_separator": false}}
+# CHECK: {"completion": "az", "description": "", "token": "baz", "flags": ["dont_escape_tildes"], "rank": 4, "match": {"type": "prefix", "case": "sensitive", "from_separator": false}}
+complete -C'json-cmd BA' --json
+# CHECK: {"completion": "bar", "description": "desc", "token": "bar", "flags": ["replaces_token", "dont_escape_tildes"], "rank": 6, "match": {"type": "prefix", "case": "insensitive", "from_separator": false}}
+# CHECK: {"completion": "baz", "description": "", "token": "baz", "flags": ["replaces_token", "dont_escape_tildes"], "rank": 6, "match": {"type": "prefix", "case": "insensitive", "from_separator": false}}
+complete -C'json-cmd --lo' --json
+# CHECK: {"completion": "ng", "description": "A long option", "token": "--long", "flags": [], "rank": 4, "match": {"type": "exact", "case": "sensitive", "from_separator": false}}
+set -g json_test_var 1
+complete -C'echo $json_test_v' --json
+# CHECK: {"completion": "ar", "description": "Variable: 1", "token": "$json_test_var", "flags": ["variable_name"], "rank": 4, "match": {"type": "prefix", "case": "sensitive", "from_separator": false}}
+complete --json
+# CHECKERR: complete: invalid option combination, --json requires --do-complete
+
+# Completions can be imported from a JSON spec. Unknown keys are ignored.
+echo '{
+    "name": "spec-tool",
+    "x-generator": "ignored",
+    "options": [
+        {"short": "v", "long": "verbose", "description": "Be verbose"},
+        {"long": "color", "argument": {"values": ["auto", {"value": "never", "description": "No colors"}]}}
+    ],
+    "subcommands": [
+        {"name": "add", "description": "Add a thing", "arguments": {"command": "printf %s\\\\n one two"}},
+        {"name": "remote", "description": "Manage remotes", "subcommands": [
+            {"name": "show", "options": [{"short": "n", "description": "Do not query"}]}
+        ]}
+    ]
+}' | complete --import-spec -
+complete -C'spec-tool '
+# CHECK: add	Add a thing
+# CHECK: remote	Manage remotes
+complete -C'spec-tool --color='
+# CHECK: --color=auto
+# CHECK: --color=never	No colors
+complete -C'spec-tool add '
+# CHECK: one
+# CHECK: two
+complete -C'spec-tool remote '
+# CHECK: show
+complete -C'spec-tool remote show -'
+# Options of the command are offered after its subcommands as well.
+# CHECK: -n	Do not query
+# CHECK: -v	Be verbose
+# CHECK: --color
+# CHECK: --verbose	Be verbose
+echo '{"name": "spec-tool", "options": [{"long": 1}]}' | complete --import-spec -
+# CHECKERR: complete: <stdin>: Invalid completion spec: 'long' must be a string or an array of strings, not number
+echo '{"name": "spec-tool",
+  "options": [' | complete --import-spec -
+# CHECKERR: complete: <stdin> (line 3): Invalid completion spec: expected a value
+echo '{"name": "x", "arguments": {"command": "echo ("}}' | complete --import-spec -
+# CHECKERR: complete: <stdin>: Invalid completion spec: command 'echo (' contains a syntax error
+string repeat -n 65 '[' | complete --import-spec -
+# CHECKERR: complete: <stdin> (line 1): Invalid completion spec: objects and arrays are nested more than 64 levels deep
+string repeat -n 100000 '[' | complete --import-spec -
+# CHECKERR: complete: <stdin> (line 1): Invalid completion spec: objects and arrays are nested more than 64 levels deep
+complete --import-spec /nonexistent/spec.json
+# CHECKERR: complete: Reading '/nonexistent/spec.json' failed: {{.*}}
+echo $status
+# CHECK: 1
+
+# Completions can be scoped to subcommands.
+complete -c sub-tool -s C -r -d 'Run in dir'
+complete -c sub-tool -s v -d Verbose
+complete -c sub-tool --subcommand remote -d 'Manage remotes'
+complete -c sub-tool --subcommand 'remote add' -d 'Add a remote'
+complete -c sub-tool --subcommand 'remote add' -l fetch -d 'Fetch after adding'
+complete -c sub-tool --subcommand 'remote remove' -xa 'origin upstream'
+complete -c sub-tool --subcommand add -l force -d Force
+complete -C'sub-tool '
+# CHECK: add
+# CHECK: remote	Manage remotes
+complete -C'sub-tool -C remote '
+# CHECK: add
+# CHECK: remote	Manage remotes
+complete -C'sub-tool -v remote '
+# CHECK: add	Add a remote
+# CHECK: remove
+complete -C'sub-tool remote add -'
+# CHECK: -C	Run in dir
+# CHECK: -v	Verbose
+# CHECK: --fetch	Fetch after adding
+complete -C'sub-tool remote remove '
+# CHECK: origin
+# CHECK: upstream
+complete -C'sub-tool add --'
+# CHECK: --force	Force
+# Nothing follows an argument which is no subcommand.
+complete -C'sub-tool add remote --f'
+# CHECK: --force	Force
+complete -c sub-tool
+# CHECK: complete sub-tool --subcommand add -l force -d Force
+# CHECK: complete --exclusive sub-tool --subcommand 'remote remove' -a 'origin upstream'
+# CHECK: complete sub-tool --subcommand 'remote add' -l fetch -d 'Fetch after adding'
+# CHECK: complete sub-tool --subcommand 'remote add' -d 'Add a remote'
+# CHECK: complete sub-tool --subcommand remote -d 'Manage remotes'
+# CHECK: complete sub-tool -s v -d Verbose
+# CHECK: complete --require-parameter sub-tool -s C -d 'Run in dir'
+complete -c sub-tool -e --subcommand remote
+complete -C'sub-tool '
+# CHECK: add
+complete -c sub-tool --subcommand ' '
+# CHECKERR: complete: --subcommand requires a non-empty string
+
+# Subcommands of specs are scoped, so their names don't clash.
+echo '{
+    "name": "spec-tool2",
+    "subcommands": [
+        {"name": "add", "options": [{"long": "all"}]},
+        {"name": "remote", "subcommands": [{"name": "add", "options": [{"long": "fetch"}]}]}
+    ]
+}' | complete --import-spec -
+complete -C'spec-tool2 add --'
+# CHECK: --all
+complete -C'spec-tool2 remote add --'
+# CHECK: --fetch
@@ -0,0 +1,65 @@
+# RUN: %fish %s
+
+coproc -n up tr a-z A-Z
+count $up
+# CHECK: 2
+test "$up_pid" = "$last_pid"
+and echo pid ok
+# CHECK: pid ok
+coproc | string replace -r '\t.*' ''
+# CHECK: up
+
+# Send input, and read the output line by line.
+echo hello >&$up[2]
+echo world >&$up[2]
+coproc --close-input up
+echo $up[2]
+# CHECK: -1
+read -l line <&$up[1]
+echo "1: $line"
+# CHECK: 1: HELLO
+read -l line <&$up[1]
+echo "2: $line"
+# CHECK: 2: WORLD
+read -l line <&$up[1]
+echo "end of output: $status"
+# CHECK: end of output: 1
+coproc --close up
+set -q up up_pid
+or echo variables erased
+# CHECK: variables erased
+
+# A conversation, where each answer is read before the next question.
+coproc sh -c 'while read line; do echo $((line * 2)); done'
+for i in 1 2 3
+    echo $i >&$coproc[2]
+    read -l answer <&$coproc[1]
+    echo "$i -> $answer"
+end
+# CHECK: 1 -> 2
+# CHECK: 2 -> 4
+# CHECK: 3 -> 6
+
+# External commands can use the file descriptors too.
+printf '%s\n' 7 8 | command cat >&$coproc[2]
+read -l answer <&$coproc[1]
+echo $answer
+# CHECK: 14
+coproc --close-input
+command cat <&$coproc[1]
+# CHECK: 16
+coproc -c
+coproc
+
+coproc --close nope
+# CHECKERR: coproc: nope: no such coprocess
+echo $status
+# CHECK: 1
+
+coproc -n bad-name true
+# CHECKERR: coproc: bad-name: invalid variable name. See `help language#shell-variable-and-function-names`
+# CHECKERR:
+# CHECKERR: {{.*}}coproc.fish (line {{\d+}}):
+# CHECKERR: coproc -n bad-name true
+# CHECKERR: ^
+# CHECKERR: (Type 'help coproc' for related documentation)
@@ -0,0 +1,71 @@
+#RUN: %fish %s
+
+mkdir -p fish-shell manual/fish-docs manual/other
+set -l base $PWD
+
+# Non-interactive sessions don't record directories.
+cd fish-shell
+cd $base
+frecency
+echo $status
+# CHECK: 1
+
+frecency add fish-shell manual/fish-docs manual/fish-docs
+frecency
+# CHECK: {{.*}}/manual/fish-docs
+# CHECK: {{.*}}/fish-shell
+frecency --show-score fish
+# CHECK: 8.00{{\t}}{{.*}}/manual/fish-docs
+# CHECK: 4.00{{\t}}{{.*}}/fish-shell
+frecency search shell
+# CHECK: {{.*}}/fish-shell
+frecency -n1
+# CHECK: {{.*}}/manual/fish-docs
+
+# The last keyword must match the last component.
+frecency manual
+echo $status
+# CHECK: 1
+frecency manual fish
+# CHECK: {{.*}}/manual/fish-docs
+
+frecency add nonexistent
+# CHECKERR: frecency: 'nonexistent' is not a directory
+
+# Visited directories come first when completing cd.
+begin
+    mkdir -p manual/fish-a manual/fish-z
+    frecency add manual/fish-z
+    complete -C 'cd manual/fish'
+    # CHECK: manual/fish-docs/
+    # CHECK: manual/fish-z/
+    # CHECK: manual/fish-a/
+end
+
+# Visited directories elsewhere are offered if nothing here matches.
+complete -C 'cd fish-do'
+# CHECK: {{.*}}/manual/fish-docs/
+frecency add manual/other
+complete -C 'cd oth'
+# CHECK: {{.*}}/manual/other/
+
+frecency delete manual/fish-docs
+frecency
+# CHECK: {{.*}}/fish-shell
+# CHECK: {{.*}}/manual/fish-z
+# CHECK: {{.*}}/manual/other
+frecency delete manual/fish-docs
+echo $status
+# CHECK: 1
+
+frecency clear extra
+# CHECKERR: frecency: clear: expected 0 arguments; got 1
+frecency clear
+frecency
+echo $status
+# CHECK: 1
+
+frecency -n nope
+# CHECKERR: frecency: nope: invalid integer
+frecency add -n 1 fish-shell
+# CHECKERR: frecency: add: subcommand takes no options
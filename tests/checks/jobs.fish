@@ -133,3 +133,24 @@ end
 
 disown 252
 # CHECKERR: disown: Could not find job '252'
+
+# Machine-readable output.
+jobs --json
+# CHECK: []
+echo $status
+# CHECK: 1
+command sh -c 'exit 3' | command sleep 5 &
+set -l sleep_pid (jobs --last --pid)[2]
+command sh -c 'kill -STOP $$; exit 4' &
+set -l stopped_pid $last_pid
+sleep .1
+jobs --json | string replace -ra -- '\b\d{3,}\b' N
+# CHECK: [
+# CHECK: {"id": 2, "internal_id": N, "pgid": {{N|null}}, "state": "stopped", "start_time": N, "disowned": false, "command": "command sh -c 'kill -STOP $$; exit 4' &", "processes": [{"pid": N, "command": "sh", "state": "stopped", "status": null}]},
+# CHECK: {"id": 1, "internal_id": N, "pgid": {{N|null}}, "state": "running", "start_time": N, "disowned": false, "command": "command sh -c 'exit 3' | command sleep 5 &", "processes": [{"pid": N, "command": "sh", "state": "completed", "status": 3}, {"pid": N, "command": "sleep", "state": "running", "status": null}]}
+# CHECK: ]
+kill -CONT $stopped_pid
+kill $sleep_pid
+wait
+jobs --json --last
+# CHECK: []
@@ -0,0 +1,60 @@
+#RUN: %fish %s
+
+set -l before (ulimit -n)
+limit -n 64 -- sh -c 'ulimit -n'
+#CHECK: 64
+test (ulimit -n) = $before
+and echo unchanged
+#CHECK: unchanged
+
+# Both the soft and the hard limit are set.
+limit -n 64 sh -c 'ulimit -Hn'
+#CHECK: 64
+
+# Sizes take a suffix, and are otherwise in kilobytes like ulimit.
+set -l fish (status fish-path)
+limit -f 1M -- $fish --no-config -c 'ulimit -f'
+#CHECK: 1024
+limit -f 1024 -- $fish --no-config -c 'ulimit -f'
+#CHECK: 1024
+
+# The exit status is that of the command, and output can be piped.
+limit -t 30 -- sh -c 'ulimit -t; exit 3' | string replace 30 thirty
+echo $pipestatus
+#CHECK: thirty
+#CHECK: 3 0
+
+# Limits apply to external commands in functions, and nest.
+function show-limits
+    sh -c 'ulimit -n; ulimit -t'
+end
+limit -n 64 -- limit -t 20 -- show-limits
+#CHECK: 64
+#CHECK: 20
+
+# Background jobs are limited too.
+limit -n 32 sh -c 'ulimit -n' &
+wait
+#CHECK: 32
+
+set -l nice (sh -c nice)
+limit --niceness 3 -- sh -c nice | read -l limited
+test $limited -eq (math "min($nice + 3, 19)")
+and echo niceness ok
+#CHECK: niceness ok
+
+limit -n abc true
+#CHECKERR: limit: Invalid limit 'abc'
+#CHECKERR: {{.*}}checks/limit.fish (line {{\d+}}):
+#CHECKERR: limit -n abc true
+#CHECKERR: ^
+#CHECKERR: (Type 'help limit' for related documentation)
+
+limit -n 5
+echo $status
+#CHECKERR: limit: missing command
+#CHECKERR: {{.*}}checks/limit.fish (line {{\d+}}):
+#CHECKERR: limit -n 5
+#CHECKERR: ^
+#CHECKERR: (Type 'help limit' for related documentation)
+#CHECK: 2
@@ -0,0 +1,104 @@
+#RUN: %fish %s
+
+# Items run at the same time.
+set -l start (date +%s)
+parallel -j 4 sleep ::: 1 1 1 1
+echo status $status
+#CHECK: status 0
+test (math (date +%s) - $start) -lt 4
+and echo concurrent
+#CHECK: concurrent
+
+# The item is appended, or replaces {}.
+parallel -k echo item ::: a b c
+#CHECK: item a
+#CHECK: item b
+#CHECK: item c
+parallel -k echo "<{}>" {}.txt ::: a b
+#CHECK: <a> a.txt
+#CHECK: <b> b.txt
+
+# Items can come from stdin.
+printf '%s\n' x y | parallel -k -j 1 echo got
+#CHECK: got x
+#CHECK: got y
+
+# Output is buffered per item, so it is not interleaved.
+parallel -k -j 2 sh -c 'echo $0 one; sleep 0.2; echo $0 two' ::: a b
+#CHECK: a one
+#CHECK: a two
+#CHECK: b one
+#CHECK: b two
+
+# Without --keep-order, output is printed as items complete.
+parallel -j 2 sh -c 'sleep $0; echo slept $0' ::: 0.5 0
+#CHECK: slept 0
+#CHECK: slept 0.5
+
+# Stderr is collected too.
+parallel -k sh -c 'echo err $0 >&2' ::: p q
+#CHECKERR: err p
+#CHECKERR: err q
+
+# Functions run in their own fish process, which gets a copy of the variables and functions.
+set -gx exported_var exported
+set -g global_var global
+function square_helper
+    math $argv[1] ^ 2
+end
+function slow_square
+    sleep 0.2
+    echo (square_helper $argv[1]) $exported_var $global_var
+    return $argv[1]
+end
+parallel -k --status-var statuses slow_square ::: 2 0 3
+echo status $status
+echo statuses $statuses
+#CHECK: 4 exported global
+#CHECK: 0 exported global
+#CHECK: 9 exported global
+#CHECK: status 2
+#CHECK: statuses 2 0 3
+
+parallel --status-var statuses true ::: a b
+echo status $status statuses $statuses
+#CHECK: status 0 statuses 0 0
+
+parallel nonexistent-command ::: a
+echo status $status
+#CHECK: status 127
+#CHECKERR: fish: Unknown command: nonexistent-command
+#CHECKERR: {{.*}}
+#CHECKERR: nonexistent-command a &
+#CHECKERR: ^~~~~~~~~~~~~~~~~~^
+
+# No items.
+parallel echo :::
+echo status $status
+#CHECK: status 0
+
+jobs
+#CHECK: jobs: There are no jobs
+
+# $last_pid still refers to the user's last background job.
+sleep 0 &
+set -l pid $last_pid
+parallel true ::: a b
+test "$last_pid" = "$pid"
+and echo last_pid kept
+#CHECK: last_pid kept
+wait
+
+parallel -j 0 echo ::: a
+#CHECKERR: parallel: 0: invalid number of jobs
+#CHECKERR: {{.*}}checks/parallel.fish (line {{\d+}}):
+#CHECKERR: parallel -j 0 echo ::: a
+#CHECKERR: ^
+#CHECKERR: (Type 'help parallel' for related documentation)
+
+parallel ::: a
+#CHECKERR: parallel: missing command
+#CHECKERR: {{.*}}checks/parallel.fish (line {{\d+}}):
+#CHECKERR: parallel ::: a
+#CHECKERR: ^
+#CHECKERR: (Type 'help parallel' for related documentation)
@@ -467,3 +467,66 @@ set -S out_of_range_codepoint
 printf \xff | { read invalid_utf8; set -S invalid_utf8 }
 # CHECK: $invalid_utf8: set in global scope, unexported, with 1 elements
 # CHECK: $invalid_utf8[1]: |\Xff|
+
+# --timeout gives up on input that does not arrive in time.
+begin
+    echo partial
+    sleep 1
+    echo rest
+end | begin
+    read --timeout 0.2 -l timed_line
+    echo $status
+    set -S timed_line
+    read -l rest
+    echo $rest
+end
+# CHECK: 0
+# CHECK: $timed_line: set in local scope, unexported, with 1 elements
+# CHECK: $timed_line[1]: |partial|
+# CHECK: rest
+
+begin
+    printf 'no newline'
+    sleep 1
+end | begin
+    read --timeout 0.2 -l timed_line
+    echo $status
+    set -S timed_line
+end
+# CHECK: 120
+# CHECK: $timed_line: set in local scope, unexported, with 0 elements
+
+begin
+    printf 'no newline'
+    sleep 1
+end | begin
+    read --timeout 0.2 --keep-partial -l first second
+    echo $status
+    set -S first second
+end
+# CHECK: 120
+# CHECK: $first: set in local scope, unexported, with 1 elements
+# CHECK: $first[1]: |no|
+# CHECK: $second: set in local scope, unexported, with 1 elements
+# CHECK: $second[1]: |newline|
+
+begin
+    echo one
+    sleep 1
+end | begin
+    read --timeout 0.2 --keep-partial --line -l first second
+    echo $status
+    set -S first second
+end
+# CHECK: 120
+# CHECK: $first: set in local scope, unexported, with 1 elements
+# CHECK: $first[1]: |one|
+# CHECK: $second: set in local scope, unexported, with 1 elements
+# CHECK: $second[1]: ||
+
+read --timeout foo
+# CHECKERR: read: foo: invalid number of seconds
+# CHECKERR: {{.*}}read.fish (line {{\d+}}):
+# CHECKERR: read --timeout foo
+# CHECKERR: ^
+# CHECKERR: (Type 'help read' for related documentation)
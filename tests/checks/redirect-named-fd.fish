@@ -0,0 +1,96 @@
+#RUN: %fish %s
+
+set -l tmpdir (mktemp -d)
+set -l file $tmpdir/log.txt
+
+# Open a file and keep it open across commands.
+true {log}>$file
+test $log -ge 10
+and echo fd ok
+#CHECK: fd ok
+set -qg log
+and echo global
+#CHECK: global
+
+echo builtin >&$log
+printf '%s\n' function >&$log
+begin
+    echo block
+end >&$log
+sh -c 'echo external' >&$log
+cat $file
+#CHECK: builtin
+#CHECK: function
+#CHECK: block
+#CHECK: external
+
+# The fd is not inherited by external commands unless redirected.
+sh -c "echo leaked >&$log" 2>/dev/null
+or echo not inherited
+#CHECK: not inherited
+
+# Closing erases the variable.
+true {log}>&-
+set -q log
+or echo closed
+#CHECK: closed
+
+# Appending.
+true {log}>>$file
+echo appended >&$log
+true {log}>&-
+tail -n 1 $file
+#CHECK: appended
+
+# Reading keeps the position between commands.
+true {in}<$file
+read -l first <&$in
+read -l second <&$in
+echo $first $second
+#CHECK: builtin function
+head -n 1 <&$in
+#CHECK: block
+true {in}>&-
+
+# Try-input falls back to /dev/null.
+true {in}<?$tmpdir/nonexistent
+read -l line <&$in
+echo read status $status
+#CHECK: read status 1
+true {in}>&-
+
+# Other arguments in braces are still arguments.
+echo {a}b {c}
+#CHECK: {a}b {c}
+
+# Brace expansions and braces not directly followed by the redirection keep their old meaning.
+echo {a,b}>$tmpdir/braces
+echo {a-b}>>$tmpdir/braces
+echo {c}x>>$tmpdir/braces
+echo {d} >>$tmpdir/braces
+cat $tmpdir/braces
+#CHECK: a b
+#CHECK: {a-b}
+#CHECK: {c}x
+#CHECK: {d}
+
+set -l fish (status fish-path)
+$fish --no-config -c 'true {fd}>&-'
+#CHECKERR: fish: Variable 'fd' does not hold a file descriptor opened by a redirection
+#CHECKERR: true {fd}>&-
+#CHECKERR: ^~~~~~^
+echo $status
+#CHECK: 2
+
+$fish --no-config -c 'true {fd}>&1'
+#CHECKERR: fish: A named file descriptor can only be opened on a file or closed with '&-'
+#CHECKERR: true {fd}>&1
+#CHECKERR: ^~~~~~^
+
+$fish --no-config -c "true {fd}<$tmpdir/nonexistent; set -q fd; or echo unset"
+#CHECKERR: fish: Could not open '{{.*}}/nonexistent': No such file or directory
+#CHECKERR: true {fd}<{{.*}}/nonexistent; set -q fd; or echo unset
+#CHECKERR: ^{{~*}}^
+#CHECK: unset
+
+rm -r $tmpdir
@@ -0,0 +1,100 @@
+# RUN: %fish %s
+
+status strict
+# CHECK: off
+
+# A failing job aborts the function that enabled strict mode, and the functions it calls.
+function inner
+    false
+    echo inner not reached
+end
+function outer
+    status strict on
+    status strict
+    inner
+    echo outer not reached
+end
+outer
+echo outer returned $status
+# CHECK: on
+# CHECKERR: {{.*}}status-strict.fish (line {{\d+}}): Job failed with status 1 in strict mode
+# CHECKERR: false
+# CHECKERR: ^~~~^
+# CHECKERR: in function 'inner'
+# CHECKERR: called on line {{\d+}} of file {{.*}}status-strict.fish
+# CHECKERR: in function 'outer'
+# CHECKERR: called on line {{\d+}} of file {{.*}}status-strict.fish
+# CHECKERR: {{.*}}status-strict.fish (line {{\d+}}): Job failed with status 1 in strict mode
+# CHECKERR: inner
+# CHECKERR: ^~~~^
+# CHECKERR: in function 'outer'
+# CHECKERR: called on line {{\d+}} of file {{.*}}status-strict.fish
+# CHECK: outer returned 1
+
+# The mode ends with the block that set it.
+status strict
+# CHECK: off
+begin
+    status strict on
+end
+false
+echo still running
+# CHECK: still running
+
+# Failures in conditions are expected, including inside the functions they call.
+function checks
+    status strict on
+    if false
+    end
+    while false
+    end
+    false; or true
+    false || true
+    false && true
+    not true
+    inner_condition; and true
+    set -l var (false; echo substitution)
+    echo $var
+    echo checks done
+end
+function inner_condition
+    false
+    echo in condition
+    false
+end
+checks
+# CHECK: in condition
+# CHECK: substitution
+# CHECK: checks done
+
+# Without pipefail, only the last process counts.
+function pipes
+    status strict on
+    false | true
+    echo without pipefail
+    status strict pipefail
+    true | sh -c 'exit 4' | true
+    echo pipefail not reached
+end
+pipes
+echo pipes returned $status $pipestatus
+# CHECK: without pipefail
+# CHECKERR: {{.*}}status-strict.fish (line {{\d+}}): Job failed with status 4 in strict mode
+# CHECKERR: true | sh -c 'exit 4' | true
+# CHECKERR: ^~~~~~~~~~~~~~~~~~~~~~~~~~~^
+# CHECKERR: in function 'pipes'
+# CHECKERR: called on line {{\d+}} of file {{.*}}status-strict.fish
+# CHECK: pipes returned 4 4
+
+status strict maybe
+# CHECKERR: status: Invalid strict mode 'maybe'
+status strict on off
+# CHECKERR: status: strict: expected 1 arguments; got 2
+
+# Outside of functions, the script is aborted.
+status strict on
+false
+# CHECKERR: {{.*}}status-strict.fish (line {{\d+}}): Job failed with status 1 in strict mode
+# CHECKERR: false
+# CHECKERR: ^~~~^
+echo script not reached
@@ -140,3 +140,12 @@ and should have failed on unrecognized feature
 # CHECKERR: status test-terminal-feature: unrecognized feature 'unrecognized-feature'
 status test-terminal-feature scroll-content-up
 and should have failed when running without a TTY
+
+status terminal-report | string match -e primary-device-attribute
+# CHECK: primary-device-attribute{{\t}}not-queried{{\t}}CSI 0 c
+status terminal-report | string match -e keyboard
+# CHECK: kitty-keyboard{{\t}}no{{\t}}CSI ? u
+# CHECK: keyboard-protocol{{\t}}unknown{{\t}}CSI ? u
+status terminal-report 1
+and should have failed on too many args
+# CHECKERR: status: terminal-report: expected 0 arguments; got 1
@@ -74,3 +74,17 @@ begin
     # CHECK: No this is Patrick
 end
 rm time
+
+# With $fish_time_processes, each external process is reported.
+begin
+    set -l fish_time_processes 1
+    time command true | command sh -c 'cat; exit 3'
+end
+#CHECKERR: ___{{.*}}
+#CHECKERR: {{.*}}
+#CHECKERR: {{.*}}
+#CHECKERR: {{.*}}
+#CHECKERR:
+#CHECKERR: pid  usr time {{\s*}}sys time {{\s*}}max rss  command
+#CHECKERR: {{\d+}} {{[\d,.\s]*}} {{millis|micros|secs}} {{[\d,.\s]*}} {{millis|micros|secs}} {{\s*\d+}} KiB  true
+#CHECKERR: {{\d+}} {{[\d,.\s]*}} {{millis|micros|secs}} {{[\d,.\s]*}} {{millis|micros|secs}} {{\s*\d+}} KiB  sh
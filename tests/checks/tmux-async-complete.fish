@@ -0,0 +1,56 @@
+#RUN: %fish %s
+#REQUIRES: command -v tmux
+
+isolated-tmux-start -C '
+    function slow-lookup
+        while not test -e flag
+            sleep 0.1
+        end
+        printf "%s\t%s\n" alpha "first host" beta "second host"
+    end
+    complete -c ssh-thing -f -a "(slow-lookup)"
+    set -g fish_async_completion 1
+'
+
+# While the completions are being computed, the pager says so.
+isolated-tmux send-keys 'ssh-thing ' Tab
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: prompt 0> ssh-thing
+# CHECK: Completing{{.*}}
+
+# Typing cancels the request.
+isolated-tmux send-keys 'x'
+tmux-sleep
+touch flag
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: prompt 0> ssh-thing x
+
+# Once the completions are done, they are shown.
+rm flag
+isolated-tmux send-keys BSpace Tab
+tmux-sleep
+touch flag
+sleep-until 'isolated-tmux capture-pane -p | grep alpha'
+isolated-tmux capture-pane -p
+# CHECK: prompt 0> ssh-thing
+# CHECK: alpha  (first host)  beta  (second host)
+
+# A unique completion is inserted.
+isolated-tmux send-keys C-u 'ssh-thing b' Tab
+sleep-until 'isolated-tmux capture-pane -p | grep beta'
+isolated-tmux capture-pane -p
+# CHECK: prompt 0> ssh-thing beta
+
+# Arguments cached by the background completion are reused by the interactive session.
+isolated-tmux send-keys C-u 'complete -c cached-thing -f -a "(echo >>lookups; echo gamma)" --cache-ttl 60' Enter
+isolated-tmux send-keys C-l 'cached-thing ' Tab
+sleep-until 'isolated-tmux capture-pane -p | grep gamma'
+isolated-tmux send-keys C-u C-l 'complete -C "cached-thing "; count <lookups' Enter
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: prompt {{\d+}}> complete -C "cached-thing "; count <lookups
+# CHECK: gamma
+# CHECK: 1
+# CHECK: prompt {{\d+}}>
@@ -0,0 +1,51 @@
+#RUN: %fish %s
+#REQUIRES: command -v tmux
+
+isolated-tmux-start -C '
+    function fish_prompt
+        set -l last_status $status
+        while set -q wait_for_file; and not test -e $wait_for_file
+            sleep 0.1
+        end
+        printf "%s %s> " $prompt_text $last_status
+    end
+    set -g prompt_text first
+    set -g fish_async_prompt 1
+'
+
+# The first prompt is computed synchronously.
+isolated-tmux capture-pane -p
+# CHECK: first 0>
+
+# While the prompt is being computed, the previous one is shown.
+isolated-tmux send-keys 'set -g prompt_text second; set -g wait_for_file flag; false' Enter
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: first 0> set -g prompt_text second; set -g wait_for_file flag; false
+# CHECK: first 0>
+
+# The prompt is repainted once it is done. It sees the exit status of the last command.
+touch flag
+sleep-until 'isolated-tmux capture-pane -p | grep "second 1>"'
+isolated-tmux capture-pane -p
+# CHECK: first 0> set -g prompt_text second; set -g wait_for_file flag; false
+# CHECK: second 1>
+
+# Submitting a command cancels the stale computation.
+rm flag
+isolated-tmux send-keys C-l 'set -g prompt_text third' Enter
+tmux-sleep
+isolated-tmux send-keys 'echo done' Enter
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: second 1> set -g prompt_text third
+# CHECK: second 1> echo done
+# CHECK: done
+# CHECK: second 1>
+touch flag
+sleep-until 'isolated-tmux capture-pane -p | grep "third 0>"'
+isolated-tmux capture-pane -p
+# CHECK: second 1> set -g prompt_text third
+# CHECK: second 1> echo done
+# CHECK: done
+# CHECK: third 0>
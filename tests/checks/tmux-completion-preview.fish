@@ -0,0 +1,65 @@
+#RUN: %fish %s
+#REQUIRES: command -v tmux
+#REQUIRES: test -z "$CI"
+
+isolated-tmux-start -C '
+    set -g fish_autosuggestion_enabled 0
+    complete -c cmd -f -a one -d First
+    complete -c cmd -f -a two -d Second
+    function fish_completion_preview
+        echo "preview of $argv[1] for" (commandline --input=$argv[3] -xp)[1]
+        printf "%s\n" $argv[2] (printf "tab:\there")
+    end
+'
+
+# Without a selection there is no preview.
+isolated-tmux send-keys 'cmd ' Tab
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: prompt 0> cmd
+# CHECK: one  (First)  two  (Second)
+
+# The preview of the selected completion is shown below the completions.
+isolated-tmux send-keys Tab
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: prompt 0> cmd one
+# CHECK: one  (First)  two  (Second)
+# CHECK: preview of one for cmd
+# CHECK: First
+# CHECK: tab:    here
+
+isolated-tmux send-keys Tab
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: prompt 0> cmd two
+# CHECK: one  (First)  two  (Second)
+# CHECK: preview of two for cmd
+# CHECK: Second
+# CHECK: tab:    here
+
+# The preview goes away with the pager.
+isolated-tmux send-keys C-u C-l
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: prompt 0>
+
+# Cycling through the completions quickly only previews the one it stops at.
+isolated-tmux send-keys 'function fish_completion_preview; echo $argv[1] >>previewed; end' Enter C-l
+isolated-tmux send-keys 'cmd ' Tab Tab Tab Tab
+tmux-sleep
+isolated-tmux send-keys C-u C-l 'cat previewed' Enter
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: prompt {{\d+}}> cat previewed
+# CHECK: one
+# CHECK: prompt {{\d+}}>
+
+# A slow preview does not block typing.
+isolated-tmux send-keys 'function fish_completion_preview; sleep 10; end' Enter C-l
+isolated-tmux send-keys 'cmd ' Tab Tab
+tmux-sleep
+isolated-tmux send-keys x
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: prompt {{\d+}}> cmd one x
@@ -0,0 +1,47 @@
+#RUN: %fish %s
+#REQUIRES: command -v tmux
+#REQUIRES: test -z "$CI"
+
+isolated-tmux-start
+
+mkdir -p deep/nested/project other
+set -l base $PWD
+
+# Directories visited interactively are remembered.
+isolated-tmux send-keys "set -g base $base; cd deep/nested/project; cd ..; cd project; cd \$base/other" Enter C-l
+tmux-sleep
+frecency -n1
+# CHECK: {{.*}}/deep/nested/project
+frecency other
+# CHECK: {{.*}}/other
+
+# cd changes to a visited directory by part of its name.
+isolated-tmux send-keys 'cd proj; string replace $base "" $PWD' Enter
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: prompt {{\d+}}> cd proj; string replace $base "" $PWD
+# CHECK: /deep/nested/project
+# CHECK: prompt {{\d+}}>
+
+# Only when there is no such directory here.
+isolated-tmux send-keys C-l 'mkdir proj; cd proj; string replace $base "" $PWD' Enter
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: prompt {{\d+}}> mkdir proj; cd proj; string replace $base "" $PWD
+# CHECK: /deep/nested/project/proj
+# CHECK: prompt {{\d+}}>
+
+# cd in functions neither records directories nor changes to visited ones.
+isolated-tmux send-keys C-l 'function go; cd $argv; end; mkdir $base/unvisited' Enter
+isolated-tmux send-keys C-l 'go $base/unvisited; frecency unvisited; echo $status' Enter
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: prompt {{\d+}}> go $base/unvisited; frecency unvisited; echo $status
+# CHECK: 1
+# CHECK: prompt {{\d+}}>
+isolated-tmux send-keys C-l 'go proj 2>/dev/null; echo $status' Enter
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: prompt {{\d+}}> go proj 2>/dev/null; echo $status
+# CHECK: 1
+# CHECK: prompt {{\d+}}>
@@ -0,0 +1,65 @@
+#RUN: %fish %s
+#REQUIRES: command -v tmux
+
+isolated-tmux-start -C '
+    function fish_title
+        echo shell title
+    end
+'
+
+function pane-title
+    isolated-tmux display-message -p '#{pane_title}'
+end
+
+isolated-tmux send-keys 'true' Enter
+tmux-sleep
+pane-title
+# CHECK: shell title
+
+# By default, a foreground job leaves the title alone.
+isolated-tmux send-keys 'sleep 0.5; sleep 1' Enter
+tmux-sleep
+pane-title
+# CHECK: shell title
+
+# The title can be set with fish_job_title while the job runs.
+isolated-tmux send-keys '
+    function fish_job_title
+        echo "running: $argv[1]"
+    end
+' Enter
+isolated-tmux send-keys 'sleep 0.5; sleep 2' Enter
+sleep-until 'pane-title | grep -x "running: sleep 2"'
+pane-title
+# CHECK: running: sleep 2
+
+# The shell's title is restored afterwards.
+sleep-until 'pane-title | grep -x "shell title"'
+pane-title
+# CHECK: shell title
+
+isolated-tmux send-keys 'sleep 2' Enter
+sleep-until 'pane-title | grep -x "running: sleep 2"'
+pane-title
+# CHECK: running: sleep 2
+
+# The title is restored when the job is stopped, and shown again when it is resumed.
+isolated-tmux send-keys C-z
+sleep-until 'pane-title | grep -x "shell title"'
+pane-title
+# CHECK: shell title
+isolated-tmux send-keys 'fg' Enter
+sleep-until 'pane-title | grep -x "running: sleep 2"'
+pane-title
+# CHECK: running: sleep 2
+sleep-until 'pane-title | grep -x "shell title"'
+pane-title
+# CHECK: shell title
+
+# Without a title of the shell's own, which could be restored, the job's title is not shown.
+isolated-tmux send-keys 'function fish_title; end' Enter 'printf "\\e]0;%s\\a" manual' Enter
+sleep-until 'pane-title | grep -x manual'
+isolated-tmux send-keys 'sleep 1' Enter
+tmux-sleep
+pane-title
+# CHECK: manual
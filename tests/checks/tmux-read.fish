@@ -17,3 +17,16 @@ isolated-tmux capture-pane -p
 # CHECK: prompt 0> echo foo
 # CHECK: foo
 # CHECK: prompt 1>
+
+# Interactive read gives up after the timeout, optionally keeping what was typed.
+isolated-tmux send-keys C-l
+tmux-sleep
+isolated-tmux send-keys 'read --timeout 1 --keep-partial -P "t> " r; echo $status $r' Enter
+tmux-sleep
+isolated-tmux send-keys partial
+sleep-until 'isolated-tmux capture-pane -p | grep "^120"'
+isolated-tmux capture-pane -p
+# CHECK: prompt 1> read --timeout 1 --keep-partial -P "t> " r; echo $status $r
+# CHECK: t> partial
+# CHECK: 120 partial
+# CHECK: prompt 2>
@@ -0,0 +1,18 @@
+#RUN: %fish %s
+#REQUIRES: command -v tmux
+
+isolated-tmux-start
+
+# $CMD_RUSAGE has one element per process of the last job, empty for builtins.
+isolated-tmux send-keys 'command true | echo | command cat' Enter
+tmux-sleep
+isolated-tmux send-keys C-l
+tmux-sleep
+isolated-tmux send-keys 'printf "<%s>\n" $CMD_RUSAGE' Enter
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: prompt 1> printf "<%s>\n" $CMD_RUSAGE
+# CHECK: <{{\d+ \d+ [1-9]\d*}}>
+# CHECK: <>
+# CHECK: <{{\d+ \d+ [1-9]\d*}}>
+# CHECK: prompt 2>
@@ -0,0 +1,66 @@
+#RUN: %fish %s
+#REQUIRES: command -v tmux
+
+isolated-tmux-start -C '
+    function fish_prompt
+        printf "prompt-line-1\nprompt-line-2> "
+    end
+    function fish_top_right_prompt
+        echo TOP
+    end
+    function fish_status_line
+        echo "status: $status_text"
+    end
+    set -g status_text initial
+    bind ctrl-g "set -g status_text updated; commandline -f repaint-status-line"
+'
+
+isolated-tmux send-keys 'echo hello'
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: prompt-line-1 {{ *}} TOP
+# CHECK: prompt-line-2> echo hello
+# CHECK: status: initial
+
+isolated-tmux send-keys C-g
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: prompt-line-1 {{ *}} TOP
+# CHECK: prompt-line-2> echo hello
+# CHECK: status: updated
+
+# The status line goes away when executing.
+isolated-tmux send-keys Enter
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: prompt-line-1 {{ *}} TOP
+# CHECK: prompt-line-2> echo hello
+# CHECK: hello
+# CHECK: prompt-line-1 {{ *}} TOP
+# CHECK: prompt-line-2>
+# CHECK: status: updated
+
+# The status line moves down as the command line grows.
+isolated-tmux send-keys C-l 'echo 1' M-Enter 'echo 2'
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: prompt-line-1 {{ *}} TOP
+# CHECK: prompt-line-2> echo 1
+# CHECK:                echo 2
+# CHECK: status: updated
+
+isolated-tmux send-keys C-u C-h 'x'
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: prompt-line-1 {{ *}} TOP
+# CHECK: prompt-line-2> echo 1x
+# CHECK: status: updated
+
+# The top right prompt is hidden if it doesn't fit.
+isolated-tmux resize-window -x 15
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: prompt-line-1
+# CHECK: prompt-line-2>
+# CHECK: echo 1x
+# CHECK: status: updated
@@ -0,0 +1,13 @@
+#RUN: %fish %s
+#REQUIRES: command -v tmux
+
+isolated-tmux-start
+
+isolated-tmux send-keys 'status terminal-report | string match -r "^(?:pri|cur|qui).*"' Enter
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: prompt 0> status terminal-report | string match -r "^(?:pri|cur|qui).*"
+# CHECK: primary-device-attribute{{ +}}answered{{ +}}CSI 0 c
+# CHECK: cursor-position-report{{ +}}yes{{ +}}CSI 6 n
+# CHECK: quirks{{ +}}tmux {{\d+\.\d+}}{{ +}}CSI > 0 q, $MC_SID
+# CHECK: prompt 1>
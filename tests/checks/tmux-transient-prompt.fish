@@ -87,3 +87,51 @@ isolated-tmux capture-pane -p
 # CHECK: foo bar
 # CHECK: transient prompt line
 # CHECK: 1>
+
+# Test the compact form of the prompt, and that the right prompt is updated once the command
+# has finished.
+isolated-tmux send-keys C-u C-l '
+    function fish_transient_left_prompt
+        echo "> "
+    end
+    function fish_transient_right_prompt
+        set -l last_status $status
+        if contains -- --finished $argv
+            echo "[$last_status]"
+        else
+            echo "[running]"
+        end
+    end
+'
+tmux-sleep
+isolated-tmux send-keys C-l 'false' Enter
+tmux-sleep
+isolated-tmux send-keys 'echo foo' Enter
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: > false{{ +}}[1]
+# CHECK: > echo foo{{ +}}[running]
+# CHECK: foo
+# CHECK: transient prompt line
+# CHECK: 1>
+
+# The right prompt is not updated if the command wrote to the terminal, which may have cleared it.
+isolated-tmux send-keys C-l 'clear; seq 3' Enter
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: 1
+# CHECK: 2
+# CHECK: 3
+# CHECK: transient prompt line
+# CHECK: 1>
+
+# The right prompt is not updated while the command is running.
+isolated-tmux send-keys C-l 'sleep 1' Enter
+tmux-sleep
+isolated-tmux capture-pane -p
+# CHECK: > sleep 1{{ +}}[running]
+sleep-until 'isolated-tmux capture-pane -p | grep "\[0\]"'
+isolated-tmux capture-pane -p
+# CHECK: > sleep 1{{ +}}[0]
+# CHECK: transient prompt line
+# CHECK: 1>
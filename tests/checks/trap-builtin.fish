@@ -0,0 +1,74 @@
+# RUN: env fth=%fish_test_helper %fish %s
+#REQUIRES: command -v %fish_test_helper
+
+# A trap runs its command when the signal is received.
+trap 'echo Got USR1' USR1
+kill -USR1 $fish_pid
+sleep .1
+# CHECK: Got USR1
+
+# An empty command ignores the signal, in fish and in the processes it runs.
+trap '' USR1 INT
+kill -USR1 $fish_pid
+kill -INT $fish_pid
+sleep .1
+echo still running
+# CHECK: still running
+sh -c 'kill -INT $$; echo child still running'
+# CHECK: child still running
+
+# Resetting restores the previous behavior, which for SIGINT is fish's own handler.
+trap - INT
+trap 'echo Got INT' INT
+kill -INT $fish_pid
+sleep .1
+# CHECK: Got INT
+trap - INT USR1
+trap -p
+
+# ERR runs whenever a top-level job fails.
+trap 'echo failed with $status' ERR
+false
+# CHECK: failed with 1
+sh -c 'exit 3'
+# CHECK: failed with 3
+true && false
+# CHECK: failed with 1
+false; or true
+false; and true
+set -l var (false)
+# CHECK: failed with 1
+begin
+    false
+end
+# CHECK: failed with 1
+function failing
+    false
+    false
+end
+failing
+# CHECK: failed with 1
+
+# But not for skipped jobs, negated jobs, conditions or failures inside functions.
+false && true
+false || true
+not false
+if false
+end
+while false
+end
+true | false | true
+echo $status
+# CHECK: 0
+
+# The failure status is preserved.
+false
+# CHECK: failed with 1
+echo $status
+# CHECK: 1
+trap - ERR
+
+# EXIT runs before fish exits.
+trap 'echo Exiting with $status' EXIT
+exit 5
+# CHECK: Exiting with 5
@@ -10,19 +10,51 @@ trap -p
 trap "true" SIGTERM kill ExIT INT
 
 trap -p
-# CHECK: # Defined via `source`
-# CHECK: function __trap_handler_EXIT --on-event fish_exit
-# CHECK:  true;
-# CHECK: end
-# CHECK: # Defined via `source`
+# CHECK: trap -- true EXIT
+# CHECK: trap -- true SIGINT
+# CHECK: trap -- true SIGKILL
+# CHECK: trap -- true SIGTERM
+
+# The handlers are functions.
+functions --no-details __trap_handler_INT
 # CHECK: function __trap_handler_INT --on-signal SIGINT
-# CHECK:  true;
-# CHECK: end
-# CHECK: # Defined via `source`
-# CHECK: function __trap_handler_KILL --on-signal SIGKILL
-# CHECK:  true;
-# CHECK: end
-# CHECK: # Defined via `source`
-# CHECK: function __trap_handler_TERM --on-signal SIGTERM
-# CHECK:  true;
+# CHECK:     eval true
 # CHECK: end
+
+# Output can be used to reinstall traps.
+trap 'echo "it\'s $x"' USR1
+trap -- '' 15
+trap -p USR1 TERM
+# CHECK: trap -- 'echo "it\'s $x"' SIGUSR1
+# CHECK: trap -- '' SIGTERM
+set -l saved (trap -p USR1)
+trap - USR1
+trap -p USR1
+eval $saved
+trap -p USR1
+# CHECK: trap -- 'echo "it\'s $x"' SIGUSR1
+
+# Resetting takes a single reason, "-" or a number.
+trap kill
+trap - EXIT
+trap 2 15
+trap -p
+# CHECK: trap -- 'echo "it\'s $x"' SIGUSR1
+
+# Erasing the handler removes the trap.
+functions -e __trap_handler_USR1
+trap -p
+
+trap -p FOO
+# CHECKERR: trap: FOO: invalid signal specification
+echo $status
+# CHECK: 1
+
+trap true ERR DEBUG
+# CHECKERR: trap: DEBUG: invalid signal specification
+trap -p
+# CHECK: trap -- true ERR
+
+trap -l | string match -r '^(?:INT|USR1)$'
+# CHECK: INT
+# CHECK: USR1
@@ -12,7 +12,6 @@ for i in (seq 16)
     set -a pids $last_pid
 end
 
-# Note fish does not (yet) report the exit status of waited-on commands.
 for pid in $pids
     wait $pid
 end
@@ -76,3 +75,106 @@ wait -- -1
 # CHECKERR: wait: Could not find child processes with the name '-1'
 wait -- -(math 2 ^ 31)
 # CHECKERR: wait: Could not find child processes with the name '-2147483648'
+
+echo
+
+# --status returns the exit status of the waited process.
+command true &
+set pid_true $last_pid
+command sh -c 'exit 3' &
+set pid_three $last_pid
+wait --status $pid_true
+echo $status
+# CHECK: 0
+wait --status $pid_three
+echo $status
+# CHECK: 3
+
+# With several processes, the last one given decides.
+command sh -c 'exit 3' &
+set pid_three $last_pid
+command sh -c 'exit 5' &
+set pid_five $last_pid
+wait --status $pid_five $pid_three
+echo $status
+# CHECK: 3
+
+# Without --status, wait still succeeds.
+command false &
+wait $last_pid
+echo $status
+# CHECK: 0
+
+# --print reports the pid and status, including the signal.
+command sh -c 'exit 4' &
+set pid_four $last_pid
+command sh -c 'kill -TERM $$' &
+set pid_term $last_pid
+wait --print $pid_four $pid_term | string replace -r -- '^\d+' pid
+# CHECK: pid	4
+# CHECK: pid	143	SIGTERM
+
+# A specific process of a pipeline can be waited on.
+command sh -c 'exit 6' | command sleep 0.5 &
+set pipeline_pids (jobs --last --pid)
+wait --status --print $pipeline_pids[1] | string replace -r -- '^\d+' pid
+# CHECK: pid	6
+jobs -q; and echo the pipeline is still running
+# CHECK: the pipeline is still running
+wait
+
+# --any waits for a whole job, not just its first process.
+command sleep 0.3 | command true &
+command sleep 5 &
+set pid_sleep $last_pid
+wait --any --print | count
+# CHECK: 2
+jobs --pid
+# CHECK: Process
+# CHECK: {{\d+}}
+kill $pid_sleep
+wait
+
+# --timeout gives up with status 120.
+command sleep 5 &
+set pid_sleep $last_pid
+command sh -c 'exit 7' &
+set pid_seven $last_pid
+sleep 0.1
+wait --timeout 0.3 --print $pid_seven $pid_sleep | string replace -r -- '^\d+' pid
+# CHECK: pid	7
+echo $pipestatus[1]
+# CHECK: 120
+kill $pid_sleep
+wait --timeout 5 --status $pid_sleep
+echo $status
+# CHECK: 143
+
+# With --any, a job whose processes have not all completed is not printed or forgotten on timeout.
+command sh -c 'exit 7' | command sleep 5 &
+set pid_sleep $last_pid
+sleep 0.1
+wait --any --timeout 0.3 --print
+echo $status
+# CHECK: 120
+kill $pid_sleep
+wait --any --print | string replace -r -- '^\d+' pid | sort
+# CHECK: pid	143	SIGTERM
+# CHECK: pid	7
+
+wait --timeout 0.5
+echo $status
+# CHECK: 0
+
+builtin wait --timeout foo
+# CHECKERR: wait: foo: invalid number of seconds
+# CHECKERR: {{.*}}wait.fish (line {{\d+}}):
+# CHECKERR: builtin wait --timeout foo
+# CHECKERR: ^
+# CHECKERR: (Type 'help wait' for related documentation)
+builtin wait --timeout -1
+# CHECKERR: wait: -1: invalid number of seconds
+# CHECKERR: {{.*}}wait.fish (line {{\d+}}):
+# CHECKERR: builtin wait --timeout -1
+# CHECKERR: ^
+# CHECKERR: (Type 'help wait' for related documentation)